@@ -0,0 +1,155 @@
+use time::format_description;
+use time::{OffsetDateTime, UtcOffset};
+
+// iCalendar (RFC 5545) content for booking invites
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalendarMethod {
+    Publish, // Read only calendars, e.g. subscribed feeds
+    Request, // Invites that add or update events
+    Cancel,  // Invites that remove events
+}
+
+impl CalendarMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CalendarMethod::Publish => "PUBLISH",
+            CalendarMethod::Request => "REQUEST",
+            CalendarMethod::Cancel => "CANCEL",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CalendarEvent {
+    pub uid: String,   // Stays the same across updates so clients replace the event
+    pub sequence: i32, // Bumped on every update or cancellation of the event
+    pub start_date: OffsetDateTime,
+    pub end_date: OffsetDateTime, // Exclusive, where the last booked slot ends
+    pub all_day: bool,            // Written as local dates rather than times
+    pub summary: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub organizer_email: Option<String>,
+    pub status: &'static str, // TENTATIVE, CONFIRMED or CANCELLED
+}
+
+#[derive(Debug, Clone)]
+pub struct CalendarInvite {
+    pub method: CalendarMethod,
+    pub ics: String,
+}
+
+impl CalendarInvite {
+    pub fn content_type(&self) -> String {
+        format!(
+            "text/calendar; charset=utf-8; method={}",
+            self.method.as_str()
+        )
+    }
+}
+
+pub fn build_calendar(
+    method: CalendarMethod,
+    name: Option<&str>,
+    events: &[CalendarEvent],
+) -> String {
+    let dtstamp = format_calendar_timestamp(&OffsetDateTime::now_utc());
+
+    let mut lines = vec![
+        String::from("BEGIN:VCALENDAR"),
+        String::from("VERSION:2.0"),
+        String::from("PRODID:-//Rentals//Bookings//EN"),
+        String::from("CALSCALE:GREGORIAN"),
+        format!("METHOD:{}", method.as_str()),
+    ];
+    if let Some(name) = name {
+        lines.push(format!("X-WR-CALNAME:{}", escape_calendar_text(name)));
+    }
+
+    for event in events {
+        lines.push(String::from("BEGIN:VEVENT"));
+        lines.push(format!("UID:{}", event.uid));
+        lines.push(format!("DTSTAMP:{}", dtstamp));
+        lines.push(format!("SEQUENCE:{}", event.sequence));
+        if event.all_day {
+            lines.push(format!(
+                "DTSTART;VALUE=DATE:{}",
+                format_calendar_date(&event.start_date)
+            ));
+            lines.push(format!(
+                "DTEND;VALUE=DATE:{}",
+                format_calendar_date(&event.end_date)
+            ));
+        } else {
+            // Timed events are written in UTC so the calendar needs no VTIMEZONE
+            lines.push(format!(
+                "DTSTART:{}",
+                format_calendar_timestamp(&event.start_date)
+            ));
+            lines.push(format!(
+                "DTEND:{}",
+                format_calendar_timestamp(&event.end_date)
+            ));
+        }
+        lines.push(format!("SUMMARY:{}", escape_calendar_text(&event.summary)));
+        if let Some(description) = &event.description {
+            lines.push(format!("DESCRIPTION:{}", escape_calendar_text(description)));
+        }
+        if let Some(location) = &event.location {
+            lines.push(format!("LOCATION:{}", escape_calendar_text(location)));
+        }
+        if let Some(organizer_email) = &event.organizer_email {
+            lines.push(format!("ORGANIZER:mailto:{}", organizer_email));
+        }
+        lines.push(format!("STATUS:{}", event.status));
+        lines.push(String::from("END:VEVENT"));
+    }
+
+    lines.push(String::from("END:VCALENDAR"));
+
+    lines
+        .iter()
+        .map(|line| fold_calendar_line(line))
+        .collect::<Vec<String>>()
+        .join("")
+}
+
+fn format_calendar_date(date: &OffsetDateTime) -> String {
+    let format =
+        format_description::parse("[year][month][day]").expect("Invalid calendar date format");
+    date.format(&format).unwrap_or_else(|_| date.to_string())
+}
+
+fn format_calendar_timestamp(date: &OffsetDateTime) -> String {
+    let date = date.to_offset(UtcOffset::UTC);
+    let format = format_description::parse("[year][month][day]T[hour][minute][second]Z")
+        .expect("Invalid calendar timestamp format");
+    date.format(&format).unwrap_or_else(|_| date.to_string())
+}
+
+fn escape_calendar_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+// Content lines longer than 75 octets are folded onto continuation lines starting with a space
+fn fold_calendar_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut line_length = 0;
+
+    for c in line.chars() {
+        if line_length + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            line_length = 1;
+        }
+        folded.push(c);
+        line_length += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+
+    folded
+}
@@ -1,312 +1,817 @@
 use crate::routes::auth::credentials::UserEmail;
+use crate::routes::bookings::bookings_calendar::CalendarInvite;
+use crate::routes::bookings::bookings_locales::{
+    format_email_date, format_email_total, translate, BookingLocale,
+};
+use crate::routes::bookings::bookings_model::{
+    Booking, BookingModification, BookingOutboxNotification,
+};
+use crate::routes::rbac::rbac_model::RbacRole;
 use crate::startup::AppState;
-use crate::utilities::email::email::BookingEmailParams;
 use crate::utilities::errors::AppError;
 use anyhow::Context;
 use std::sync::Arc;
+use tera::Tera;
+use time::OffsetDateTime;
+use time_tz::Tz;
+
+// Every email the bookings module sends. Adding a kind only needs a variant here, a
+// template name below and its subject and plain body in the locale catalogs.
+pub enum BookingNotification {
+    // TODO: Not sure that the canceled email is necessary. We can just send a refund email.
+    //  otherwise only the accept/decline emails are sent
+    Canceled {
+        details: BookingEmailDetails,
+        access_link: Option<String>, // Signed link to the booking, for renters who aren't logged in
+        calendar_invite: Option<CalendarInvite>,
+    },
+    Confirmed {
+        details: BookingEmailDetails,
+        access_link: Option<String>,
+        calendar_invite: Option<CalendarInvite>,
+    },
+    Declined {
+        details: BookingEmailDetails,
+        access_link: Option<String>,
+    },
+    Partial {
+        details: BookingEmailDetails,
+        access_link: Option<String>,
+    },
+    Refunded {
+        details: BookingEmailDetails,
+        access_link: Option<String>,
+    },
+    Reminder {
+        details: BookingEmailDetails,
+        access_link: Option<String>,
+        calendar_invite: Option<CalendarInvite>,
+    },
+    ReturnReminder {
+        details: BookingEmailDetails,
+        access_link: Option<String>,
+    },
+    Updated {
+        details: BookingEmailDetails,
+        access_link: Option<String>,
+        calendar_invite: Option<CalendarInvite>,
+    },
+    // Changes the renter requested, which the vendor has to approve
+    ModificationRequested {
+        modification: BookingModification,
+    },
+    // Changes the vendor requested, which the renter has to approve
+    ModificationProposed {
+        details: BookingEmailDetails,
+        access_link: Option<String>,
+        modification: BookingModification,
+    },
+    // The next day's pickups and returns, a booking on a single day is in both
+    PickList {
+        pick_date: OffsetDateTime, // Midnight at the start of the day in the vendor's timezone
+        pickups: Vec<Booking>,
+        returns: Vec<Booking>,
+    },
+    VendorCanceled {
+        booking: Booking,
+    },
+    VendorRequested {
+        booking: Booking,
+    },
+    VendorResponseDeadline {
+        booking: Booking,
+        respond_by: OffsetDateTime,
+    },
+}
 
-// TODO: Hookup canceled email
-// TODO: Not sure that this one is necessary. We can just send a refund email.
-//  otherwise only the accept/decline emails are sent
-#[tracing::instrument(
-    name = "Send a booking canceled email to user",
-    skip(state, user_email, params)
-)]
-pub async fn send_booking_canceled_email(
-    state: Arc<AppState>,
-    user_email: UserEmail,
-    params: BookingEmailParams,
-) -> anyhow::Result<(), AppError> {
-    let base_url = &state.configuration.client.base_url;
-    let enable_emails = &state.configuration.application.enable_emails;
-    let email_client = &state.email_client;
-
-    if !enable_emails {
-        return Ok(());
-    }
+// The transaction's email params carry preformatted dates and total, renter emails only take
+// the confirmation code and rentals from them and format the raw values for the renter's locale
+pub struct BookingEmailDetails {
+    pub confirmation_code: String,
+    pub rentals: serde_json::Value, // The transaction's rentals, as shown by the templates
+    pub locale: Option<String>,     // The renter's preferred locale tag, e.g. "fr"
+    pub start_date: OffsetDateTime, // Earliest start date of the transaction's bookings
+    pub end_date: OffsetDateTime,   // Latest end date of the transaction's bookings
+    pub total: f64,
+}
 
-    let bookings_link = format!("{}/bookings", base_url,);
-
-    let mut tera_context = tera::Context::new();
-    tera_context.insert("bookings_link", bookings_link.as_str());
-    tera_context.insert("confirmation_code", &params.confirmation_code);
-    tera_context.insert("start_date", &params.start_date);
-    tera_context.insert("end_date", &params.end_date);
-    tera_context.insert("total", &params.total);
-    tera_context.insert("rentals", &params.rentals);
-
-    let booking_canceled_template = email_client
-        .tera
-        .render("booking_canceled.html", &tera_context)
-        .context("Failed to parse booking canceled email template")?;
-
-    let plain_body = format!(
-        "Your rental booking has been canceled.\nPlease visit {} for details.",
-        bookings_link
-    );
-
-    email_client
-        .send_email(
-            &user_email,
-            "Your rental booking has been canceled",
-            booking_canceled_template.as_str(),
-            &plain_body,
-        )
-        .await
-        .context("Failed to send a booking canceled email")?;
+pub struct BookingEmailAttachment {
+    pub filename: String,
+    pub content_type: String,
+    pub content: String,
+}
 
-    Ok(())
+pub struct RenderedBookingNotification {
+    pub subject: String,
+    pub html_body: String,
+    pub plain_body: String,
+    pub attachment: Option<BookingEmailAttachment>,
 }
 
-// TODO: Lets generate another token for each email
-// then link to /bookings/{confirmation_code}?token={token}
-// if token exists, then we dont need to validate user is logged in.
-// This way the links always work, regardless if its an anon user or logged in user.
-#[tracing::instrument(
-    name = "Send a booking confirmed email to user",
-    skip(state, user_email, params)
-)]
-pub async fn send_booking_confirmed_email(
-    state: Arc<AppState>,
-    user_email: UserEmail,
-    params: BookingEmailParams,
-) -> anyhow::Result<(), AppError> {
-    let base_url = &state.configuration.client.base_url;
-    let enable_emails = &state.configuration.application.enable_emails;
-    let email_client = &state.email_client;
+impl BookingNotification {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BookingNotification::Canceled { .. } => "booking_canceled",
+            BookingNotification::Confirmed { .. } => "booking_confirmed",
+            BookingNotification::Declined { .. } => "booking_declined",
+            BookingNotification::Partial { .. } => "booking_partial",
+            BookingNotification::Refunded { .. } => "booking_refunded",
+            BookingNotification::Reminder { .. } => "booking_reminder",
+            BookingNotification::ReturnReminder { .. } => "booking_return_reminder",
+            BookingNotification::Updated { .. } => "booking_updated",
+            BookingNotification::ModificationRequested { .. } => "booking_modification_requested",
+            BookingNotification::ModificationProposed { .. } => "booking_modification_proposed",
+            BookingNotification::PickList { .. } => "booking_pick_list",
+            BookingNotification::VendorCanceled { .. } => "booking_vendor_canceled",
+            BookingNotification::VendorRequested { .. } => "booking_vendor_requested",
+            BookingNotification::VendorResponseDeadline { .. } => {
+                "booking_vendor_response_deadline"
+            }
+        }
+    }
 
-    if !enable_emails {
-        return Ok(());
+    // Renters get emails in their preferred locale, vendor emails use the default locale
+    fn locale(&self) -> BookingLocale {
+        match self {
+            BookingNotification::Canceled { details, .. }
+            | BookingNotification::Confirmed { details, .. }
+            | BookingNotification::Declined { details, .. }
+            | BookingNotification::Partial { details, .. }
+            | BookingNotification::Refunded { details, .. }
+            | BookingNotification::Reminder { details, .. }
+            | BookingNotification::ReturnReminder { details, .. }
+            | BookingNotification::Updated { details, .. }
+            | BookingNotification::ModificationProposed { details, .. } => {
+                BookingLocale::parse(details.locale.as_deref())
+            }
+            BookingNotification::ModificationRequested { .. }
+            | BookingNotification::PickList { .. }
+            | BookingNotification::VendorCanceled { .. }
+            | BookingNotification::VendorRequested { .. }
+            | BookingNotification::VendorResponseDeadline { .. } => BookingLocale::default(),
+        }
     }
 
-    let bookings_link = format!("{}/bookings", base_url,);
-
-    let mut tera_context = tera::Context::new();
-    tera_context.insert("bookings_link", &bookings_link);
-    tera_context.insert("confirmation_code", &params.confirmation_code);
-    tera_context.insert("start_date", &params.start_date);
-    tera_context.insert("end_date", &params.end_date);
-    tera_context.insert("total", &params.total);
-    tera_context.insert("rentals", &params.rentals);
-
-    let booking_confirmed_template = email_client
-        .tera
-        .render("booking_confirmed.html", &tera_context)
-        .context("Failed to parse booking confirmed email template")?;
-
-    let plain_body = format!(
-        "Your rental booking has been confirmed.\nPlease visit {} for details.",
-        bookings_link
-    );
-
-    email_client
-        .send_email(
-            &user_email,
-            "Your rental booking has been confirmed",
-            booking_confirmed_template.as_str(),
-            &plain_body,
-        )
-        .await
-        .context("Failed to send a booking confirmed email")?;
+    // Which of the vendor's employees get vendor facing emails, renter emails have none
+    pub fn vendor_roles(&self) -> &'static [RbacRole] {
+        match self {
+            BookingNotification::ModificationRequested { .. }
+            | BookingNotification::VendorCanceled { .. }
+            | BookingNotification::VendorRequested { .. }
+            | BookingNotification::VendorResponseDeadline { .. } => {
+                &[RbacRole::Owner, RbacRole::Admin]
+            }
+            BookingNotification::PickList { .. } => {
+                &[RbacRole::Owner, RbacRole::Admin, RbacRole::Employee]
+            }
+            _ => &[],
+        }
+    }
 
-    Ok(())
+    // Renters can add bookings to their calendar from the invite attached to these emails
+    fn calendar_invite(&self) -> Option<&CalendarInvite> {
+        match self {
+            BookingNotification::Canceled {
+                calendar_invite, ..
+            }
+            | BookingNotification::Confirmed {
+                calendar_invite, ..
+            }
+            | BookingNotification::Reminder {
+                calendar_invite, ..
+            }
+            | BookingNotification::Updated {
+                calendar_invite, ..
+            } => calendar_invite.as_ref(),
+            _ => None,
+        }
+    }
+
+    fn tera_context(&self, locale: BookingLocale, timezone: &Tz, base_url: &str) -> tera::Context {
+        let mut tera_context = tera::Context::new();
+        tera_context.insert("locale", locale.as_str());
+
+        match self {
+            BookingNotification::Canceled {
+                details,
+                access_link,
+                ..
+            }
+            | BookingNotification::Confirmed {
+                details,
+                access_link,
+                ..
+            }
+            | BookingNotification::Declined {
+                details,
+                access_link,
+            }
+            | BookingNotification::Partial {
+                details,
+                access_link,
+            }
+            | BookingNotification::Refunded {
+                details,
+                access_link,
+            }
+            | BookingNotification::Reminder {
+                details,
+                access_link,
+                ..
+            }
+            | BookingNotification::ReturnReminder {
+                details,
+                access_link,
+            }
+            | BookingNotification::Updated {
+                details,
+                access_link,
+                ..
+            } => {
+                let bookings_link = access_link
+                    .clone()
+                    .unwrap_or(format!("{}/bookings", base_url));
+
+                tera_context.insert("bookings_link", bookings_link.as_str());
+                tera_context.insert("confirmation_code", &details.confirmation_code);
+                tera_context.insert(
+                    "start_date",
+                    &format_email_date(&details.start_date, locale, timezone),
+                );
+                tera_context.insert(
+                    "end_date",
+                    &format_email_date(&details.end_date, locale, timezone),
+                );
+                tera_context.insert("total", &format_email_total(details.total, locale));
+                tera_context.insert("rentals", &details.rentals);
+            }
+            BookingNotification::ModificationRequested { modification } => {
+                let booking_link = format!("{}/bookings/{}", base_url, modification.booking_id);
+
+                tera_context.insert("booking_link", booking_link.as_str());
+                tera_context.insert(
+                    "previous_start_date",
+                    &format_email_date(&modification.previous_start_date, locale, timezone),
+                );
+                tera_context.insert(
+                    "previous_end_date",
+                    &format_email_date(&modification.previous_end_date, locale, timezone),
+                );
+                tera_context.insert("previous_quantity", &modification.previous_quantity);
+                tera_context.insert(
+                    "start_date",
+                    &format_email_date(&modification.start_date, locale, timezone),
+                );
+                tera_context.insert(
+                    "end_date",
+                    &format_email_date(&modification.end_date, locale, timezone),
+                );
+                tera_context.insert("quantity", &modification.quantity);
+                tera_context.insert(
+                    "price_delta",
+                    &format_email_total(modification.price_delta, locale),
+                );
+            }
+            BookingNotification::ModificationProposed {
+                details,
+                access_link,
+                modification,
+            } => {
+                let bookings_link = access_link
+                    .clone()
+                    .unwrap_or(format!("{}/bookings", base_url));
+
+                tera_context.insert("bookings_link", bookings_link.as_str());
+                tera_context.insert("confirmation_code", &details.confirmation_code);
+                tera_context.insert(
+                    "previous_start_date",
+                    &format_email_date(&modification.previous_start_date, locale, timezone),
+                );
+                tera_context.insert(
+                    "previous_end_date",
+                    &format_email_date(&modification.previous_end_date, locale, timezone),
+                );
+                tera_context.insert("previous_quantity", &modification.previous_quantity);
+                tera_context.insert(
+                    "start_date",
+                    &format_email_date(&modification.start_date, locale, timezone),
+                );
+                tera_context.insert(
+                    "end_date",
+                    &format_email_date(&modification.end_date, locale, timezone),
+                );
+                tera_context.insert("quantity", &modification.quantity);
+                tera_context.insert(
+                    "price_delta",
+                    &format_email_total(modification.price_delta, locale),
+                );
+            }
+            BookingNotification::PickList {
+                pick_date,
+                pickups,
+                returns,
+            } => {
+                let bookings_link = format!("{}/bookings", base_url);
+
+                tera_context.insert("bookings_link", bookings_link.as_str());
+                tera_context.insert("pick_date", &format_email_date(pick_date, locale, timezone));
+                tera_context.insert("pickups", pickups);
+                tera_context.insert("returns", returns);
+            }
+            // The booking is already canceled, so there is nothing left to accept or decline
+            BookingNotification::VendorCanceled { booking } => {
+                let booking_link = format!("{}/bookings/{}", base_url, booking.booking_id);
+
+                tera_context.insert("booking_link", booking_link.as_str());
+                tera_context.insert(
+                    "start_date",
+                    &format_email_date(&booking.start_date, locale, timezone),
+                );
+                tera_context.insert(
+                    "end_date",
+                    &format_email_date(&booking.end_date, locale, timezone),
+                );
+                tera_context.insert("quantity", &booking.quantity);
+                tera_context.insert("booking", booking);
+            }
+            BookingNotification::VendorRequested { booking } => {
+                let booking_link = format!("{}/bookings/{}", base_url, booking.booking_id);
+
+                tera_context.insert("accept_link", &format!("{}?action=accept", booking_link));
+                tera_context.insert("decline_link", &format!("{}?action=decline", booking_link));
+                tera_context.insert("booking_link", booking_link.as_str());
+                tera_context.insert(
+                    "start_date",
+                    &format_email_date(&booking.start_date, locale, timezone),
+                );
+                tera_context.insert(
+                    "end_date",
+                    &format_email_date(&booking.end_date, locale, timezone),
+                );
+                tera_context.insert("quantity", &booking.quantity);
+                tera_context.insert("total", &format_email_total(booking.total, locale));
+                tera_context.insert("booking", booking);
+            }
+            BookingNotification::VendorResponseDeadline {
+                booking,
+                respond_by,
+            } => {
+                let booking_link = format!("{}/bookings/{}", base_url, booking.booking_id);
+
+                tera_context.insert("accept_link", &format!("{}?action=accept", booking_link));
+                tera_context.insert("decline_link", &format!("{}?action=decline", booking_link));
+                tera_context.insert("booking_link", booking_link.as_str());
+                tera_context.insert(
+                    "respond_by",
+                    &format_email_date(respond_by, locale, timezone),
+                );
+                tera_context.insert(
+                    "start_date",
+                    &format_email_date(&booking.start_date, locale, timezone),
+                );
+                tera_context.insert(
+                    "end_date",
+                    &format_email_date(&booking.end_date, locale, timezone),
+                );
+                tera_context.insert("booking", booking);
+            }
+        }
+
+        tera_context
+    }
 }
 
+/// Renders the subject, HTML and plain text bodies of a notification without sending it.
+pub fn render_booking_notification(
+    notification: &BookingNotification,
+    tera: &Tera,
+    base_url: &str,
+    timezone: &Tz,
+) -> Result<RenderedBookingNotification, AppError> {
+    let locale = notification.locale();
+    let tera_context = notification.tera_context(locale, timezone, base_url);
+
+    // Localized templates like booking_confirmed.fr.html are optional, the default is used otherwise
+    let localized_template = format!("{}.{}.html", notification.name(), locale.as_str());
+    let template = if tera
+        .get_template_names()
+        .any(|name| name == localized_template)
+    {
+        localized_template
+    } else {
+        format!("{}.html", notification.name())
+    };
+
+    let html_body = tera.render(&template, &tera_context).context(format!(
+        "Failed to parse {} email template",
+        notification.name()
+    ))?;
+    let plain_body = Tera::one_off(
+        translate(locale, &format!("{}.plain", notification.name())),
+        &tera_context,
+        false,
+    )
+    .context(format!(
+        "Failed to parse {} plain email template",
+        notification.name()
+    ))?;
+
+    let attachment = notification
+        .calendar_invite()
+        .map(|invite| BookingEmailAttachment {
+            filename: String::from("booking.ics"),
+            content_type: invite.content_type(),
+            content: invite.ics.clone(),
+        });
+
+    Ok(RenderedBookingNotification {
+        subject: String::from(translate(
+            locale,
+            &format!("{}.subject", notification.name()),
+        )),
+        html_body,
+        plain_body,
+        attachment,
+    })
+}
+
+// Only called by the outbox delivery job, everything else queues notifications
 #[tracing::instrument(
-    name = "Send a booking declined email to user",
-    skip(state, user_email, params)
+    name = "Send a booking notification",
+    skip(state, notification),
+    fields(notification_id = %notification.notification_id)
 )]
-pub async fn send_booking_declined_email(
+pub async fn send_booking_notification(
     state: Arc<AppState>,
-    user_email: UserEmail,
-    params: BookingEmailParams,
+    notification: &BookingOutboxNotification,
 ) -> anyhow::Result<(), AppError> {
-    let base_url = &state.configuration.client.base_url;
-    let enable_emails = &state.configuration.application.enable_emails;
     let email_client = &state.email_client;
 
-    if !enable_emails {
-        return Ok(());
+    let recipient =
+        UserEmail::parse(notification.recipient.clone()).map_err(AppError::ValidationError)?;
+
+    match (
+        &notification.attachment_filename,
+        &notification.attachment_content_type,
+        &notification.attachment_content,
+    ) {
+        (Some(filename), Some(content_type), Some(content)) => {
+            email_client
+                .send_email_with_attachment(
+                    &recipient,
+                    &notification.subject,
+                    &notification.html_body,
+                    &notification.plain_body,
+                    filename,
+                    content_type,
+                    content.as_bytes(),
+                )
+                .await
+        }
+        _ => {
+            email_client
+                .send_email(
+                    &recipient,
+                    &notification.subject,
+                    &notification.html_body,
+                    &notification.plain_body,
+                )
+                .await
+        }
     }
-
-    let bookings_link = format!("{}/bookings", base_url,);
-
-    let mut tera_context = tera::Context::new();
-    tera_context.insert("bookings_link", bookings_link.as_str());
-    tera_context.insert("confirmation_code", &params.confirmation_code);
-    tera_context.insert("start_date", &params.start_date);
-    tera_context.insert("end_date", &params.end_date);
-    tera_context.insert("total", &params.total);
-    tera_context.insert("rentals", &params.rentals);
-
-    let booking_declined_template = email_client
-        .tera
-        .render("booking_declined.html", &tera_context)
-        .context("Failed to parse booking declined email template")?;
-
-    let plain_body = format!(
-        "Your rental booking has been declined.\nPlease visit {} for details.",
-        bookings_link
-    );
-
-    email_client
-        .send_email(
-            &user_email,
-            "Your rental booking has been declined",
-            booking_declined_template.as_str(),
-            &plain_body,
-        )
-        .await
-        .context("Failed to send a booking declined email")?;
+    .context(format!(
+        "Failed to send a {} email",
+        notification.notification
+    ))?;
 
     Ok(())
 }
 
-#[tracing::instrument(
-    name = "Send a booking partial email to user",
-    skip(state, user_email, params)
-)]
-pub async fn send_booking_partial_email(
-    state: Arc<AppState>,
-    user_email: UserEmail,
-    params: BookingEmailParams,
-) -> anyhow::Result<(), AppError> {
-    let base_url = &state.configuration.client.base_url;
-    let enable_emails = &state.configuration.application.enable_emails;
-    let email_client = &state.email_client;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routes::bookings::bookings_calendar::CalendarMethod;
+    use crate::routes::bookings::bookings_model::{
+        BookingModificationStatus, BookingParty, BookingStatus,
+    };
+    use time_tz::timezones;
+    use uuid::Uuid;
+
+    const BASE_URL: &str = "https://example.com";
+    const ACCESS_LINK: &str = "https://example.com/bookings/access/token";
+
+    fn date(unix_timestamp: i64) -> OffsetDateTime {
+        OffsetDateTime::from_unix_timestamp(unix_timestamp).unwrap()
+    }
 
-    if !enable_emails {
-        return Ok(());
+    fn start_date() -> OffsetDateTime {
+        date(1_710_082_800) // March 10, 2024 15:00 UTC
     }
 
-    let bookings_link = format!("{}/bookings", base_url,);
-
-    let mut tera_context = tera::Context::new();
-    tera_context.insert("bookings_link", bookings_link.as_str());
-    tera_context.insert("confirmation_code", &params.confirmation_code);
-    tera_context.insert("start_date", &params.start_date);
-    tera_context.insert("end_date", &params.end_date);
-    tera_context.insert("total", &params.total);
-    tera_context.insert("rentals", &params.rentals);
-
-    let booking_partial_template = email_client
-        .tera
-        .render("booking_partial.html", &tera_context)
-        .context("Failed to parse booking partial email template")?;
-
-    let plain_body = format!("At least one of the items in your latest request were declined.\nPlease visit {} to confirm or deny you
-        would like to continue with a partial booking.", bookings_link);
-
-    email_client
-        .send_email(
-            &user_email,
-            "Would you like to accept a partial booking?",
-            booking_partial_template.as_str(),
-            &plain_body,
-        )
-        .await
-        .context("Failed to send a booking partial email")?;
+    fn end_date() -> OffsetDateTime {
+        date(1_710_255_600) // March 12, 2024 15:00 UTC
+    }
 
-    Ok(())
-}
+    fn booking_id() -> Uuid {
+        Uuid::from_u128(1)
+    }
 
-#[tracing::instrument(
-    name = "Send a booking refunded email to user",
-    skip(state, user_email, params)
-)]
-pub async fn send_booking_refunded_email(
-    state: Arc<AppState>,
-    user_email: UserEmail,
-    params: BookingEmailParams,
-) -> anyhow::Result<(), AppError> {
-    let base_url = &state.configuration.client.base_url;
-    let enable_emails = &state.configuration.application.enable_emails;
-    let email_client = &state.email_client;
+    fn details(locale: Option<&str>) -> BookingEmailDetails {
+        BookingEmailDetails {
+            confirmation_code: String::from("ABC123"),
+            rentals: serde_json::json!([{ "name": "Kayak" }]),
+            locale: locale.map(String::from),
+            start_date: start_date(),
+            end_date: end_date(),
+            total: 1234.5,
+        }
+    }
 
-    if !enable_emails {
-        return Ok(());
+    fn booking() -> Booking {
+        Booking {
+            booking_id: booking_id(),
+            created_at: start_date(),
+            updated_at: start_date(),
+            transaction_id: Uuid::from_u128(2),
+            rental_id: Uuid::from_u128(3),
+            vendor_id: Uuid::from_u128(4),
+            pricing_id: None,
+            quantity: 2,
+            start_date: start_date(),
+            end_date: end_date(),
+            booking_status: BookingStatus::Requested,
+            total: 1234.5,
+            rental: None,
+            available: None,
+        }
     }
 
-    let bookings_link = format!("{}/bookings", base_url);
-
-    let mut tera_context = tera::Context::new();
-    tera_context.insert("bookings_link", bookings_link.as_str());
-    tera_context.insert("confirmation_code", &params.confirmation_code);
-    tera_context.insert("start_date", &params.start_date);
-    tera_context.insert("end_date", &params.end_date);
-    tera_context.insert("total", &params.total);
-    tera_context.insert("rentals", &params.rentals);
-
-    let booking_refunded_template = email_client
-        .tera
-        .render("booking_refunded.html", &tera_context)
-        .context("Failed to parse booking refunded email template")?;
-
-    let plain_body = format!(
-        "Your rental booking has been refunded.\nPlease visit {} for details.",
-        bookings_link
-    );
-
-    email_client
-        .send_email(
-            &user_email,
-            "Your rental booking has been refunded",
-            booking_refunded_template.as_str(),
-            &plain_body,
-        )
-        .await
-        .context("Failed to send a booking refunded email")?;
+    fn modification() -> BookingModification {
+        BookingModification {
+            modification_id: Uuid::from_u128(5),
+            created_at: start_date(),
+            updated_at: start_date(),
+            booking_id: booking_id(),
+            requested_by: Uuid::from_u128(6),
+            requested_by_party: BookingParty::Renter,
+            previous_quantity: 2,
+            previous_start_date: start_date(),
+            previous_end_date: end_date(),
+            previous_total: 1234.5,
+            quantity: 3,
+            start_date: start_date(),
+            end_date: end_date(),
+            total: 1851.75,
+            price_delta: 617.25,
+            modification_status: BookingModificationStatus::Pending,
+            resolved_by: None,
+            resolved_at: None,
+        }
+    }
 
-    Ok(())
-}
+    fn invite() -> CalendarInvite {
+        CalendarInvite {
+            method: CalendarMethod::Request,
+            ics: String::from("BEGIN:VCALENDAR\r\nEND:VCALENDAR\r\n"),
+        }
+    }
 
-// TODO: Hookup reminder email
-#[tracing::instrument(
-    name = "Send a booking reminder email to user",
-    skip(state, user_email, params)
-)]
-pub async fn send_booking_reminder_email(
-    state: Arc<AppState>,
-    user_email: UserEmail,
-    params: BookingEmailParams,
-) -> anyhow::Result<(), AppError> {
-    let base_url = &state.configuration.client.base_url;
-    let enable_emails = &state.configuration.application.enable_emails;
-    let email_client = &state.email_client;
+    // Every template renders its own name and the locale, so tests can tell which was picked
+    fn tera() -> Tera {
+        let mut tera = Tera::default();
+        for name in [
+            "booking_canceled",
+            "booking_confirmed",
+            "booking_declined",
+            "booking_partial",
+            "booking_refunded",
+            "booking_reminder",
+            "booking_return_reminder",
+            "booking_updated",
+            "booking_modification_requested",
+            "booking_modification_proposed",
+            "booking_pick_list",
+            "booking_vendor_canceled",
+            "booking_vendor_requested",
+            "booking_vendor_response_deadline",
+            "booking_confirmed.fr",
+        ] {
+            let template = format!("{}.html", name);
+            tera.add_raw_template(&template, &format!("{} {{{{ locale }}}}", template))
+                .unwrap();
+        }
+        tera
+    }
 
-    if !enable_emails {
-        return Ok(());
+    fn render(notification: &BookingNotification, timezone: &Tz) -> RenderedBookingNotification {
+        render_booking_notification(notification, &tera(), BASE_URL, timezone).unwrap()
     }
 
-    let bookings_link = format!("{}/bookings", base_url,);
-
-    let mut tera_context = tera::Context::new();
-    tera_context.insert("bookings_link", bookings_link.as_str());
-    tera_context.insert("confirmation_code", &params.confirmation_code);
-    tera_context.insert("start_date", &params.start_date);
-    tera_context.insert("end_date", &params.end_date);
-    tera_context.insert("total", &params.total);
-    tera_context.insert("rentals", &params.rentals);
-
-    let booking_reminder_template = email_client
-        .tera
-        .render("booking_reminder.html", &tera_context)
-        .context("Failed to parse booking reminder email template")?;
-
-    let plain_body = format!(
-        "Your rental booking is coming up soon.\nPlease visit {} for details.",
-        bookings_link
-    );
-
-    email_client
-        .send_email(
-            &user_email,
-            "Your upcoming rental booking details",
-            booking_reminder_template.as_str(),
-            &plain_body,
-        )
-        .await
-        .context("Failed to send a booking reminder email")?;
+    #[test]
+    fn renders_every_notification() {
+        let booking_link = format!("{}/bookings/{}", BASE_URL, booking_id());
+        let cases = [
+            (
+                BookingNotification::Canceled {
+                    details: details(None),
+                    access_link: None,
+                    calendar_invite: None,
+                },
+                "Your rental booking has been canceled",
+                "Your rental booking has been canceled.\nPlease visit https://example.com/bookings for details.".to_string(),
+            ),
+            (
+                BookingNotification::Confirmed {
+                    details: details(None),
+                    access_link: Some(String::from(ACCESS_LINK)),
+                    calendar_invite: None,
+                },
+                "Your rental booking has been confirmed",
+                format!("Your rental booking has been confirmed.\nPlease visit {} for details.", ACCESS_LINK),
+            ),
+            (
+                BookingNotification::Declined {
+                    details: details(None),
+                    access_link: None,
+                },
+                "Your rental booking has been declined",
+                "Your rental booking has been declined.\nPlease visit https://example.com/bookings for details.".to_string(),
+            ),
+            (
+                BookingNotification::Partial {
+                    details: details(None),
+                    access_link: Some(String::from(ACCESS_LINK)),
+                },
+                "Would you like to accept a partial booking?",
+                format!("At least one of the items in your latest request were declined.\nPlease visit {} to confirm or deny you would like to continue with a partial booking.", ACCESS_LINK),
+            ),
+            (
+                BookingNotification::Refunded {
+                    details: details(None),
+                    access_link: None,
+                },
+                "Your rental booking has been refunded",
+                "Your rental booking has been refunded.\nPlease visit https://example.com/bookings for details.".to_string(),
+            ),
+            (
+                BookingNotification::Reminder {
+                    details: details(None),
+                    access_link: None,
+                    calendar_invite: None,
+                },
+                "Your upcoming rental booking details",
+                "Your rental booking is coming up soon.\nPlease visit https://example.com/bookings for details.".to_string(),
+            ),
+            (
+                BookingNotification::ReturnReminder {
+                    details: details(None),
+                    access_link: None,
+                },
+                "Your rental booking is ending soon",
+                "Your rental booking is ending soon, please get ready to return your rentals.\nPlease visit https://example.com/bookings for details.".to_string(),
+            ),
+            (
+                BookingNotification::Updated {
+                    details: details(None),
+                    access_link: None,
+                    calendar_invite: None,
+                },
+                "Your rental booking has been updated",
+                "Your rental booking has been updated.\nPlease visit https://example.com/bookings for details.".to_string(),
+            ),
+            (
+                BookingNotification::ModificationRequested {
+                    modification: modification(),
+                },
+                "A renter has requested changes to their booking",
+                format!("A renter has requested changes to their booking.\nPlease visit {} to approve or reject the changes.", booking_link),
+            ),
+            (
+                BookingNotification::ModificationProposed {
+                    details: details(None),
+                    access_link: Some(String::from(ACCESS_LINK)),
+                    modification: modification(),
+                },
+                "Your vendor has proposed changes to your booking",
+                format!("Your vendor has proposed changes to your booking, which would run from March 10, 2024 to March 12, 2024 with a price change of $617.25.\nPlease visit {} to approve or reject the changes.", ACCESS_LINK),
+            ),
+            (
+                BookingNotification::PickList {
+                    pick_date: date(1_710_028_800), // March 10, 2024 00:00 UTC
+                    pickups: vec![booking()],
+                    returns: Vec::new(),
+                },
+                "Your pickups and returns for tomorrow",
+                "You have 1 pickups and 0 returns on March 10, 2024.\nPlease visit https://example.com/bookings for details.".to_string(),
+            ),
+            (
+                BookingNotification::VendorCanceled { booking: booking() },
+                "A renter has canceled their booking",
+                format!("A renter has canceled their booking from March 10, 2024 to March 12, 2024.\nPlease visit {} for details.", booking_link),
+            ),
+            (
+                BookingNotification::VendorRequested { booking: booking() },
+                "You have a new booking request",
+                format!("A renter has requested a booking from March 10, 2024 to March 12, 2024.\nAccept: {0}?action=accept\nDecline: {0}?action=decline", booking_link),
+            ),
+            (
+                BookingNotification::VendorResponseDeadline {
+                    booking: booking(),
+                    respond_by: date(1_709_996_400), // March 9, 2024 15:00 UTC
+                },
+                "A booking request is about to expire",
+                format!("A booking request from March 10, 2024 to March 12, 2024 expires on March 9, 2024 unless you answer it.\nAccept: {0}?action=accept\nDecline: {0}?action=decline", booking_link),
+            ),
+        ];
+
+        for (notification, subject, plain_body) in cases {
+            let rendered = render(&notification, timezones::db::UTC);
+
+            assert_eq!(rendered.subject, subject, "{}", notification.name());
+            assert_eq!(
+                rendered.html_body,
+                format!("{}.html en", notification.name())
+            );
+            assert_eq!(rendered.plain_body, plain_body, "{}", notification.name());
+            assert!(rendered.attachment.is_none());
+        }
+    }
 
-    Ok(())
+    #[test]
+    fn renders_in_the_renter_locale() {
+        let rendered = render(
+            &BookingNotification::Confirmed {
+                details: details(Some("fr-CA")),
+                access_link: None,
+                calendar_invite: None,
+            },
+            timezones::db::UTC,
+        );
+
+        assert_eq!(
+            rendered.subject,
+            "Votre réservation de location a été confirmée"
+        );
+        assert_eq!(rendered.html_body, "booking_confirmed.fr.html fr");
+        assert_eq!(
+            rendered.plain_body,
+            "Votre réservation de location a été confirmée.\nConsultez https://example.com/bookings pour plus de détails."
+        );
+    }
+
+    #[test]
+    fn falls_back_to_the_default_template() {
+        // There is no booking_canceled.fr.html, but the subject is still translated
+        let rendered = render(
+            &BookingNotification::Canceled {
+                details: details(Some("fr")),
+                access_link: None,
+                calendar_invite: None,
+            },
+            timezones::db::UTC,
+        );
+
+        assert_eq!(
+            rendered.subject,
+            "Votre réservation de location a été annulée"
+        );
+        assert_eq!(rendered.html_body, "booking_canceled.html fr");
+    }
+
+    #[test]
+    fn shows_the_pick_date_in_the_vendor_timezone() {
+        let rendered = render(
+            &BookingNotification::PickList {
+                pick_date: date(1_710_082_800), // Midnight on March 11, 2024 in Tokyo
+                pickups: Vec::new(),
+                returns: vec![booking()],
+            },
+            timezones::db::asia::TOKYO,
+        );
+
+        assert_eq!(
+            rendered.plain_body,
+            "You have 0 pickups and 1 returns on March 11, 2024.\nPlease visit https://example.com/bookings for details."
+        );
+    }
+
+    #[test]
+    fn attaches_the_calendar_invite() {
+        let rendered = render(
+            &BookingNotification::Confirmed {
+                details: details(None),
+                access_link: None,
+                calendar_invite: Some(invite()),
+            },
+            timezones::db::UTC,
+        );
+
+        let attachment = rendered.attachment.unwrap();
+        assert_eq!(attachment.filename, "booking.ics");
+        assert_eq!(
+            attachment.content_type,
+            "text/calendar; charset=utf-8; method=REQUEST"
+        );
+        assert_eq!(attachment.content, invite().ics);
+    }
 }
@@ -0,0 +1,217 @@
+use crate::routes::bookings::bookings_model::{
+    Booking, BookingActor, BookingModification, BookingParty, BookingStatus,
+};
+use crate::routes::bookings::bookings_service::{
+    queue_booking_cancellation_notifications, queue_booking_modification_requested_notification,
+    queue_booking_updated_notification, queue_booking_webhook_event,
+};
+use crate::routes::bookings::bookings_utils::webhook_event_for_status;
+use crate::startup::AppState;
+use crate::utilities::database::db_executor::DbExecutor;
+use crate::utilities::errors::AppError;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use uuid::Uuid;
+
+// Domain events emitted by the bookings service. They're written to the booking_events table in
+// the same SQL transaction as the change and dispatched to subscribers by a booking job, so
+// subscribers never see changes that were rolled back.
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "event_type", rename_all = "snake_case")]
+pub enum BookingEvent {
+    Created {
+        booking: Booking,
+    },
+    StatusChanged {
+        booking: Booking,
+        previous_status: BookingStatus,
+        actor: BookingActor,
+    },
+    // Published next to the StatusChanged event of a cancellation, with the side that canceled
+    Canceled {
+        booking: Booking,
+        previous_status: BookingStatus,
+        canceled_by: BookingParty,
+    },
+    ModificationRequested {
+        modification: BookingModification,
+    },
+    Modified {
+        booking: Booking,
+        modification: BookingModification,
+    },
+    ModificationRejected {
+        modification: BookingModification,
+    },
+    HoldExpired {
+        booking_hold_id: Uuid,
+        transaction_id: Uuid,
+    },
+}
+
+impl BookingEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BookingEvent::Created { .. } => "created",
+            BookingEvent::StatusChanged { .. } => "status_changed",
+            BookingEvent::Canceled { .. } => "canceled",
+            BookingEvent::ModificationRequested { .. } => "modification_requested",
+            BookingEvent::Modified { .. } => "modified",
+            BookingEvent::ModificationRejected { .. } => "modification_rejected",
+            BookingEvent::HoldExpired { .. } => "hold_expired",
+        }
+    }
+
+    pub fn booking_id(&self) -> Option<Uuid> {
+        match self {
+            BookingEvent::Created { booking }
+            | BookingEvent::StatusChanged { booking, .. }
+            | BookingEvent::Canceled { booking, .. }
+            | BookingEvent::Modified { booking, .. } => Some(booking.booking_id),
+            BookingEvent::ModificationRequested { modification }
+            | BookingEvent::ModificationRejected { modification } => Some(modification.booking_id),
+            BookingEvent::HoldExpired { .. } => None,
+        }
+    }
+}
+
+// Each event is dispatched in its own SQL transaction. A failing subscriber rolls back the event
+// and it's retried with backoff, with the booking's later events waiting behind it, until it's
+// dead lettered after too many attempts. Subscribers should only write to the database, e.g. into
+// an outbox, rather than call out to other services.
+#[async_trait]
+pub trait BookingEventSubscriber: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    async fn handle(
+        &self,
+        event: &BookingEvent,
+        state: Arc<AppState>,
+        executor: &mut DbExecutor<'_>,
+    ) -> Result<(), AppError>;
+}
+
+#[derive(Clone)]
+pub struct BookingEventBus {
+    subscribers: Vec<Arc<dyn BookingEventSubscriber>>,
+}
+
+impl Default for BookingEventBus {
+    fn default() -> Self {
+        Self {
+            subscribers: vec![
+                Arc::new(BookingWebhookSubscriber),
+                Arc::new(BookingEmailSubscriber),
+            ],
+        }
+    }
+}
+
+impl BookingEventBus {
+    pub fn subscribe(mut self, subscriber: Arc<dyn BookingEventSubscriber>) -> Self {
+        if !self
+            .subscribers
+            .iter()
+            .any(|existing| existing.name() == subscriber.name())
+        {
+            self.subscribers.push(subscriber);
+        }
+        self
+    }
+
+    #[tracing::instrument(name = "Dispatch booking event", skip(self, state, executor))]
+    pub async fn dispatch(
+        &self,
+        event: &BookingEvent,
+        state: Arc<AppState>,
+        executor: &mut DbExecutor<'_>,
+    ) -> Result<(), AppError> {
+        for subscriber in self.subscribers.iter() {
+            if let Err(e) = subscriber.handle(event, state.clone(), executor).await {
+                tracing::error!(
+                    "Booking event subscriber {} failed to handle {}: {:?}",
+                    subscriber.name(),
+                    event.name(),
+                    e
+                );
+                return Err(e);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Queues webhook deliveries for bookings that are created or change status
+pub struct BookingWebhookSubscriber;
+
+#[async_trait]
+impl BookingEventSubscriber for BookingWebhookSubscriber {
+    fn name(&self) -> &'static str {
+        "webhooks"
+    }
+
+    async fn handle(
+        &self,
+        event: &BookingEvent,
+        _state: Arc<AppState>,
+        executor: &mut DbExecutor<'_>,
+    ) -> Result<(), AppError> {
+        let booking = match event {
+            BookingEvent::Created { booking } | BookingEvent::StatusChanged { booking, .. } => {
+                booking
+            }
+            _ => return Ok(()),
+        };
+
+        if let Some(webhook_event) = webhook_event_for_status(&booking.booking_status) {
+            queue_booking_webhook_event(booking, &webhook_event, executor).await?;
+        }
+
+        Ok(())
+    }
+}
+
+// Queues the renter and vendor emails for cancellations and modifications
+pub struct BookingEmailSubscriber;
+
+#[async_trait]
+impl BookingEventSubscriber for BookingEmailSubscriber {
+    fn name(&self) -> &'static str {
+        "emails"
+    }
+
+    async fn handle(
+        &self,
+        event: &BookingEvent,
+        state: Arc<AppState>,
+        executor: &mut DbExecutor<'_>,
+    ) -> Result<(), AppError> {
+        match event {
+            BookingEvent::Canceled {
+                booking,
+                previous_status,
+                canceled_by,
+            } => {
+                queue_booking_cancellation_notifications(
+                    booking,
+                    previous_status,
+                    canceled_by,
+                    state,
+                    executor,
+                )
+                .await
+            }
+            BookingEvent::ModificationRequested { modification } => {
+                queue_booking_modification_requested_notification(modification, state, executor)
+                    .await
+            }
+            BookingEvent::Modified { booking, .. } => {
+                queue_booking_updated_notification(booking, state, executor).await
+            }
+            _ => Ok(()),
+        }
+    }
+}
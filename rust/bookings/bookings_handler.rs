@@ -1,16 +1,46 @@
 use crate::routes::booking_holds::booking_holds_model::BookingHoldStatus;
 use crate::routes::bookings::bookings_model::{
-    Availabilities, Availability, Booking, BookingStatus, GetAvailabilitiesQuery,
-    GetAvailabilityQuery,
+    Availabilities, Availability, AvailabilityRange, Booking, BookingAccessQuery, BookingActor,
+    BookingBlackout, BookingCalendarFeed, BookingCalendarFeedLink, BookingDispute,
+    BookingEmailPreferences, BookingGranularity, BookingHoldExpiry, BookingImportReport,
+    BookingJobRun, BookingModification, BookingOperatorRole, BookingOutboxNotification,
+    BookingRentalSettings, BookingStatus, BookingStatusEvent, BookingVendorSettings,
+    BookingWebhook, BookingWebhookDelivery, CanceledBooking, CancellationPolicy,
+    CreateBookingBlackout, CreateBookingWebhook, DisputeBooking, GetAvailabilitiesQuery,
+    GetAvailabilityQuery, GetBookingCalendarFeedLinkQuery, ImportBookingsQuery, ModifyBooking,
+    PartialBooking, ReassignBooking, ResolveBookingDispute, UpdateBookingEmailPreferences,
+    UpdateBookingRentalSettings, UpdateBookingVendorSettings, UpsertCancellationPolicy,
 };
 use crate::routes::bookings::bookings_service::{
-    accept_booking, cancel_booking, check_availability, complete_booking, decline_booking,
-    get_availabilities, get_availability, get_booking_by_booking_id,
+    abandon_partial_booking, accept_booking, approve_booking_modification,
+    build_booking_calendar_feed, cancel_booking, check_availability, complete_booking,
+    confirm_partial_booking, create_booking_blackout, create_booking_webhook, decline_booking,
+    delete_booking_blackout, delete_booking_webhook, dispute_booking, extend_booking_hold,
+    get_availabilities, get_availability, get_availability_ranges,
+    get_booking_blackout_by_blackout_id, get_booking_blackouts_by_vendor_id,
+    get_booking_by_booking_id, get_booking_calendar_feed_etag, get_booking_calendar_feed_link,
+    get_booking_dispute_by_booking_id, get_booking_email_preferences_by_user_id,
+    get_booking_hold_expiry, get_booking_job_runs, get_booking_modification_by_modification_id,
+    get_booking_modifications_by_booking_id, get_booking_rental_settings_by_rental_id,
+    get_booking_status_history_by_booking_id, get_booking_vendor_settings_by_vendor_id,
+    get_booking_webhook_by_webhook_id, get_booking_webhook_deliveries,
+    get_booking_webhooks_by_vendor_id, get_cancellation_policy_by_vendor_id,
+    get_dead_booking_notifications, get_partial_booking, get_rental_booking_timezone,
+    import_bookings, modify_booking, ping_booking_webhook, queue_booking_webhook_ping,
+    reassign_booking, reject_booking_modification, replay_dead_booking_notification,
+    resolve_booking_dispute, revoke_booking_access_tokens, revoke_booking_calendar_feed_tokens,
+    rotate_booking_webhook_secret, update_booking_email_preferences,
+    update_booking_rental_settings, update_booking_vendor_settings, upsert_cancellation_policy,
+    verify_booking_calendar_feed_token,
+};
+use crate::routes::bookings::bookings_utils::{
+    align_to_booking_granularity, parse_calendar_feed_file, validate_booking_status_transition,
+    verify_booking_access, verify_booking_operator_session, verify_booking_party,
 };
-use crate::routes::bookings::bookings_utils::validate_booking_status_transition;
 use crate::routes::rbac::rbac_service::{
     verify_rbac_user_employee_session, verify_rbac_user_session,
 };
+use crate::routes::rentals::rentals_service::get_rental_by_rental_id;
 use crate::routes::transactions::transactions_service::get_transaction_by_transaction_id;
 use crate::session::UserSession;
 use crate::startup::AppState;
@@ -19,7 +49,10 @@ use crate::utilities::errors::AppError;
 use crate::utilities::extractors::query::SerdeQsQuery;
 use anyhow::{Context, Result};
 use axum::extract::Path;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
 use axum::{extract, Json};
+use secrecy::ExposeSecret;
 use std::sync::Arc;
 use time::OffsetDateTime;
 use uuid::Uuid;
@@ -53,7 +86,10 @@ pub async fn handle_accept_booking(
     };
     check_availability(booking.quantity, availability_query, &mut executor).await?;
 
-    let booking = accept_booking(&booking_id, state, &mut executor).await?;
+    let actor = BookingActor::User {
+        user_id: session.id()?.expect("User id not found in session"),
+    };
+    let booking = accept_booking(&booking_id, &actor, state, &mut executor).await?;
 
     executor
         .commit()
@@ -80,7 +116,10 @@ pub async fn handle_decline_booking(
     verify_rbac_user_employee_session(&session, &booking.vendor_id, &mut executor).await?;
     validate_booking_status_transition(booking.booking_status, BookingStatus::Declined)?;
 
-    let booking = decline_booking(&booking_id, state, &mut executor).await?;
+    let actor = BookingActor::User {
+        user_id: session.id()?.expect("User id not found in session"),
+    };
+    let booking = decline_booking(&booking_id, &actor, state, &mut executor).await?;
 
     executor
         .commit()
@@ -90,12 +129,13 @@ pub async fn handle_decline_booking(
     Ok(Json(booking))
 }
 
-#[tracing::instrument(name = "Cancel booking handler", skip(session, state))]
+#[tracing::instrument(name = "Cancel booking handler", skip(session, access, state))]
 pub async fn handle_cancel_booking(
-    session: UserSession,
+    session: Option<UserSession>,
     booking_id: Path<Uuid>,
+    extract::Query(access): extract::Query<BookingAccessQuery>,
     extract::State(state): extract::State<Arc<AppState>>,
-) -> Result<Json<Booking>, AppError> {
+) -> Result<Json<CanceledBooking>, AppError> {
     let transaction = state
         .db_pool
         .begin()
@@ -104,22 +144,18 @@ pub async fn handle_cancel_booking(
     let mut executor = DbExecutor::Transaction(transaction);
 
     let booking = get_booking_by_booking_id(&booking_id, &mut executor).await?;
-
-    let is_employee =
-        verify_rbac_user_employee_session(&session, &booking.vendor_id, &mut executor).await;
-    match is_employee {
-        Ok(_) => {}
-        Err(_) => {
-            let transaction =
-                get_transaction_by_transaction_id(&booking.transaction_id, &mut executor).await?;
-            let user_id = &transaction.user_id.expect("Missing user id in transaction");
-            verify_rbac_user_session(&session, user_id).await?;
-        }
-    }
+    let (canceled_by, actor) = verify_booking_access(
+        session.as_ref(),
+        access.token.as_deref(),
+        &booking,
+        state.clone(),
+        &mut executor,
+    )
+    .await?;
 
     validate_booking_status_transition(booking.booking_status, BookingStatus::Canceled)?;
 
-    let booking = cancel_booking(booking, state, &mut executor).await?;
+    let booking = cancel_booking(booking, &canceled_by, &actor, state, &mut executor).await?;
 
     executor
         .commit()
@@ -146,14 +182,20 @@ pub async fn handle_complete_booking(
     verify_rbac_user_employee_session(&session, &booking.vendor_id, &mut executor).await?;
     validate_booking_status_transition(booking.booking_status, BookingStatus::Completed)?;
 
-    let current_utc_date = OffsetDateTime::now_utc();
-    if booking.end_date >= current_utc_date {
+    // Bookings can be completed from the start of their last day in the rental's timezone
+    let timezone = get_rental_booking_timezone(&booking.rental_id, &mut executor).await?;
+    let last_day =
+        align_to_booking_granularity(booking.end_date, &BookingGranularity::Day, timezone);
+    if last_day > OffsetDateTime::now_utc() {
         return Err(AppError::ValidationError(String::from(
             "Booking cannot be completed before its end date.",
         )));
     }
 
-    let booking = complete_booking(booking, state.clone(), &mut executor).await?;
+    let actor = BookingActor::User {
+        user_id: session.id()?.expect("User id not found in session"),
+    };
+    let booking = complete_booking(booking, &actor, state.clone(), &mut executor).await?;
 
     executor
         .commit()
@@ -163,76 +205,1078 @@ pub async fn handle_complete_booking(
     Ok(Json(booking))
 }
 
-// #[tracing::instrument(name = "Get all bookings by query handler", skip(session, state))]
-// pub async fn handle_get_bookings_by_query(
-//     session: UserSession,
-//     extract::Query(query_params): extract::Query<GetBookingsQuery>,
-//     extract::State(state): extract::State<Arc<AppState>>,
-// ) -> Result<Json<Vec<Booking>>, AppError> {
-//     let user_id = &session.id()?.expect("User id not found in session");
-//     let mut executor = DbExecutor::Pool(&state.db_pool);
-//     verify_get_bookings_rbac(user_id, &query_params, &mut executor).await?;
-//
-//     let bookings = get_bookings_by_query(&query_params, &mut executor).await?;
-//
-//     Ok(Json(bookings))
-// }
+#[tracing::instrument(name = "Modify booking handler", skip(session, state))]
+pub async fn handle_modify_booking(
+    session: UserSession,
+    booking_id: Path<Uuid>,
+    extract::State(state): extract::State<Arc<AppState>>,
+    Json(modification): Json<ModifyBooking>,
+) -> Result<Json<BookingModification>, AppError> {
+    let transaction = state
+        .db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+    let mut executor = DbExecutor::Transaction(transaction);
 
-#[tracing::instrument(name = "Handle get availability", skip(state))]
-pub async fn handle_get_availability(
-    extract::Query(query_params): extract::Query<GetAvailabilityQuery>,
+    let booking = get_booking_by_booking_id(&booking_id, &mut executor).await?;
+    let party = verify_booking_party(&session, &booking, &mut executor).await?;
+
+    let user_id = &session.id()?.expect("User id not found in session");
+    let modification =
+        modify_booking(booking, modification, user_id, &party, state, &mut executor).await?;
+
+    executor
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to modify booking.")?;
+
+    Ok(Json(modification))
+}
+
+#[tracing::instrument(name = "Get booking modifications handler", skip(session, state))]
+pub async fn handle_get_booking_modifications(
+    session: UserSession,
+    booking_id: Path<Uuid>,
     extract::State(state): extract::State<Arc<AppState>>,
-) -> Result<Json<Vec<Availability>>, AppError> {
+) -> Result<Json<Vec<BookingModification>>, AppError> {
     let mut executor = DbExecutor::Pool(&state.db_pool);
-    let availability = get_availability(query_params, &mut executor).await?;
+    let booking = get_booking_by_booking_id(&booking_id, &mut executor).await?;
+    verify_booking_party(&session, &booking, &mut executor).await?;
 
-    Ok(Json(availability))
+    let modifications =
+        get_booking_modifications_by_booking_id(&booking.booking_id, &mut executor).await?;
+
+    Ok(Json(modifications))
 }
 
-#[tracing::instrument(name = "Handle get availabilities", skip(state))]
-pub async fn handle_get_availabilities(
-    SerdeQsQuery(query_params): SerdeQsQuery<GetAvailabilitiesQuery>,
+#[tracing::instrument(name = "Approve booking modification handler", skip(session, state))]
+pub async fn handle_approve_booking_modification(
+    session: UserSession,
+    Path((booking_id, modification_id)): Path<(Uuid, Uuid)>,
     extract::State(state): extract::State<Arc<AppState>>,
-) -> Result<Json<Availabilities>, AppError> {
-    let mut executor = DbExecutor::Pool(&state.db_pool);
-    let query_params = query_params.expect("Failed to deserialize query params");
-    let availabilities = get_availabilities(query_params, &mut executor).await?;
+) -> Result<Json<BookingModification>, AppError> {
+    let transaction = state
+        .db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+    let mut executor = DbExecutor::Transaction(transaction);
 
-    Ok(Json(availabilities))
+    // Either party can approve, as long as it's not the one that requested the modification
+    let booking = get_booking_by_booking_id(&booking_id, &mut executor).await?;
+    let party = verify_booking_party(&session, &booking, &mut executor).await?;
+
+    let modification =
+        get_booking_modification_by_modification_id(&booking_id, &modification_id, &mut executor)
+            .await?;
+    let user_id = &session.id()?.expect("User id not found in session");
+    let modification =
+        approve_booking_modification(booking, modification, user_id, &party, state, &mut executor)
+            .await?;
+
+    executor
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to approve booking modification.")?;
+
+    Ok(Json(modification))
 }
 
-#[tracing::instrument(name = "Handle check availability", skip(state))]
-pub async fn handle_check_availability(
-    Path(quantity): Path<i32>,
-    extract::Query(query_params): extract::Query<GetAvailabilityQuery>,
+#[tracing::instrument(name = "Reject booking modification handler", skip(session, state))]
+pub async fn handle_reject_booking_modification(
+    session: UserSession,
+    Path((booking_id, modification_id)): Path<(Uuid, Uuid)>,
     extract::State(state): extract::State<Arc<AppState>>,
-) -> Result<Json<Vec<Availability>>, AppError> {
-    let mut executor = DbExecutor::Pool(&state.db_pool);
-    let availability = check_availability(quantity, query_params, &mut executor).await?;
+) -> Result<Json<BookingModification>, AppError> {
+    let transaction = state
+        .db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+    let mut executor = DbExecutor::Transaction(transaction);
 
-    Ok(Json(availability))
+    // The requesting party can withdraw its own modification, the other party can reject it
+    let booking = get_booking_by_booking_id(&booking_id, &mut executor).await?;
+    verify_booking_party(&session, &booking, &mut executor).await?;
+
+    let modification =
+        get_booking_modification_by_modification_id(&booking_id, &modification_id, &mut executor)
+            .await?;
+    let user_id = &session.id()?.expect("User id not found in session");
+    let modification = reject_booking_modification(modification, user_id, &mut executor).await?;
+
+    executor
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to reject booking modification.")?;
+
+    Ok(Json(modification))
 }
 
-#[tracing::instrument(name = "Get booking handler", skip(session, state))]
-pub async fn handle_get_booking(
+#[tracing::instrument(name = "Get booking history handler", skip(session, state))]
+pub async fn handle_get_booking_history(
     session: UserSession,
     booking_id: Path<Uuid>,
     extract::State(state): extract::State<Arc<AppState>>,
-) -> Result<Json<Booking>, AppError> {
+) -> Result<Json<Vec<BookingStatusEvent>>, AppError> {
     let mut executor = DbExecutor::Pool(&state.db_pool);
     let booking = get_booking_by_booking_id(&booking_id, &mut executor).await?;
+    verify_booking_party(&session, &booking, &mut executor).await?;
+
+    let history =
+        get_booking_status_history_by_booking_id(&booking.booking_id, &mut executor).await?;
+
+    Ok(Json(history))
+}
+
+#[tracing::instrument(name = "Dispute booking handler", skip(session, state))]
+pub async fn handle_dispute_booking(
+    session: UserSession,
+    booking_id: Path<Uuid>,
+    extract::State(state): extract::State<Arc<AppState>>,
+    Json(dispute): Json<DisputeBooking>,
+) -> Result<Json<BookingDispute>, AppError> {
+    let transaction = state
+        .db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+    let mut executor = DbExecutor::Transaction(transaction);
+
+    let booking = get_booking_by_booking_id(&booking_id, &mut executor).await?;
+    let party = verify_booking_party(&session, &booking, &mut executor).await?;
+    validate_booking_status_transition(booking.booking_status, BookingStatus::Disputed)?;
+
+    let user_id = &session.id()?.expect("User id not found in session");
+    let dispute = dispute_booking(booking, user_id, &party, dispute, &mut executor).await?;
+
+    executor
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to dispute booking.")?;
+
+    Ok(Json(dispute))
+}
+
+#[tracing::instrument(name = "Resolve booking dispute handler", skip(session, state))]
+pub async fn handle_resolve_booking_dispute(
+    session: UserSession,
+    booking_id: Path<Uuid>,
+    extract::State(state): extract::State<Arc<AppState>>,
+    Json(resolution): Json<ResolveBookingDispute>,
+) -> Result<Json<Booking>, AppError> {
+    let transaction = state
+        .db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+    let mut executor = DbExecutor::Transaction(transaction);
+
+    // Neither party gets to settle their own dispute, only support staff can
+    let user_id = verify_booking_operator_session(
+        &session,
+        &[BookingOperatorRole::Support, BookingOperatorRole::Admin],
+        &mut executor,
+    )
+    .await?;
 
-    let is_employee =
-        verify_rbac_user_employee_session(&session, &booking.vendor_id, &mut executor).await;
-    match is_employee {
-        Ok(_) => {}
-        Err(_) => {
-            let transaction =
-                get_transaction_by_transaction_id(&booking.transaction_id, &mut executor).await?;
-            let user_id = &transaction.user_id.expect("Missing user id in transaction");
-            verify_rbac_user_session(&session, user_id).await?;
-        }
+    let booking = get_booking_by_booking_id(&booking_id, &mut executor).await?;
+    if booking.booking_status != BookingStatus::Disputed {
+        return Err(AppError::ValidationError(String::from(
+            "Only disputed bookings can be resolved.",
+        )));
     }
 
+    let booking =
+        resolve_booking_dispute(booking, &user_id, resolution, state, &mut executor).await?;
+
+    executor
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to resolve booking dispute.")?;
+
     Ok(Json(booking))
 }
+
+#[tracing::instrument(name = "Get booking dispute handler", skip(session, state))]
+pub async fn handle_get_booking_dispute(
+    session: UserSession,
+    booking_id: Path<Uuid>,
+    extract::State(state): extract::State<Arc<AppState>>,
+) -> Result<Json<BookingDispute>, AppError> {
+    let mut executor = DbExecutor::Pool(&state.db_pool);
+    let booking = get_booking_by_booking_id(&booking_id, &mut executor).await?;
+    verify_booking_party(&session, &booking, &mut executor).await?;
+
+    let dispute = get_booking_dispute_by_booking_id(&booking.booking_id, &mut executor).await?;
+
+    Ok(Json(dispute))
+}
+
+#[tracing::instrument(name = "Get partial booking handler", skip(session, state))]
+pub async fn handle_get_partial_booking(
+    session: UserSession,
+    transaction_id: Path<Uuid>,
+    extract::State(state): extract::State<Arc<AppState>>,
+) -> Result<Json<PartialBooking>, AppError> {
+    let mut executor = DbExecutor::Pool(&state.db_pool);
+
+    let transaction = get_transaction_by_transaction_id(&transaction_id, &mut executor).await?;
+    let user_id = &transaction
+        .user_id
+        .ok_or(AppError::DoesNotExistError(String::from(
+            "Transaction has no user",
+        )))?;
+    verify_rbac_user_session(&session, user_id).await?;
+
+    let partial_booking = get_partial_booking(&transaction_id, &mut executor).await?;
+
+    Ok(Json(partial_booking))
+}
+
+#[tracing::instrument(name = "Confirm partial booking handler", skip(session, state))]
+pub async fn handle_confirm_partial_booking(
+    session: UserSession,
+    transaction_id: Path<Uuid>,
+    extract::State(state): extract::State<Arc<AppState>>,
+) -> Result<Json<Vec<Booking>>, AppError> {
+    let transaction = state
+        .db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+    let mut executor = DbExecutor::Transaction(transaction);
+
+    let transaction = get_transaction_by_transaction_id(&transaction_id, &mut executor).await?;
+    let user_id = &transaction
+        .user_id
+        .ok_or(AppError::DoesNotExistError(String::from(
+            "Transaction has no user",
+        )))?;
+    verify_rbac_user_session(&session, user_id).await?;
+
+    let partial_booking = get_partial_booking(&transaction_id, &mut executor).await?;
+    let actor = BookingActor::User { user_id: *user_id };
+    let bookings = confirm_partial_booking(partial_booking, &actor, &mut executor).await?;
+
+    executor
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to confirm partial booking.")?;
+
+    Ok(Json(bookings))
+}
+
+#[tracing::instrument(name = "Abandon partial booking handler", skip(session, state))]
+pub async fn handle_abandon_partial_booking(
+    session: UserSession,
+    transaction_id: Path<Uuid>,
+    extract::State(state): extract::State<Arc<AppState>>,
+) -> Result<Json<Vec<Booking>>, AppError> {
+    let transaction = state
+        .db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+    let mut executor = DbExecutor::Transaction(transaction);
+
+    let transaction = get_transaction_by_transaction_id(&transaction_id, &mut executor).await?;
+    let user_id = &transaction
+        .user_id
+        .ok_or(AppError::DoesNotExistError(String::from(
+            "Transaction has no user",
+        )))?;
+    verify_rbac_user_session(&session, user_id).await?;
+
+    let partial_booking = get_partial_booking(&transaction_id, &mut executor).await?;
+    let actor = BookingActor::User { user_id: *user_id };
+    let bookings = abandon_partial_booking(partial_booking, &actor, state, &mut executor).await?;
+
+    executor
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to abandon partial booking.")?;
+
+    Ok(Json(bookings))
+}
+
+#[tracing::instrument(name = "Get booking vendor settings handler", skip(session, state))]
+pub async fn handle_get_booking_vendor_settings(
+    session: UserSession,
+    vendor_id: Path<Uuid>,
+    extract::State(state): extract::State<Arc<AppState>>,
+) -> Result<Json<BookingVendorSettings>, AppError> {
+    let mut executor = DbExecutor::Pool(&state.db_pool);
+    verify_rbac_user_employee_session(&session, &vendor_id, &mut executor).await?;
+
+    let settings = get_booking_vendor_settings_by_vendor_id(&vendor_id, &mut executor).await?;
+
+    Ok(Json(settings))
+}
+
+#[tracing::instrument(name = "Update booking vendor settings handler", skip(session, state))]
+pub async fn handle_update_booking_vendor_settings(
+    session: UserSession,
+    vendor_id: Path<Uuid>,
+    extract::State(state): extract::State<Arc<AppState>>,
+    Json(update): Json<UpdateBookingVendorSettings>,
+) -> Result<Json<BookingVendorSettings>, AppError> {
+    let transaction = state
+        .db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+    let mut executor = DbExecutor::Transaction(transaction);
+
+    verify_rbac_user_employee_session(&session, &vendor_id, &mut executor).await?;
+
+    let settings = update_booking_vendor_settings(&vendor_id, update, &mut executor).await?;
+
+    executor
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to update booking vendor settings.")?;
+
+    Ok(Json(settings))
+}
+
+#[tracing::instrument(name = "Get booking email preferences handler", skip(session, state))]
+pub async fn handle_get_booking_email_preferences(
+    session: UserSession,
+    extract::State(state): extract::State<Arc<AppState>>,
+) -> Result<Json<BookingEmailPreferences>, AppError> {
+    let user_id = session.id()?.expect("User id not found in session");
+
+    let mut executor = DbExecutor::Pool(&state.db_pool);
+    let preferences = get_booking_email_preferences_by_user_id(&user_id, &mut executor).await?;
+
+    Ok(Json(preferences))
+}
+
+#[tracing::instrument(
+    name = "Update booking email preferences handler",
+    skip(session, state)
+)]
+pub async fn handle_update_booking_email_preferences(
+    session: UserSession,
+    extract::State(state): extract::State<Arc<AppState>>,
+    Json(update): Json<UpdateBookingEmailPreferences>,
+) -> Result<Json<BookingEmailPreferences>, AppError> {
+    let user_id = session.id()?.expect("User id not found in session");
+
+    let transaction = state
+        .db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+    let mut executor = DbExecutor::Transaction(transaction);
+
+    let preferences = update_booking_email_preferences(&user_id, update, &mut executor).await?;
+
+    executor
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to update booking email preferences.")?;
+
+    Ok(Json(preferences))
+}
+
+// Renters need the granularity to offer the right slots, so it's readable without a session
+#[tracing::instrument(name = "Get booking rental settings handler", skip(state))]
+pub async fn handle_get_booking_rental_settings(
+    rental_id: Path<Uuid>,
+    extract::State(state): extract::State<Arc<AppState>>,
+) -> Result<Json<BookingRentalSettings>, AppError> {
+    let mut executor = DbExecutor::Pool(&state.db_pool);
+
+    let settings = get_booking_rental_settings_by_rental_id(&rental_id, &mut executor).await?;
+
+    Ok(Json(settings))
+}
+
+#[tracing::instrument(name = "Update booking rental settings handler", skip(session, state))]
+pub async fn handle_update_booking_rental_settings(
+    session: UserSession,
+    rental_id: Path<Uuid>,
+    extract::State(state): extract::State<Arc<AppState>>,
+    Json(update): Json<UpdateBookingRentalSettings>,
+) -> Result<Json<BookingRentalSettings>, AppError> {
+    let transaction = state
+        .db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+    let mut executor = DbExecutor::Transaction(transaction);
+
+    let rental = get_rental_by_rental_id(&rental_id, &mut executor).await?;
+    verify_rbac_user_employee_session(&session, &rental.vendor_id, &mut executor).await?;
+
+    let settings = update_booking_rental_settings(&rental_id, update, &mut executor).await?;
+
+    executor
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to update booking rental settings.")?;
+
+    Ok(Json(settings))
+}
+
+#[tracing::instrument(name = "Get cancellation policy handler", skip(state))]
+pub async fn handle_get_cancellation_policy(
+    vendor_id: Path<Uuid>,
+    extract::State(state): extract::State<Arc<AppState>>,
+) -> Result<Json<CancellationPolicy>, AppError> {
+    let mut executor = DbExecutor::Pool(&state.db_pool);
+
+    let policy = get_cancellation_policy_by_vendor_id(&vendor_id, &mut executor)
+        .await?
+        .ok_or(AppError::DoesNotExistError(String::from(
+            "Cancellation policy not found",
+        )))?;
+
+    Ok(Json(policy))
+}
+
+#[tracing::instrument(name = "Upsert cancellation policy handler", skip(session, state))]
+pub async fn handle_upsert_cancellation_policy(
+    session: UserSession,
+    vendor_id: Path<Uuid>,
+    extract::State(state): extract::State<Arc<AppState>>,
+    Json(policy): Json<UpsertCancellationPolicy>,
+) -> Result<Json<CancellationPolicy>, AppError> {
+    let transaction = state
+        .db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+    let mut executor = DbExecutor::Transaction(transaction);
+
+    verify_rbac_user_employee_session(&session, &vendor_id, &mut executor).await?;
+
+    let policy = upsert_cancellation_policy(&vendor_id, policy, &mut executor).await?;
+
+    executor
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to upsert cancellation policy.")?;
+
+    Ok(Json(policy))
+}
+
+#[tracing::instrument(name = "Get booking job runs handler", skip(session, state))]
+pub async fn handle_get_booking_job_runs(
+    session: UserSession,
+    extract::State(state): extract::State<Arc<AppState>>,
+) -> Result<Json<Vec<BookingJobRun>>, AppError> {
+    let mut executor = DbExecutor::Pool(&state.db_pool);
+    verify_booking_operator_session(&session, &[BookingOperatorRole::Admin], &mut executor).await?;
+
+    let job_runs = get_booking_job_runs(&mut executor).await?;
+
+    Ok(Json(job_runs))
+}
+
+// #[tracing::instrument(name = "Get all bookings by query handler", skip(session, state))]
+// pub async fn handle_get_bookings_by_query(
+//     session: UserSession,
+//     extract::Query(query_params): extract::Query<GetBookingsQuery>,
+//     extract::State(state): extract::State<Arc<AppState>>,
+// ) -> Result<Json<Vec<Booking>>, AppError> {
+//     let user_id = &session.id()?.expect("User id not found in session");
+//     let mut executor = DbExecutor::Pool(&state.db_pool);
+//     verify_get_bookings_rbac(user_id, &query_params, &mut executor).await?;
+//
+//     let bookings = get_bookings_by_query(&query_params, &mut executor).await?;
+//
+//     Ok(Json(bookings))
+// }
+
+#[tracing::instrument(name = "Handle get availability", skip(state))]
+pub async fn handle_get_availability(
+    extract::Query(query_params): extract::Query<GetAvailabilityQuery>,
+    extract::State(state): extract::State<Arc<AppState>>,
+) -> Result<Json<Vec<Availability>>, AppError> {
+    let mut executor = DbExecutor::Pool(&state.db_pool);
+    let availability = get_availability(query_params, &mut executor).await?;
+
+    Ok(Json(availability))
+}
+
+#[tracing::instrument(name = "Handle get availability ranges", skip(state))]
+pub async fn handle_get_availability_ranges(
+    extract::Query(query_params): extract::Query<GetAvailabilityQuery>,
+    extract::State(state): extract::State<Arc<AppState>>,
+) -> Result<Json<Vec<AvailabilityRange>>, AppError> {
+    let mut executor = DbExecutor::Pool(&state.db_pool);
+    let ranges = get_availability_ranges(query_params, &mut executor).await?;
+
+    Ok(Json(ranges))
+}
+
+#[tracing::instrument(name = "Handle get availabilities", skip(state))]
+pub async fn handle_get_availabilities(
+    SerdeQsQuery(query_params): SerdeQsQuery<GetAvailabilitiesQuery>,
+    extract::State(state): extract::State<Arc<AppState>>,
+) -> Result<Json<Availabilities>, AppError> {
+    let mut executor = DbExecutor::Pool(&state.db_pool);
+    let query_params = query_params.expect("Failed to deserialize query params");
+    let availabilities = get_availabilities(query_params, &mut executor).await?;
+
+    Ok(Json(availabilities))
+}
+
+#[tracing::instrument(name = "Handle check availability", skip(state))]
+pub async fn handle_check_availability(
+    Path(quantity): Path<i32>,
+    extract::Query(query_params): extract::Query<GetAvailabilityQuery>,
+    extract::State(state): extract::State<Arc<AppState>>,
+) -> Result<Json<Vec<Availability>>, AppError> {
+    let mut executor = DbExecutor::Pool(&state.db_pool);
+    let availability = check_availability(quantity, query_params, &mut executor).await?;
+
+    Ok(Json(availability))
+}
+
+#[tracing::instrument(name = "Get booking handler", skip(session, access, state))]
+pub async fn handle_get_booking(
+    session: Option<UserSession>,
+    booking_id: Path<Uuid>,
+    extract::Query(access): extract::Query<BookingAccessQuery>,
+    extract::State(state): extract::State<Arc<AppState>>,
+) -> Result<Json<Booking>, AppError> {
+    let mut executor = DbExecutor::Pool(&state.db_pool);
+    let booking = get_booking_by_booking_id(&booking_id, &mut executor).await?;
+
+    verify_booking_access(
+        session.as_ref(),
+        access.token.as_deref(),
+        &booking,
+        state.clone(),
+        &mut executor,
+    )
+    .await?;
+
+    Ok(Json(booking))
+}
+
+#[tracing::instrument(name = "Reassign booking handler", skip(session, state))]
+pub async fn handle_reassign_booking(
+    session: UserSession,
+    booking_id: Path<Uuid>,
+    extract::State(state): extract::State<Arc<AppState>>,
+    Json(reassignment): Json<ReassignBooking>,
+) -> Result<Json<Booking>, AppError> {
+    let transaction = state
+        .db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+    let mut executor = DbExecutor::Transaction(transaction);
+
+    let booking = get_booking_by_booking_id(&booking_id, &mut executor).await?;
+    verify_rbac_user_employee_session(&session, &booking.vendor_id, &mut executor).await?;
+
+    // Fails if the transaction the booking is moved to doesn't exist
+    get_transaction_by_transaction_id(&reassignment.transaction_id, &mut executor).await?;
+    let booking = reassign_booking(&booking, &reassignment.transaction_id, &mut executor).await?;
+
+    executor
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to reassign booking.")?;
+
+    Ok(Json(booking))
+}
+
+#[tracing::instrument(name = "Revoke booking access tokens handler", skip(session, state))]
+pub async fn handle_revoke_booking_access_tokens(
+    session: UserSession,
+    booking_id: Path<Uuid>,
+    extract::State(state): extract::State<Arc<AppState>>,
+) -> Result<Json<u64>, AppError> {
+    let transaction = state
+        .db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+    let mut executor = DbExecutor::Transaction(transaction);
+
+    let booking = get_booking_by_booking_id(&booking_id, &mut executor).await?;
+    verify_booking_party(&session, &booking, &mut executor).await?;
+
+    let revoked = revoke_booking_access_tokens(&booking.booking_id, &mut executor).await?;
+
+    executor
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to revoke booking access tokens.")?;
+
+    Ok(Json(revoked))
+}
+
+#[tracing::instrument(name = "Get booking hold expiry handler", skip(session, state))]
+pub async fn handle_get_booking_hold_expiry(
+    session: UserSession,
+    booking_hold_id: Path<Uuid>,
+    extract::State(state): extract::State<Arc<AppState>>,
+) -> Result<Json<BookingHoldExpiry>, AppError> {
+    let mut executor = DbExecutor::Pool(&state.db_pool);
+    let expiry = get_booking_hold_expiry(&booking_hold_id, &mut executor).await?;
+
+    let transaction =
+        get_transaction_by_transaction_id(&expiry.transaction_id, &mut executor).await?;
+    let user_id = &transaction
+        .user_id
+        .ok_or(AppError::DoesNotExistError(String::from(
+            "Transaction has no user",
+        )))?;
+    verify_rbac_user_session(&session, user_id).await?;
+
+    Ok(Json(expiry))
+}
+
+#[tracing::instrument(name = "Extend booking hold handler", skip(session, state))]
+pub async fn handle_extend_booking_hold(
+    session: UserSession,
+    booking_hold_id: Path<Uuid>,
+    extract::State(state): extract::State<Arc<AppState>>,
+) -> Result<Json<BookingHoldExpiry>, AppError> {
+    let transaction = state
+        .db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+    let mut executor = DbExecutor::Transaction(transaction);
+
+    // Only the renter on the checkout page can extend their hold
+    let expiry = get_booking_hold_expiry(&booking_hold_id, &mut executor).await?;
+    let transaction =
+        get_transaction_by_transaction_id(&expiry.transaction_id, &mut executor).await?;
+    let user_id = &transaction
+        .user_id
+        .ok_or(AppError::DoesNotExistError(String::from(
+            "Transaction has no user",
+        )))?;
+    verify_rbac_user_session(&session, user_id).await?;
+
+    let expiry = extend_booking_hold(&booking_hold_id, &mut executor).await?;
+
+    executor
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to extend a booking hold.")?;
+
+    Ok(Json(expiry))
+}
+
+#[tracing::instrument(name = "Get dead booking notifications handler", skip(session, state))]
+pub async fn handle_get_dead_booking_notifications(
+    session: UserSession,
+    extract::State(state): extract::State<Arc<AppState>>,
+) -> Result<Json<Vec<BookingOutboxNotification>>, AppError> {
+    let mut executor = DbExecutor::Pool(&state.db_pool);
+    verify_booking_operator_session(&session, &[BookingOperatorRole::Admin], &mut executor).await?;
+
+    let notifications = get_dead_booking_notifications(&mut executor).await?;
+
+    Ok(Json(notifications))
+}
+
+#[tracing::instrument(
+    name = "Replay dead booking notification handler",
+    skip(session, state)
+)]
+pub async fn handle_replay_dead_booking_notification(
+    session: UserSession,
+    notification_id: Path<Uuid>,
+    extract::State(state): extract::State<Arc<AppState>>,
+) -> Result<(), AppError> {
+    let transaction = state
+        .db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+    let mut executor = DbExecutor::Transaction(transaction);
+
+    verify_booking_operator_session(&session, &[BookingOperatorRole::Admin], &mut executor).await?;
+
+    replay_dead_booking_notification(&notification_id, &mut executor).await?;
+
+    executor
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to replay a booking notification.")?;
+
+    Ok(())
+}
+
+#[tracing::instrument(name = "Get booking calendar feed link handler", skip(session, state))]
+pub async fn handle_get_booking_calendar_feed_link(
+    session: UserSession,
+    vendor_id: Path<Uuid>,
+    extract::Query(query_params): extract::Query<GetBookingCalendarFeedLinkQuery>,
+    extract::State(state): extract::State<Arc<AppState>>,
+) -> Result<Json<BookingCalendarFeedLink>, AppError> {
+    let transaction = state
+        .db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+    let mut executor = DbExecutor::Transaction(transaction);
+
+    verify_rbac_user_employee_session(&session, &vendor_id, &mut executor).await?;
+
+    let feed = BookingCalendarFeed {
+        vendor_id: *vendor_id,
+        rental_id: query_params.rental_id,
+    };
+    let link = get_booking_calendar_feed_link(&feed, state.clone(), &mut executor).await?;
+
+    executor
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to get a booking calendar feed link.")?;
+
+    Ok(Json(link))
+}
+
+#[tracing::instrument(
+    name = "Revoke booking calendar feed tokens handler",
+    skip(session, state)
+)]
+pub async fn handle_revoke_booking_calendar_feed_tokens(
+    session: UserSession,
+    vendor_id: Path<Uuid>,
+    extract::Query(query_params): extract::Query<GetBookingCalendarFeedLinkQuery>,
+    extract::State(state): extract::State<Arc<AppState>>,
+) -> Result<Json<u64>, AppError> {
+    let transaction = state
+        .db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+    let mut executor = DbExecutor::Transaction(transaction);
+
+    verify_rbac_user_employee_session(&session, &vendor_id, &mut executor).await?;
+
+    let feed = BookingCalendarFeed {
+        vendor_id: *vendor_id,
+        rental_id: query_params.rental_id,
+    };
+    let revoked = revoke_booking_calendar_feed_tokens(&feed, &mut executor).await?;
+
+    executor
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to revoke booking calendar feed tokens.")?;
+
+    Ok(Json(revoked))
+}
+
+#[tracing::instrument(name = "Import bookings handler", skip(session, state, content))]
+pub async fn handle_import_bookings(
+    session: UserSession,
+    vendor_id: Path<Uuid>,
+    extract::Query(query_params): extract::Query<ImportBookingsQuery>,
+    extract::State(state): extract::State<Arc<AppState>>,
+    content: String,
+) -> Result<Json<BookingImportReport>, AppError> {
+    let transaction = state
+        .db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+    let mut executor = DbExecutor::Transaction(transaction);
+
+    verify_rbac_user_employee_session(&session, &vendor_id, &mut executor).await?;
+
+    let report = import_bookings(&vendor_id, &query_params, &content, &mut executor).await?;
+
+    // Dry runs drop the transaction so nothing they created is kept
+    if !report.dry_run {
+        executor
+            .commit()
+            .await
+            .context("Failed to commit SQL transaction to import bookings.")?;
+    }
+
+    Ok(Json(report))
+}
+
+#[tracing::instrument(
+    name = "Get vendor booking calendar feed handler",
+    skip(headers, access, state)
+)]
+pub async fn handle_get_vendor_booking_calendar_feed(
+    Path(file): Path<String>,
+    headers: HeaderMap,
+    extract::Query(access): extract::Query<BookingAccessQuery>,
+    extract::State(state): extract::State<Arc<AppState>>,
+) -> Result<Response, AppError> {
+    let feed = BookingCalendarFeed {
+        vendor_id: parse_calendar_feed_file(&file)?,
+        rental_id: None,
+    };
+
+    booking_calendar_feed_response(feed, headers, access, state).await
+}
+
+#[tracing::instrument(
+    name = "Get rental booking calendar feed handler",
+    skip(headers, access, state)
+)]
+pub async fn handle_get_rental_booking_calendar_feed(
+    Path((vendor_id, file)): Path<(Uuid, String)>,
+    headers: HeaderMap,
+    extract::Query(access): extract::Query<BookingAccessQuery>,
+    extract::State(state): extract::State<Arc<AppState>>,
+) -> Result<Response, AppError> {
+    let feed = BookingCalendarFeed {
+        vendor_id,
+        rental_id: Some(parse_calendar_feed_file(&file)?),
+    };
+
+    booking_calendar_feed_response(feed, headers, access, state).await
+}
+
+async fn booking_calendar_feed_response(
+    feed: BookingCalendarFeed,
+    headers: HeaderMap,
+    access: BookingAccessQuery,
+    state: Arc<AppState>,
+) -> Result<Response, AppError> {
+    let token = access.token.ok_or(AppError::ValidationError(String::from(
+        "Calendar feed token is required",
+    )))?;
+
+    let mut executor = DbExecutor::Pool(&state.db_pool);
+    verify_booking_calendar_feed_token(&token, &feed, state.clone(), &mut executor).await?;
+
+    // Calendar clients poll often, skip building the feed when nothing changed
+    let etag = get_booking_calendar_feed_etag(&feed, &mut executor).await?;
+    let if_none_match = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok());
+    if if_none_match == Some(etag.as_str()) {
+        return Ok((StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response());
+    }
+
+    let calendar = build_booking_calendar_feed(&feed, &mut executor).await?;
+
+    Ok((
+        [
+            (
+                header::CONTENT_TYPE,
+                String::from("text/calendar; charset=utf-8"),
+            ),
+            (header::ETAG, etag),
+        ],
+        calendar,
+    )
+        .into_response())
+}
+
+#[tracing::instrument(name = "Create booking webhook handler", skip(session, state))]
+pub async fn handle_create_booking_webhook(
+    session: UserSession,
+    vendor_id: Path<Uuid>,
+    extract::State(state): extract::State<Arc<AppState>>,
+    Json(webhook): Json<CreateBookingWebhook>,
+) -> Result<Json<BookingWebhook>, AppError> {
+    let transaction = state
+        .db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+    let mut executor = DbExecutor::Transaction(transaction);
+
+    verify_rbac_user_employee_session(&session, &vendor_id, &mut executor).await?;
+
+    let webhook = create_booking_webhook(&vendor_id, webhook, &mut executor).await?;
+
+    executor
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to create a booking webhook.")?;
+
+    Ok(Json(webhook))
+}
+
+#[tracing::instrument(name = "Get booking webhooks handler", skip(session, state))]
+pub async fn handle_get_booking_webhooks(
+    session: UserSession,
+    vendor_id: Path<Uuid>,
+    extract::State(state): extract::State<Arc<AppState>>,
+) -> Result<Json<Vec<BookingWebhook>>, AppError> {
+    let mut executor = DbExecutor::Pool(&state.db_pool);
+    verify_rbac_user_employee_session(&session, &vendor_id, &mut executor).await?;
+
+    let webhooks = get_booking_webhooks_by_vendor_id(&vendor_id, &mut executor).await?;
+
+    Ok(Json(webhooks))
+}
+
+#[tracing::instrument(name = "Delete booking webhook handler", skip(session, state))]
+pub async fn handle_delete_booking_webhook(
+    session: UserSession,
+    webhook_id: Path<Uuid>,
+    extract::State(state): extract::State<Arc<AppState>>,
+) -> Result<(), AppError> {
+    let transaction = state
+        .db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+    let mut executor = DbExecutor::Transaction(transaction);
+
+    let webhook = get_booking_webhook_by_webhook_id(&webhook_id, &mut executor).await?;
+    verify_rbac_user_employee_session(&session, &webhook.vendor_id, &mut executor).await?;
+
+    delete_booking_webhook(&webhook_id, &mut executor).await?;
+
+    executor
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to delete a booking webhook.")?;
+
+    Ok(())
+}
+
+#[tracing::instrument(name = "Create booking blackout handler", skip(session, state))]
+pub async fn handle_create_booking_blackout(
+    session: UserSession,
+    vendor_id: Path<Uuid>,
+    extract::State(state): extract::State<Arc<AppState>>,
+    Json(blackout): Json<CreateBookingBlackout>,
+) -> Result<Json<BookingBlackout>, AppError> {
+    let transaction = state
+        .db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+    let mut executor = DbExecutor::Transaction(transaction);
+
+    verify_rbac_user_employee_session(&session, &vendor_id, &mut executor).await?;
+
+    let blackout = create_booking_blackout(&vendor_id, blackout, &mut executor).await?;
+
+    executor
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to create a booking blackout.")?;
+
+    Ok(Json(blackout))
+}
+
+#[tracing::instrument(name = "Get booking blackouts handler", skip(session, state))]
+pub async fn handle_get_booking_blackouts(
+    session: UserSession,
+    vendor_id: Path<Uuid>,
+    extract::State(state): extract::State<Arc<AppState>>,
+) -> Result<Json<Vec<BookingBlackout>>, AppError> {
+    let mut executor = DbExecutor::Pool(&state.db_pool);
+    verify_rbac_user_employee_session(&session, &vendor_id, &mut executor).await?;
+
+    let blackouts = get_booking_blackouts_by_vendor_id(&vendor_id, &mut executor).await?;
+
+    Ok(Json(blackouts))
+}
+
+#[tracing::instrument(name = "Delete booking blackout handler", skip(session, state))]
+pub async fn handle_delete_booking_blackout(
+    session: UserSession,
+    blackout_id: Path<Uuid>,
+    extract::State(state): extract::State<Arc<AppState>>,
+) -> Result<(), AppError> {
+    let transaction = state
+        .db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+    let mut executor = DbExecutor::Transaction(transaction);
+
+    let blackout = get_booking_blackout_by_blackout_id(&blackout_id, &mut executor).await?;
+    verify_rbac_user_employee_session(&session, &blackout.vendor_id, &mut executor).await?;
+
+    delete_booking_blackout(&blackout_id, &mut executor).await?;
+
+    executor
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to delete a booking blackout.")?;
+
+    Ok(())
+}
+
+#[tracing::instrument(name = "Get booking webhook deliveries handler", skip(session, state))]
+pub async fn handle_get_booking_webhook_deliveries(
+    session: UserSession,
+    webhook_id: Path<Uuid>,
+    extract::State(state): extract::State<Arc<AppState>>,
+) -> Result<Json<Vec<BookingWebhookDelivery>>, AppError> {
+    let mut executor = DbExecutor::Pool(&state.db_pool);
+
+    let webhook = get_booking_webhook_by_webhook_id(&webhook_id, &mut executor).await?;
+    verify_rbac_user_employee_session(&session, &webhook.vendor_id, &mut executor).await?;
+
+    let deliveries = get_booking_webhook_deliveries(&webhook_id, &mut executor).await?;
+
+    Ok(Json(deliveries))
+}
+
+#[tracing::instrument(name = "Rotate booking webhook secret handler", skip(session, state))]
+pub async fn handle_rotate_booking_webhook_secret(
+    session: UserSession,
+    webhook_id: Path<Uuid>,
+    extract::State(state): extract::State<Arc<AppState>>,
+) -> Result<Json<BookingWebhook>, AppError> {
+    let transaction = state
+        .db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+    let mut executor = DbExecutor::Transaction(transaction);
+
+    let webhook = get_booking_webhook_by_webhook_id(&webhook_id, &mut executor).await?;
+    verify_rbac_user_employee_session(&session, &webhook.vendor_id, &mut executor).await?;
+
+    let webhook = rotate_booking_webhook_secret(webhook, &mut executor).await?;
+
+    executor
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to rotate a booking webhook secret.")?;
+
+    Ok(Json(webhook))
+}
+
+#[tracing::instrument(name = "Ping booking webhook handler", skip(session, state))]
+pub async fn handle_ping_booking_webhook(
+    session: UserSession,
+    webhook_id: Path<Uuid>,
+    extract::State(state): extract::State<Arc<AppState>>,
+) -> Result<Json<BookingWebhookDelivery>, AppError> {
+    let transaction = state
+        .db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+    let mut executor = DbExecutor::Transaction(transaction);
+
+    let webhook = get_booking_webhook_by_webhook_id(&webhook_id, &mut executor).await?;
+    verify_rbac_user_employee_session(&session, &webhook.vendor_id, &mut executor).await?;
+
+    let delivery = queue_booking_webhook_ping(&webhook, &mut executor).await?;
+
+    executor
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to ping a booking webhook.")?;
+
+    // Sent after the commit, so a slow endpoint doesn't hold a connection and its locks
+    let mut executor = DbExecutor::Pool(&state.db_pool);
+    let delivery = ping_booking_webhook(&delivery, &mut executor).await?;
+
+    Ok(Json(delivery))
+}
@@ -0,0 +1,254 @@
+use crate::routes::bookings::bookings_model::RequestBooking;
+use crate::routes::bookings::bookings_utils::assume_booking_timezone;
+use crate::routes::transactions::transactions_model::TransactionType;
+use std::collections::HashMap;
+use time::format_description;
+use time::format_description::well_known::Iso8601;
+use time::{Date, OffsetDateTime, PrimitiveDateTime};
+use time_tz::Tz;
+use uuid::Uuid;
+
+// Parses booking import files into external booking requests. Each row parses on its own so
+// one bad row doesn't fail the whole import, the error is reported back for that row instead.
+
+pub type ParsedImportRow = Result<RequestBooking, String>;
+
+/// Parses a CSV file with a header row. The rental_id, quantity, start_date, end_date and
+/// total columns can come in any order; rental_id can be left out when a default is given.
+pub fn parse_bookings_csv(
+    content: &str,
+    vendor_id: &Uuid,
+    transaction_id: &Uuid,
+    default_rental_id: Option<Uuid>,
+    timezone: &Tz,
+) -> Vec<ParsedImportRow> {
+    let mut lines = content.lines().filter(|line| !line.trim().is_empty());
+
+    let columns: HashMap<String, usize> = match lines.next() {
+        Some(header) => header
+            .split(',')
+            .enumerate()
+            .map(|(index, column)| (column.trim().to_lowercase(), index))
+            .collect(),
+        None => return Vec::new(),
+    };
+
+    lines
+        .map(|line| {
+            let values: Vec<&str> = line.split(',').map(|value| value.trim()).collect();
+            let value = |column: &str| -> Option<&str> {
+                columns
+                    .get(column)
+                    .and_then(|index| values.get(*index))
+                    .copied()
+                    .filter(|value| !value.is_empty())
+            };
+
+            let rental_id = match value("rental_id") {
+                Some(rental_id) => Uuid::parse_str(rental_id)
+                    .map_err(|_| format!("Invalid rental_id {}", rental_id))?,
+                None => default_rental_id.ok_or(String::from("Missing rental_id"))?,
+            };
+            let quantity = match value("quantity") {
+                Some(quantity) => quantity
+                    .parse::<i32>()
+                    .map_err(|_| format!("Invalid quantity {}", quantity))?,
+                None => 1,
+            };
+            let start_date =
+                parse_import_date(value("start_date").ok_or("Missing start_date")?, timezone)?;
+            let end_date =
+                parse_import_date(value("end_date").ok_or("Missing end_date")?, timezone)?;
+            let total = match value("total") {
+                Some(total) => total
+                    .parse::<f64>()
+                    .map_err(|_| format!("Invalid total {}", total))?,
+                None => 0.0,
+            };
+
+            build_import_request(
+                vendor_id,
+                transaction_id,
+                rental_id,
+                quantity,
+                start_date,
+                end_date,
+                total,
+            )
+        })
+        .collect()
+}
+
+/// Parses the VEVENTs of an iCalendar file. Events can set X-RENTAL-ID, X-QUANTITY and
+/// X-TOTAL, otherwise the default rental, a quantity of 1 and a total of 0 are used.
+pub fn parse_bookings_ics(
+    content: &str,
+    vendor_id: &Uuid,
+    transaction_id: &Uuid,
+    default_rental_id: Option<Uuid>,
+    timezone: &Tz,
+) -> Vec<ParsedImportRow> {
+    // Continuation lines start with a space or tab and belong to the previous line
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.lines() {
+        match (
+            line.strip_prefix(' ').or(line.strip_prefix('\t')),
+            lines.last_mut(),
+        ) {
+            (Some(continuation), Some(previous)) => previous.push_str(continuation),
+            _ => lines.push(String::from(line)),
+        }
+    }
+
+    let mut rows = Vec::new();
+    let mut event: Option<HashMap<String, (String, String)>> = None;
+    for line in lines.iter() {
+        match line.trim() {
+            "BEGIN:VEVENT" => event = Some(HashMap::new()),
+            "END:VEVENT" => {
+                if let Some(properties) = event.take() {
+                    rows.push(parse_ics_event(
+                        &properties,
+                        vendor_id,
+                        transaction_id,
+                        default_rental_id,
+                        timezone,
+                    ));
+                }
+            }
+            line => {
+                if let (Some(properties), Some((name, value))) =
+                    (event.as_mut(), line.split_once(':'))
+                {
+                    // Keep parameters like VALUE=DATE apart from the property name
+                    let (name, parameters) = name.split_once(';').unwrap_or((name, ""));
+                    properties.insert(
+                        name.to_uppercase(),
+                        (parameters.to_uppercase(), String::from(value)),
+                    );
+                }
+            }
+        }
+    }
+
+    rows
+}
+
+fn parse_ics_event(
+    properties: &HashMap<String, (String, String)>,
+    vendor_id: &Uuid,
+    transaction_id: &Uuid,
+    default_rental_id: Option<Uuid>,
+    timezone: &Tz,
+) -> ParsedImportRow {
+    let value = |name: &str| properties.get(name).map(|(_, value)| value.trim());
+
+    let rental_id = match value("X-RENTAL-ID") {
+        Some(rental_id) => {
+            Uuid::parse_str(rental_id).map_err(|_| format!("Invalid X-RENTAL-ID {}", rental_id))?
+        }
+        None => default_rental_id.ok_or(String::from("Missing rental_id"))?,
+    };
+    let quantity = match value("X-QUANTITY") {
+        Some(quantity) => quantity
+            .parse::<i32>()
+            .map_err(|_| format!("Invalid X-QUANTITY {}", quantity))?,
+        None => 1,
+    };
+    let total = match value("X-TOTAL") {
+        Some(total) => total
+            .parse::<f64>()
+            .map_err(|_| format!("Invalid X-TOTAL {}", total))?,
+        None => 0.0,
+    };
+
+    let start_date = parse_ics_date(value("DTSTART").ok_or("Missing DTSTART")?, timezone)?;
+    let end_date = match properties.get("DTEND") {
+        // All day DTEND is exclusive, bookings end on the last day they cover
+        Some((parameters, end_date)) if parameters.contains("VALUE=DATE") => {
+            let last_day = parse_ics_day(end_date.trim())?
+                .previous_day()
+                .ok_or(format!("Invalid DTEND {}", end_date.trim()))?;
+            assume_booking_timezone(last_day.midnight(), timezone)
+        }
+        Some((_, end_date)) => parse_ics_date(end_date.trim(), timezone)?,
+        None => start_date,
+    };
+
+    build_import_request(
+        vendor_id,
+        transaction_id,
+        rental_id,
+        quantity,
+        start_date,
+        end_date,
+        total,
+    )
+}
+
+fn build_import_request(
+    vendor_id: &Uuid,
+    transaction_id: &Uuid,
+    rental_id: Uuid,
+    quantity: i32,
+    start_date: OffsetDateTime,
+    end_date: OffsetDateTime,
+    total: f64,
+) -> ParsedImportRow {
+    if quantity <= 0 {
+        return Err(String::from("Quantity must be at least 1"));
+    }
+    if end_date < start_date {
+        return Err(String::from("End date cannot be before start date"));
+    }
+
+    Ok(RequestBooking {
+        transaction_id: Some(*transaction_id),
+        transaction_type: TransactionType::External,
+        rental_id,
+        vendor_id: *vendor_id,
+        pricing_id: None,
+        total: Some(total),
+        quantity,
+        start_date,
+        end_date,
+    })
+}
+
+// Accepts full ISO 8601 timestamps or plain dates, which are taken as midnight in the vendor's
+// timezone
+fn parse_import_date(value: &str, timezone: &Tz) -> Result<OffsetDateTime, String> {
+    if let Ok(date) = OffsetDateTime::parse(value, &Iso8601::DEFAULT) {
+        return Ok(date);
+    }
+
+    let format =
+        format_description::parse("[year]-[month]-[day]").expect("Invalid import date format");
+    Date::parse(value, &format)
+        .map(|date| assume_booking_timezone(date.midnight(), timezone))
+        .map_err(|_| format!("Invalid date {}", value))
+}
+
+fn parse_ics_day(value: &str) -> Result<Date, String> {
+    let format = format_description::parse("[year][month][day]").expect("Invalid ics date format");
+    Date::parse(value, &format).map_err(|_| format!("Invalid date {}", value))
+}
+
+// Accepts 20240501, 20240501T100000Z and floating 20240501T100000, dates and floating times are
+// taken in the vendor's timezone
+fn parse_ics_date(value: &str, timezone: &Tz) -> Result<OffsetDateTime, String> {
+    if value.len() == 8 {
+        return parse_ics_day(value).map(|date| assume_booking_timezone(date.midnight(), timezone));
+    }
+
+    let format = format_description::parse("[year][month][day]T[hour][minute][second]")
+        .expect("Invalid ics timestamp format");
+    let date = PrimitiveDateTime::parse(value.trim_end_matches('Z'), &format)
+        .map_err(|_| format!("Invalid date {}", value))?;
+
+    if value.ends_with('Z') {
+        Ok(date.assume_utc())
+    } else {
+        Ok(assume_booking_timezone(date, timezone))
+    }
+}
@@ -0,0 +1,596 @@
+use crate::routes::bookings::bookings_model::{
+    Availability, AvailabilityRange, BookingBlackout, BookingBlackoutRecurrence, BookingGranularity,
+};
+use crate::routes::bookings::bookings_utils::{
+    assume_booking_timezone, booking_blackout_occurrence, next_booking_slot,
+};
+use time::{Duration, OffsetDateTime, PrimitiveDateTime, UtcOffset};
+use time_tz::{OffsetDateTimeExt, Tz};
+
+// Availability is computed by sweeping over the intervals bookings, holds and blackouts occupy.
+// Every interval adds its quantity at its start and removes it at its end, so the occupied
+// quantity only changes at those points and the window splits into ranges of constant
+// availability.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookingIntervalKind {
+    Booked,
+    Held,
+    Buffer,   // Turnaround time before or after a booking or hold
+    Blackout, // The vendor closed the rental, nothing is available regardless of quantity
+}
+
+// Half open, the quantity is occupied from start up to but not including end
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BookingInterval {
+    pub start: OffsetDateTime,
+    pub end: OffsetDateTime,
+    pub quantity: i32,
+    pub kind: BookingIntervalKind,
+}
+
+impl BookingInterval {
+    // Bookings and holds store the start of their last slot as the end date
+    pub fn from_slots(
+        start_date: OffsetDateTime,
+        end_date: OffsetDateTime,
+        quantity: i32,
+        kind: BookingIntervalKind,
+        granularity: &BookingGranularity,
+        timezone: &Tz,
+    ) -> Self {
+        Self {
+            start: start_date,
+            end: next_booking_slot(end_date, granularity, timezone),
+            quantity,
+            kind,
+        }
+    }
+
+    // The same units stay occupied for the buffers right before and after the interval
+    pub fn buffers(&self, before: Duration, after: Duration) -> Vec<BookingInterval> {
+        [
+            (self.start - before, self.start),
+            (self.end, self.end + after),
+        ]
+        .into_iter()
+        .filter(|(start, end)| start < end)
+        .map(|(start, end)| BookingInterval {
+            start,
+            end,
+            quantity: self.quantity,
+            kind: BookingIntervalKind::Buffer,
+        })
+        .collect()
+    }
+}
+
+/// The occurrences of a blackout that overlap the window. Recurring blackouts repeat on the
+/// local wall clock of the given timezone, so a blackout every Sunday stays on Sundays across
+/// DST changes.
+pub fn blackout_intervals(
+    blackout: &BookingBlackout,
+    window_start: OffsetDateTime,
+    window_end: OffsetDateTime,
+    timezone: &Tz,
+) -> Vec<BookingInterval> {
+    let mut intervals: Vec<BookingInterval> = Vec::new();
+    let Some(recurrence) = &blackout.recurrence else {
+        if blackout.start_date < window_end && blackout.end_date > window_start {
+            intervals.push(BookingInterval {
+                start: blackout.start_date,
+                end: blackout.end_date,
+                quantity: 1,
+                kind: BookingIntervalKind::Blackout,
+            });
+        }
+        return intervals;
+    };
+
+    let local_start = blackout.start_date.to_timezone(timezone);
+    let local_start = PrimitiveDateTime::new(local_start.date(), local_start.time());
+    let local_end = blackout.end_date.to_timezone(timezone);
+    let length = PrimitiveDateTime::new(local_end.date(), local_end.time()) - local_start;
+
+    // Daily and weekly occurrences have a fixed length, so those ending long before the window
+    // can be skipped. One is kept as a margin for DST changes.
+    let days_before_window = (window_start - blackout.end_date).whole_days();
+    let mut n = match recurrence {
+        BookingBlackoutRecurrence::Daily => days_before_window - 1,
+        BookingBlackoutRecurrence::Weekly => days_before_window / 7 - 1,
+        _ => 0,
+    }
+    .max(0);
+
+    while let Some(occurrence) = booking_blackout_occurrence(local_start, recurrence, n) {
+        let start = assume_booking_timezone(occurrence, timezone).to_offset(UtcOffset::UTC);
+        if start >= window_end || blackout.recur_until.is_some_and(|until| start >= until) {
+            break;
+        }
+
+        let end = assume_booking_timezone(occurrence + length, timezone).to_offset(UtcOffset::UTC);
+        if end > window_start {
+            intervals.push(BookingInterval {
+                start,
+                end,
+                quantity: 1,
+                kind: BookingIntervalKind::Blackout,
+            });
+        }
+
+        n += 1;
+    }
+
+    intervals
+}
+
+/// Splits the window into consecutive ranges of constant availability. Adjacent ranges always
+/// differ in their available quantity or its breakdown and together cover the whole window.
+pub fn sweep_availability_ranges(
+    intervals: &[BookingInterval],
+    window_start: OffsetDateTime,
+    window_end: OffsetDateTime,
+    total_quantity: i32,
+) -> Vec<AvailabilityRange> {
+    let mut ranges: Vec<AvailabilityRange> = Vec::new();
+    if window_start >= window_end {
+        return ranges;
+    }
+
+    // Intervals are clipped to the window, anything outside of it can't affect availability
+    let mut changes: Vec<(OffsetDateTime, BookingIntervalKind, i32)> =
+        Vec::with_capacity(intervals.len() * 2);
+    for interval in intervals {
+        let start = interval.start.max(window_start);
+        let end = interval.end.min(window_end);
+        if start >= end || interval.quantity == 0 {
+            continue;
+        }
+        changes.push((start, interval.kind, interval.quantity));
+        changes.push((end, interval.kind, -interval.quantity));
+    }
+    changes.sort_by_key(|(date, _, _)| *date);
+
+    let (mut booked, mut held, mut buffer, mut blackout) = (0, 0, 0, 0);
+    let mut next_change = 0;
+    let mut cursor = window_start;
+    while cursor < window_end {
+        // Apply every change at the cursor before measuring, so back to back intervals don't
+        // count as overlapping
+        while next_change < changes.len() && changes[next_change].0 <= cursor {
+            let (_, kind, quantity) = changes[next_change];
+            match kind {
+                BookingIntervalKind::Booked => booked += quantity,
+                BookingIntervalKind::Held => held += quantity,
+                BookingIntervalKind::Buffer => buffer += quantity,
+                BookingIntervalKind::Blackout => blackout += quantity,
+            }
+            next_change += 1;
+        }
+
+        let range_end = changes
+            .get(next_change)
+            .map_or(window_end, |(date, _, _)| *date);
+        let blacked_out = blackout > 0;
+        let range = AvailabilityRange {
+            start_date: cursor,
+            end_date: range_end,
+            available_quantity: if blacked_out {
+                0
+            } else {
+                total_quantity - booked - held - buffer
+            },
+            booked_quantity: booked,
+            held_quantity: held,
+            buffer_quantity: buffer,
+            blacked_out,
+        };
+
+        match ranges.last_mut() {
+            Some(last)
+                if last.available_quantity == range.available_quantity
+                    && last.booked_quantity == range.booked_quantity
+                    && last.held_quantity == range.held_quantity
+                    && last.buffer_quantity == range.buffer_quantity
+                    && last.blacked_out == range.blacked_out =>
+            {
+                last.end_date = range_end;
+            }
+            _ => ranges.push(range),
+        }
+
+        cursor = range_end;
+    }
+
+    ranges
+}
+
+/// The lowest available quantity at any point between start and end, None when the ranges
+/// don't overlap the window at all.
+pub fn min_available_quantity(
+    ranges: &[AvailabilityRange],
+    start: OffsetDateTime,
+    end: OffsetDateTime,
+) -> Option<i32> {
+    ranges
+        .iter()
+        .filter(|range| range.start_date < end && range.end_date > start)
+        .map(|range| range.available_quantity)
+        .min()
+}
+
+/// Expands compact ranges into one entry per slot between window start and end, each holding
+/// the lowest availability at any point in the slot. The ranges have to be sorted and
+/// consecutive, as returned by the sweep. Slots past the last range are left out, as are slots
+/// in gaps between ranges.
+pub fn expand_availability_ranges(
+    ranges: &[AvailabilityRange],
+    window_start: OffsetDateTime,
+    window_end: OffsetDateTime,
+    granularity: &BookingGranularity,
+    timezone: &Tz,
+) -> Vec<Availability> {
+    let mut availability: Vec<Availability> = Vec::new();
+    if ranges.is_empty() {
+        return availability;
+    }
+
+    let mut index = 0;
+    let mut slot = window_start;
+    while slot < window_end {
+        let slot_end = next_booking_slot(slot, granularity, timezone);
+
+        // Ranges are sorted, so those ending before this slot can't overlap later slots either
+        while index < ranges.len() && ranges[index].end_date <= slot {
+            index += 1;
+        }
+        if index == ranges.len() {
+            break;
+        }
+        if ranges[index].start_date >= slot_end {
+            slot = slot_end;
+            continue;
+        }
+
+        let overlapping = ranges[index..]
+            .iter()
+            .take_while(|range| range.start_date < slot_end);
+        let available_quantity = overlapping
+            .clone()
+            .fold(ranges[index].available_quantity, |min, range| {
+                min.min(range.available_quantity)
+            });
+        let blacked_out = overlapping.clone().any(|range| range.blacked_out);
+
+        availability.push(Availability {
+            date: slot,
+            available_quantity,
+            blacked_out,
+        });
+
+        slot = slot_end;
+    }
+
+    availability
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time_tz::timezones;
+
+    // Every interval starts and ends on this grid, so availability only changes on grid points
+    // and checking each point against a brute force count covers every range
+    const GRID_MINUTES: i64 = 15;
+    const WINDOW_HOURS: i64 = 24;
+    const TOTAL_QUANTITY: i32 = 5;
+
+    // Deterministic xorshift, so failures reproduce without an extra dependency
+    struct Rng(u64);
+
+    impl Rng {
+        fn next_u64(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn range(&mut self, low: i64, high: i64) -> i64 {
+            low + (self.next_u64() % (high - low) as u64) as i64
+        }
+    }
+
+    #[derive(Debug)]
+    struct Occupation {
+        start: i64, // Grid steps from the window start, can be outside of the window
+        end: i64,
+        quantity: i32,
+        kind: BookingIntervalKind,
+        buffer_before: i64,
+        buffer_after: i64,
+    }
+
+    fn window_start() -> OffsetDateTime {
+        OffsetDateTime::from_unix_timestamp(1_699_999_200).unwrap() // On the hour
+    }
+
+    fn at(step: i64) -> OffsetDateTime {
+        window_start() + Duration::minutes(step * GRID_MINUTES)
+    }
+
+    fn window_steps() -> i64 {
+        WINDOW_HOURS * 60 / GRID_MINUTES
+    }
+
+    fn random_occupations(rng: &mut Rng) -> Vec<Occupation> {
+        let steps = window_steps();
+        (0..rng.range(0, 12))
+            .map(|_| {
+                // Starting and ending past the window edges exercises the clipping
+                let start = rng.range(-8, steps + 8);
+                let kind = match rng.range(0, 4) {
+                    0 => BookingIntervalKind::Held,
+                    1 => BookingIntervalKind::Blackout,
+                    _ => BookingIntervalKind::Booked,
+                };
+                let buffered = kind != BookingIntervalKind::Blackout && rng.range(0, 2) == 0;
+                Occupation {
+                    start,
+                    end: start + rng.range(1, 16),
+                    quantity: if kind == BookingIntervalKind::Blackout {
+                        1
+                    } else {
+                        rng.range(1, 3) as i32
+                    },
+                    kind,
+                    buffer_before: if buffered { rng.range(0, 5) } else { 0 },
+                    buffer_after: if buffered { rng.range(0, 5) } else { 0 },
+                }
+            })
+            .collect()
+    }
+
+    fn intervals(occupations: &[Occupation]) -> Vec<BookingInterval> {
+        let mut intervals = Vec::new();
+        for occupation in occupations {
+            let interval = BookingInterval {
+                start: at(occupation.start),
+                end: at(occupation.end),
+                quantity: occupation.quantity,
+                kind: occupation.kind,
+            };
+            intervals.extend(interval.buffers(
+                Duration::minutes(occupation.buffer_before * GRID_MINUTES),
+                Duration::minutes(occupation.buffer_after * GRID_MINUTES),
+            ));
+            intervals.push(interval);
+        }
+        intervals
+    }
+
+    // Available, booked, held and buffer quantity and whether it's blacked out
+    type Counts = (i32, i32, i32, i32, bool);
+
+    fn range_counts(range: &AvailabilityRange) -> Counts {
+        (
+            range.available_quantity,
+            range.booked_quantity,
+            range.held_quantity,
+            range.buffer_quantity,
+            range.blacked_out,
+        )
+    }
+
+    // Counts at a grid point, checking every occupation on its own
+    fn brute_force(occupations: &[Occupation], step: i64) -> Counts {
+        let (mut booked, mut held, mut buffer, mut blackout) = (0, 0, 0, 0);
+        for occupation in occupations {
+            if occupation.start <= step && step < occupation.end {
+                match occupation.kind {
+                    BookingIntervalKind::Booked => booked += occupation.quantity,
+                    BookingIntervalKind::Held => held += occupation.quantity,
+                    BookingIntervalKind::Blackout => blackout += occupation.quantity,
+                    BookingIntervalKind::Buffer => unreachable!(),
+                }
+            }
+            let in_buffer_before =
+                occupation.start - occupation.buffer_before <= step && step < occupation.start;
+            let in_buffer_after =
+                occupation.end <= step && step < occupation.end + occupation.buffer_after;
+            if in_buffer_before || in_buffer_after {
+                buffer += occupation.quantity;
+            }
+        }
+
+        let blacked_out = blackout > 0;
+        let available = if blacked_out {
+            0
+        } else {
+            TOTAL_QUANTITY - booked - held - buffer
+        };
+        (available, booked, held, buffer, blacked_out)
+    }
+
+    fn sweep(occupations: &[Occupation]) -> Vec<AvailabilityRange> {
+        sweep_availability_ranges(
+            &intervals(occupations),
+            window_start(),
+            at(window_steps()),
+            TOTAL_QUANTITY,
+        )
+    }
+
+    #[test]
+    fn sweep_matches_brute_force_counts() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        for _ in 0..500 {
+            let occupations = random_occupations(&mut rng);
+            let ranges = sweep(&occupations);
+
+            assert_eq!(ranges.first().unwrap().start_date, window_start());
+            assert_eq!(ranges.last().unwrap().end_date, at(window_steps()));
+            for pair in ranges.windows(2) {
+                assert_eq!(pair[0].end_date, pair[1].start_date);
+                assert_ne!(
+                    range_counts(&pair[0]),
+                    range_counts(&pair[1]),
+                    "adjacent ranges should have been merged"
+                );
+            }
+
+            for step in 0..window_steps() {
+                let range = ranges
+                    .iter()
+                    .find(|range| range.start_date <= at(step) && at(step) < range.end_date)
+                    .unwrap();
+                assert_eq!(
+                    range_counts(range),
+                    brute_force(&occupations, step),
+                    "step {} of {:?}",
+                    step,
+                    occupations
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn min_available_quantity_matches_brute_force() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        for _ in 0..500 {
+            let occupations = random_occupations(&mut rng);
+            let ranges = sweep(&occupations);
+
+            let start = rng.range(0, window_steps());
+            let end = rng.range(start + 1, window_steps() + 1);
+            let expected = (start..end)
+                .map(|step| brute_force(&occupations, step).0)
+                .min();
+
+            assert_eq!(
+                min_available_quantity(&ranges, at(start), at(end)),
+                expected,
+                "steps {}..{} of {:?}",
+                start,
+                end,
+                occupations
+            );
+        }
+    }
+
+    #[test]
+    fn expand_matches_brute_force_per_slot() {
+        let steps_per_hour = 60 / GRID_MINUTES;
+        let mut rng = Rng(0xd1b5_4a32_d192_ed03);
+        for _ in 0..500 {
+            let occupations = random_occupations(&mut rng);
+            let ranges = sweep(&occupations);
+
+            let availability = expand_availability_ranges(
+                &ranges,
+                window_start(),
+                at(window_steps()),
+                &BookingGranularity::Hour,
+                timezones::db::UTC,
+            );
+
+            assert_eq!(availability.len() as i64, WINDOW_HOURS);
+            for (hour, slot) in availability.iter().enumerate() {
+                let hour = hour as i64;
+                let counts: Vec<Counts> = (hour * steps_per_hour..(hour + 1) * steps_per_hour)
+                    .map(|step| brute_force(&occupations, step))
+                    .collect();
+
+                assert_eq!(slot.date, at(hour * steps_per_hour));
+                assert_eq!(
+                    slot.available_quantity,
+                    counts.iter().map(|count| count.0).min().unwrap()
+                );
+                assert_eq!(slot.blacked_out, counts.iter().any(|count| count.4));
+            }
+        }
+    }
+
+    #[test]
+    fn back_to_back_bookings_do_not_overlap() {
+        let occupations = [
+            Occupation {
+                start: 4,
+                end: 8,
+                quantity: TOTAL_QUANTITY,
+                kind: BookingIntervalKind::Booked,
+                buffer_before: 0,
+                buffer_after: 0,
+            },
+            Occupation {
+                start: 8,
+                end: 12,
+                quantity: TOTAL_QUANTITY,
+                kind: BookingIntervalKind::Booked,
+                buffer_before: 0,
+                buffer_after: 0,
+            },
+        ];
+        let ranges = sweep(&occupations);
+
+        assert_eq!(min_available_quantity(&ranges, at(4), at(12)), Some(0));
+        assert_eq!(
+            min_available_quantity(&ranges, at(0), at(4)),
+            Some(TOTAL_QUANTITY)
+        );
+        assert_eq!(
+            min_available_quantity(&ranges, at(12), at(16)),
+            Some(TOTAL_QUANTITY)
+        );
+    }
+
+    #[test]
+    fn unaligned_bookings_count_against_every_slot_they_touch() {
+        // 00:45 to 02:15 reaches into three hourly slots
+        let occupations = [Occupation {
+            start: 3,
+            end: 9,
+            quantity: 2,
+            kind: BookingIntervalKind::Booked,
+            buffer_before: 0,
+            buffer_after: 0,
+        }];
+        let availability = expand_availability_ranges(
+            &sweep(&occupations),
+            window_start(),
+            at(16),
+            &BookingGranularity::Hour,
+            timezones::db::UTC,
+        );
+
+        let available: Vec<i32> = availability
+            .iter()
+            .map(|slot| slot.available_quantity)
+            .collect();
+        assert_eq!(available, vec![3, 3, 3, 5]);
+    }
+
+    #[test]
+    fn expand_stops_at_the_last_range() {
+        // The ranges only cover the first 4 hours of a 24 hour window
+        let ranges = sweep_availability_ranges(&[], window_start(), at(16), TOTAL_QUANTITY);
+        let availability = expand_availability_ranges(
+            &ranges,
+            window_start(),
+            at(window_steps()),
+            &BookingGranularity::Hour,
+            timezones::db::UTC,
+        );
+
+        assert_eq!(availability.len(), 4);
+        assert!(expand_availability_ranges(
+            &[],
+            window_start(),
+            at(window_steps()),
+            &BookingGranularity::Hour,
+            timezones::db::UTC,
+        )
+        .is_empty());
+    }
+}
@@ -0,0 +1,276 @@
+use crate::routes::bookings::bookings_events::{BookingEventBus, BookingEventSubscriber};
+use crate::routes::bookings::bookings_model::BookingJobOutcome;
+use crate::routes::bookings::bookings_service::{
+    acquire_booking_job_lease, complete_finished_bookings, delete_stale_pending_booking_holds,
+    deliver_queued_booking_notifications, deliver_queued_booking_webhooks, dispatch_booking_events,
+    expire_booking_holds, expire_unanswered_booking_requests, finish_booking_job_run,
+    renew_booking_job_lease, resolve_expired_partial_bookings, send_booking_pick_lists,
+    send_due_booking_reminders, send_vendor_booking_request_notifications,
+};
+use crate::startup::AppState;
+use crate::utilities::database::db_executor::DbExecutor;
+use crate::utilities::errors::AppError;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+use uuid::Uuid;
+
+// How many times per interval a running job renews its lease
+const BOOKING_JOB_LEASE_RENEWALS: u32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookingJob {
+    Completion,
+    EventDispatch,
+    HoldExpiry,
+    NotificationDelivery,
+    PartialBookingTimeouts,
+    PendingHoldCleanup,
+    PickLists,
+    Reminders,
+    RequestExpiry,
+    VendorRequestNotifications,
+    WebhookDelivery,
+}
+
+impl BookingJob {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BookingJob::Completion => "completion",
+            BookingJob::EventDispatch => "event_dispatch",
+            BookingJob::HoldExpiry => "hold_expiry",
+            BookingJob::NotificationDelivery => "notification_delivery",
+            BookingJob::PartialBookingTimeouts => "partial_booking_timeouts",
+            BookingJob::PendingHoldCleanup => "pending_hold_cleanup",
+            BookingJob::PickLists => "pick_lists",
+            BookingJob::Reminders => "reminders",
+            BookingJob::RequestExpiry => "request_expiry",
+            BookingJob::VendorRequestNotifications => "vendor_request_notifications",
+            BookingJob::WebhookDelivery => "webhook_delivery",
+        }
+    }
+
+    pub fn interval(&self) -> Duration {
+        match self {
+            BookingJob::Completion => Duration::from_secs(60 * 60),
+            BookingJob::EventDispatch => Duration::from_secs(30),
+            BookingJob::HoldExpiry => Duration::from_secs(60),
+            BookingJob::NotificationDelivery => Duration::from_secs(60),
+            BookingJob::PartialBookingTimeouts => Duration::from_secs(15 * 60),
+            BookingJob::PendingHoldCleanup => Duration::from_secs(60 * 60),
+            BookingJob::PickLists => Duration::from_secs(60 * 60),
+            BookingJob::Reminders => Duration::from_secs(15 * 60),
+            BookingJob::RequestExpiry => Duration::from_secs(15 * 60),
+            BookingJob::VendorRequestNotifications => Duration::from_secs(5 * 60),
+            BookingJob::WebhookDelivery => Duration::from_secs(60),
+        }
+    }
+
+    // Jobs commit each item they process in its own SQL transaction
+    async fn run(
+        &self,
+        events: Arc<BookingEventBus>,
+        state: Arc<AppState>,
+    ) -> Result<String, AppError> {
+        match self {
+            BookingJob::Completion => {
+                let completed = complete_finished_bookings(state).await?;
+                Ok(format!("Completed {} finished bookings", completed))
+            }
+            BookingJob::EventDispatch => {
+                let dispatched = dispatch_booking_events(&events, state).await?;
+                Ok(format!("Dispatched {} booking events", dispatched))
+            }
+            BookingJob::HoldExpiry => {
+                let expired = expire_booking_holds(state).await?;
+                Ok(format!("Expired {} booking holds", expired))
+            }
+            BookingJob::NotificationDelivery => {
+                let (delivered, failed) = deliver_queued_booking_notifications(state).await?;
+                Ok(format!(
+                    "Delivered {} booking notifications, {} failed",
+                    delivered, failed
+                ))
+            }
+            BookingJob::PartialBookingTimeouts => {
+                let resolved = resolve_expired_partial_bookings(state).await?;
+                Ok(format!("Abandoned {} expired partial bookings", resolved))
+            }
+            BookingJob::PendingHoldCleanup => {
+                let deleted = delete_stale_pending_booking_holds(state).await?;
+                Ok(format!("Deleted {} pending booking holds", deleted))
+            }
+            BookingJob::PickLists => {
+                let sent = send_booking_pick_lists(state).await?;
+                Ok(format!("Sent {} booking pick lists", sent))
+            }
+            BookingJob::Reminders => {
+                let sent = send_due_booking_reminders(state).await?;
+                Ok(format!("Sent {} booking reminders", sent))
+            }
+            BookingJob::RequestExpiry => {
+                let expired = expire_unanswered_booking_requests(state).await?;
+                Ok(format!("Expired {} unanswered booking requests", expired))
+            }
+            BookingJob::VendorRequestNotifications => {
+                let sent = send_vendor_booking_request_notifications(state).await?;
+                Ok(format!(
+                    "Sent {} vendor booking request notifications",
+                    sent
+                ))
+            }
+            BookingJob::WebhookDelivery => {
+                let (delivered, failed) = deliver_queued_booking_webhooks(state).await?;
+                Ok(format!(
+                    "Delivered {} booking webhooks, {} failed",
+                    delivered, failed
+                ))
+            }
+        }
+    }
+}
+
+// Runs booking jobs in the background of every replica. A Postgres lease per job
+// makes sure only one replica runs a job each interval.
+pub struct BookingJobScheduler {
+    instance_id: Uuid,
+    jobs: Vec<BookingJob>,
+    events: BookingEventBus,
+}
+
+impl Default for BookingJobScheduler {
+    fn default() -> Self {
+        Self {
+            instance_id: Uuid::new_v4(),
+            jobs: vec![
+                BookingJob::Completion,
+                BookingJob::EventDispatch,
+                BookingJob::HoldExpiry,
+                BookingJob::NotificationDelivery,
+                BookingJob::PartialBookingTimeouts,
+                BookingJob::PendingHoldCleanup,
+                BookingJob::PickLists,
+                BookingJob::Reminders,
+                BookingJob::RequestExpiry,
+                BookingJob::VendorRequestNotifications,
+                BookingJob::WebhookDelivery,
+            ],
+            events: BookingEventBus::default(),
+        }
+    }
+}
+
+impl BookingJobScheduler {
+    pub fn register(mut self, job: BookingJob) -> Self {
+        if !self.jobs.contains(&job) {
+            self.jobs.push(job);
+        }
+        self
+    }
+
+    pub fn subscribe(mut self, subscriber: Arc<dyn BookingEventSubscriber>) -> Self {
+        self.events = self.events.subscribe(subscriber);
+        self
+    }
+
+    pub fn start(&self, state: Arc<AppState>) -> Vec<JoinHandle<()>> {
+        let events = Arc::new(self.events.clone());
+        self.jobs
+            .iter()
+            .map(|job| {
+                tokio::spawn(run_booking_job_on_interval(
+                    *job,
+                    self.instance_id,
+                    events.clone(),
+                    state.clone(),
+                ))
+            })
+            .collect()
+    }
+}
+
+async fn run_booking_job_on_interval(
+    job: BookingJob,
+    instance_id: Uuid,
+    events: Arc<BookingEventBus>,
+    state: Arc<AppState>,
+) {
+    let mut interval = tokio::time::interval(job.interval());
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+        if let Err(e) = run_booking_job(job, &instance_id, events.clone(), state.clone()).await {
+            tracing::error!("Booking job {} failed: {:?}", job.name(), e);
+        }
+    }
+}
+
+#[tracing::instrument(name = "Run booking job", skip(events, state))]
+pub async fn run_booking_job(
+    job: BookingJob,
+    instance_id: &Uuid,
+    events: Arc<BookingEventBus>,
+    state: Arc<AppState>,
+) -> Result<(), AppError> {
+    let mut executor = DbExecutor::Pool(&state.db_pool);
+
+    let acquired =
+        acquire_booking_job_lease(job.name(), instance_id, job.interval(), &mut executor).await?;
+    if !acquired {
+        return Ok(()); // Another replica is running this job
+    }
+
+    let result = run_booking_job_with_lease(job, instance_id, events, state.clone()).await;
+
+    let (outcome, message) = match &result {
+        Ok(message) => (BookingJobOutcome::Succeeded, message.clone()),
+        Err(e) => (BookingJobOutcome::Failed, format!("{:?}", e)),
+    };
+    finish_booking_job_run(
+        job.name(),
+        instance_id,
+        &outcome,
+        &message,
+        job.interval(),
+        &mut executor,
+    )
+    .await?;
+
+    result.map(|_| ())
+}
+
+// Renews the lease while the job runs so runs longer than the interval keep it. If the lease is
+// lost anyway the run is dropped, which rolls back the item it was processing.
+async fn run_booking_job_with_lease(
+    job: BookingJob,
+    instance_id: &Uuid,
+    events: Arc<BookingEventBus>,
+    state: Arc<AppState>,
+) -> Result<String, AppError> {
+    let run = job.run(events, state.clone());
+    tokio::pin!(run);
+
+    let mut renewal = tokio::time::interval(job.interval() / BOOKING_JOB_LEASE_RENEWALS);
+    renewal.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    renewal.tick().await; // The first tick completes right away
+
+    loop {
+        tokio::select! {
+            result = &mut run => return result,
+            _ = renewal.tick() => {
+                let mut executor = DbExecutor::Pool(&state.db_pool);
+                let renewed =
+                    renew_booking_job_lease(job.name(), instance_id, job.interval(), &mut executor)
+                        .await?;
+                if !renewed {
+                    return Err(AppError::UnexpectedError(anyhow::anyhow!(
+                        "Lost the lease for booking job {}",
+                        job.name()
+                    )));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,420 @@
+use time::OffsetDateTime;
+use time_tz::{OffsetDateTimeExt, Tz};
+
+// Translation catalog and locale formatting for booking emails. Adding a locale only needs
+// a variant here, its catalog, its month names and its number format.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BookingLocale {
+    #[default]
+    En,
+    Fr,
+    Es,
+}
+
+impl BookingLocale {
+    /// Reads a preferred locale tag like "fr", "fr-CA" or "es_MX", falling back to English
+    /// for missing or unsupported tags.
+    pub fn parse(tag: Option<&str>) -> BookingLocale {
+        tag.and_then(BookingLocale::from_tag).unwrap_or_default()
+    }
+
+    /// Reads a locale tag, returning None for unsupported languages.
+    pub fn from_tag(tag: &str) -> Option<BookingLocale> {
+        let language = tag
+            .split(['-', '_'])
+            .next()
+            .map(|language| language.trim().to_lowercase());
+
+        match language.as_deref() {
+            Some("en") => Some(BookingLocale::En),
+            Some("fr") => Some(BookingLocale::Fr),
+            Some("es") => Some(BookingLocale::Es),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BookingLocale::En => "en",
+            BookingLocale::Fr => "fr",
+            BookingLocale::Es => "es",
+        }
+    }
+
+    fn catalog(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            BookingLocale::En => EN_CATALOG,
+            BookingLocale::Fr => FR_CATALOG,
+            BookingLocale::Es => ES_CATALOG,
+        }
+    }
+
+    // Totals are in US dollars, written the way each locale writes them
+    fn number_format(&self) -> NumberFormat {
+        match self {
+            BookingLocale::En => NumberFormat {
+                group_separator: ',',
+                decimal_separator: '.',
+                currency_prefix: "$",
+                currency_suffix: "",
+            },
+            BookingLocale::Fr => NumberFormat {
+                group_separator: '\u{202f}',
+                decimal_separator: ',',
+                currency_prefix: "",
+                currency_suffix: "\u{a0}$US",
+            },
+            BookingLocale::Es => NumberFormat {
+                group_separator: '.',
+                decimal_separator: ',',
+                currency_prefix: "",
+                currency_suffix: "\u{a0}US$",
+            },
+        }
+    }
+}
+
+struct NumberFormat {
+    group_separator: char,
+    decimal_separator: char,
+    currency_prefix: &'static str,
+    currency_suffix: &'static str,
+}
+
+/// Looks up a message in the locale's catalog, falling back to the English message and then to
+/// the key itself.
+pub fn translate(locale: BookingLocale, key: &str) -> &str {
+    let lookup = |catalog: &'static [(&'static str, &'static str)]| {
+        catalog
+            .iter()
+            .find(|(message_key, _)| *message_key == key)
+            .map(|(_, message)| *message)
+    };
+
+    lookup(locale.catalog())
+        .or_else(|| lookup(EN_CATALOG))
+        .unwrap_or_else(|| {
+            tracing::warn!("Missing booking email message {}", key);
+            key
+        })
+}
+
+// Dates are shown in the timezone of the vendor or rental they belong to
+pub fn format_email_date(date: &OffsetDateTime, locale: BookingLocale, timezone: &Tz) -> String {
+    let date = date.to_timezone(timezone);
+    let month = usize::from(u8::from(date.month())) - 1;
+
+    match locale {
+        BookingLocale::En => format!("{} {}, {}", EN_MONTHS[month], date.day(), date.year()),
+        BookingLocale::Fr => format!("{} {} {}", date.day(), FR_MONTHS[month], date.year()),
+        BookingLocale::Es => format!("{} de {} de {}", date.day(), ES_MONTHS[month], date.year()),
+    }
+}
+
+pub fn format_email_total(total: f64, locale: BookingLocale) -> String {
+    let cents = (total.abs() * 100.0).round() as u64;
+    let sign = if total < 0.0 && cents > 0 { "-" } else { "" };
+    let format = locale.number_format();
+
+    format!(
+        "{}{}{}{}{:02}{}",
+        sign,
+        format.currency_prefix,
+        group_digits(cents / 100, format.group_separator),
+        format.decimal_separator,
+        cents % 100,
+        format.currency_suffix
+    )
+}
+
+fn group_digits(value: u64, separator: char) -> String {
+    let digits = value.to_string();
+    let mut grouped = String::new();
+
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index) % 3 == 0 {
+            grouped.push(separator);
+        }
+        grouped.push(digit);
+    }
+
+    grouped
+}
+
+const EN_MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+const FR_MONTHS: [&str; 12] = [
+    "janvier",
+    "février",
+    "mars",
+    "avril",
+    "mai",
+    "juin",
+    "juillet",
+    "août",
+    "septembre",
+    "octobre",
+    "novembre",
+    "décembre",
+];
+
+const ES_MONTHS: [&str; 12] = [
+    "enero",
+    "febrero",
+    "marzo",
+    "abril",
+    "mayo",
+    "junio",
+    "julio",
+    "agosto",
+    "septiembre",
+    "octubre",
+    "noviembre",
+    "diciembre",
+];
+
+// Keys are the notification name followed by .subject or .plain, plain bodies are Tera templates
+const EN_CATALOG: &[(&str, &str)] = &[
+    ("booking_canceled.subject", "Your rental booking has been canceled"),
+    (
+        "booking_canceled.plain",
+        "Your rental booking has been canceled.\nPlease visit {{ bookings_link }} for details.",
+    ),
+    ("booking_confirmed.subject", "Your rental booking has been confirmed"),
+    (
+        "booking_confirmed.plain",
+        "Your rental booking has been confirmed.\nPlease visit {{ bookings_link }} for details.",
+    ),
+    ("booking_declined.subject", "Your rental booking has been declined"),
+    (
+        "booking_declined.plain",
+        "Your rental booking has been declined.\nPlease visit {{ bookings_link }} for details.",
+    ),
+    ("booking_partial.subject", "Would you like to accept a partial booking?"),
+    (
+        "booking_partial.plain",
+        "At least one of the items in your latest request were declined.\nPlease visit {{ bookings_link }} to confirm or deny you would like to continue with a partial booking.",
+    ),
+    ("booking_refunded.subject", "Your rental booking has been refunded"),
+    (
+        "booking_refunded.plain",
+        "Your rental booking has been refunded.\nPlease visit {{ bookings_link }} for details.",
+    ),
+    ("booking_reminder.subject", "Your upcoming rental booking details"),
+    (
+        "booking_reminder.plain",
+        "Your rental booking is coming up soon.\nPlease visit {{ bookings_link }} for details.",
+    ),
+    ("booking_return_reminder.subject", "Your rental booking is ending soon"),
+    (
+        "booking_return_reminder.plain",
+        "Your rental booking is ending soon, please get ready to return your rentals.\nPlease visit {{ bookings_link }} for details.",
+    ),
+    ("booking_updated.subject", "Your rental booking has been updated"),
+    (
+        "booking_updated.plain",
+        "Your rental booking has been updated.\nPlease visit {{ bookings_link }} for details.",
+    ),
+    ("booking_modification_requested.subject", "A renter has requested changes to their booking"),
+    (
+        "booking_modification_requested.plain",
+        "A renter has requested changes to their booking.\nPlease visit {{ booking_link }} to approve or reject the changes.",
+    ),
+    ("booking_modification_proposed.subject", "Your vendor has proposed changes to your booking"),
+    (
+        "booking_modification_proposed.plain",
+        "Your vendor has proposed changes to your booking, which would run from {{ start_date }} to {{ end_date }} with a price change of {{ price_delta }}.\nPlease visit {{ bookings_link }} to approve or reject the changes.",
+    ),
+    ("booking_pick_list.subject", "Your pickups and returns for tomorrow"),
+    (
+        "booking_pick_list.plain",
+        "You have {{ pickups | length }} pickups and {{ returns | length }} returns on {{ pick_date }}.\nPlease visit {{ bookings_link }} for details.",
+    ),
+    ("booking_vendor_canceled.subject", "A renter has canceled their booking"),
+    (
+        "booking_vendor_canceled.plain",
+        "A renter has canceled their booking from {{ start_date }} to {{ end_date }}.\nPlease visit {{ booking_link }} for details.",
+    ),
+    ("booking_vendor_requested.subject", "You have a new booking request"),
+    (
+        "booking_vendor_requested.plain",
+        "A renter has requested a booking from {{ start_date }} to {{ end_date }}.\nAccept: {{ accept_link }}\nDecline: {{ decline_link }}",
+    ),
+    ("booking_vendor_response_deadline.subject", "A booking request is about to expire"),
+    (
+        "booking_vendor_response_deadline.plain",
+        "A booking request from {{ start_date }} to {{ end_date }} expires on {{ respond_by }} unless you answer it.\nAccept: {{ accept_link }}\nDecline: {{ decline_link }}",
+    ),
+];
+
+const FR_CATALOG: &[(&str, &str)] = &[
+    ("booking_canceled.subject", "Votre réservation de location a été annulée"),
+    (
+        "booking_canceled.plain",
+        "Votre réservation de location a été annulée.\nConsultez {{ bookings_link }} pour plus de détails.",
+    ),
+    ("booking_confirmed.subject", "Votre réservation de location a été confirmée"),
+    (
+        "booking_confirmed.plain",
+        "Votre réservation de location a été confirmée.\nConsultez {{ bookings_link }} pour plus de détails.",
+    ),
+    ("booking_declined.subject", "Votre réservation de location a été refusée"),
+    (
+        "booking_declined.plain",
+        "Votre réservation de location a été refusée.\nConsultez {{ bookings_link }} pour plus de détails.",
+    ),
+    ("booking_partial.subject", "Souhaitez-vous accepter une réservation partielle ?"),
+    (
+        "booking_partial.plain",
+        "Au moins un des articles de votre dernière demande a été refusé.\nConsultez {{ bookings_link }} pour indiquer si vous souhaitez poursuivre avec une réservation partielle.",
+    ),
+    ("booking_refunded.subject", "Votre réservation de location a été remboursée"),
+    (
+        "booking_refunded.plain",
+        "Votre réservation de location a été remboursée.\nConsultez {{ bookings_link }} pour plus de détails.",
+    ),
+    ("booking_reminder.subject", "Les détails de votre prochaine réservation de location"),
+    (
+        "booking_reminder.plain",
+        "Votre réservation de location approche.\nConsultez {{ bookings_link }} pour plus de détails.",
+    ),
+    ("booking_return_reminder.subject", "Votre réservation de location se termine bientôt"),
+    (
+        "booking_return_reminder.plain",
+        "Votre réservation de location se termine bientôt, préparez-vous à rendre les articles loués.\nConsultez {{ bookings_link }} pour plus de détails.",
+    ),
+    ("booking_updated.subject", "Votre réservation de location a été modifiée"),
+    (
+        "booking_updated.plain",
+        "Votre réservation de location a été modifiée.\nConsultez {{ bookings_link }} pour plus de détails.",
+    ),
+    (
+        "booking_modification_requested.subject",
+        "Un locataire a demandé des modifications à sa réservation",
+    ),
+    (
+        "booking_modification_requested.plain",
+        "Un locataire a demandé des modifications à sa réservation.\nConsultez {{ booking_link }} pour approuver ou refuser les modifications.",
+    ),
+    (
+        "booking_modification_proposed.subject",
+        "Votre loueur a proposé des modifications à votre réservation",
+    ),
+    (
+        "booking_modification_proposed.plain",
+        "Votre loueur propose de modifier votre réservation pour la période du {{ start_date }} au {{ end_date }}, avec une différence de prix de {{ price_delta }}.\nConsultez {{ bookings_link }} pour approuver ou refuser les modifications.",
+    ),
+    ("booking_pick_list.subject", "Vos retraits et retours de demain"),
+    (
+        "booking_pick_list.plain",
+        "Vous avez {{ pickups | length }} retraits et {{ returns | length }} retours le {{ pick_date }}.\nConsultez {{ bookings_link }} pour plus de détails.",
+    ),
+    ("booking_vendor_canceled.subject", "Un locataire a annulé sa réservation"),
+    (
+        "booking_vendor_canceled.plain",
+        "Un locataire a annulé sa réservation du {{ start_date }} au {{ end_date }}.\nConsultez {{ booking_link }} pour plus de détails.",
+    ),
+    ("booking_vendor_requested.subject", "Vous avez une nouvelle demande de réservation"),
+    (
+        "booking_vendor_requested.plain",
+        "Un locataire a demandé une réservation du {{ start_date }} au {{ end_date }}.\nAccepter : {{ accept_link }}\nRefuser : {{ decline_link }}",
+    ),
+    ("booking_vendor_response_deadline.subject", "Une demande de réservation va bientôt expirer"),
+    (
+        "booking_vendor_response_deadline.plain",
+        "Une demande de réservation du {{ start_date }} au {{ end_date }} expire le {{ respond_by }} sans réponse de votre part.\nAccepter : {{ accept_link }}\nRefuser : {{ decline_link }}",
+    ),
+];
+
+const ES_CATALOG: &[(&str, &str)] = &[
+    ("booking_canceled.subject", "Tu reserva de alquiler ha sido cancelada"),
+    (
+        "booking_canceled.plain",
+        "Tu reserva de alquiler ha sido cancelada.\nVisita {{ bookings_link }} para ver los detalles.",
+    ),
+    ("booking_confirmed.subject", "Tu reserva de alquiler ha sido confirmada"),
+    (
+        "booking_confirmed.plain",
+        "Tu reserva de alquiler ha sido confirmada.\nVisita {{ bookings_link }} para ver los detalles.",
+    ),
+    ("booking_declined.subject", "Tu reserva de alquiler ha sido rechazada"),
+    (
+        "booking_declined.plain",
+        "Tu reserva de alquiler ha sido rechazada.\nVisita {{ bookings_link }} para ver los detalles.",
+    ),
+    ("booking_partial.subject", "¿Quieres aceptar una reserva parcial?"),
+    (
+        "booking_partial.plain",
+        "Al menos uno de los artículos de tu última solicitud fue rechazado.\nVisita {{ bookings_link }} para confirmar si quieres continuar con una reserva parcial.",
+    ),
+    ("booking_refunded.subject", "Tu reserva de alquiler ha sido reembolsada"),
+    (
+        "booking_refunded.plain",
+        "Tu reserva de alquiler ha sido reembolsada.\nVisita {{ bookings_link }} para ver los detalles.",
+    ),
+    ("booking_reminder.subject", "Los detalles de tu próxima reserva de alquiler"),
+    (
+        "booking_reminder.plain",
+        "Tu reserva de alquiler se acerca.\nVisita {{ bookings_link }} para ver los detalles.",
+    ),
+    ("booking_return_reminder.subject", "Tu reserva de alquiler termina pronto"),
+    (
+        "booking_return_reminder.plain",
+        "Tu reserva de alquiler termina pronto, prepárate para devolver los artículos alquilados.\nVisita {{ bookings_link }} para ver los detalles.",
+    ),
+    ("booking_updated.subject", "Tu reserva de alquiler ha sido actualizada"),
+    (
+        "booking_updated.plain",
+        "Tu reserva de alquiler ha sido actualizada.\nVisita {{ bookings_link }} para ver los detalles.",
+    ),
+    (
+        "booking_modification_requested.subject",
+        "Un arrendatario ha solicitado cambios en su reserva",
+    ),
+    (
+        "booking_modification_requested.plain",
+        "Un arrendatario ha solicitado cambios en su reserva.\nVisita {{ booking_link }} para aprobar o rechazar los cambios.",
+    ),
+    (
+        "booking_modification_proposed.subject",
+        "Tu proveedor ha propuesto cambios en tu reserva",
+    ),
+    (
+        "booking_modification_proposed.plain",
+        "Tu proveedor propone cambiar tu reserva al período del {{ start_date }} al {{ end_date }}, con una diferencia de precio de {{ price_delta }}.\nVisita {{ bookings_link }} para aprobar o rechazar los cambios.",
+    ),
+    ("booking_pick_list.subject", "Tus entregas y devoluciones de mañana"),
+    (
+        "booking_pick_list.plain",
+        "Tienes {{ pickups | length }} entregas y {{ returns | length }} devoluciones el {{ pick_date }}.\nVisita {{ bookings_link }} para ver los detalles.",
+    ),
+    ("booking_vendor_canceled.subject", "Un arrendatario ha cancelado su reserva"),
+    (
+        "booking_vendor_canceled.plain",
+        "Un arrendatario ha cancelado su reserva del {{ start_date }} al {{ end_date }}.\nVisita {{ booking_link }} para ver los detalles.",
+    ),
+    ("booking_vendor_requested.subject", "Tienes una nueva solicitud de reserva"),
+    (
+        "booking_vendor_requested.plain",
+        "Un arrendatario ha solicitado una reserva del {{ start_date }} al {{ end_date }}.\nAceptar: {{ accept_link }}\nRechazar: {{ decline_link }}",
+    ),
+    ("booking_vendor_response_deadline.subject", "Una solicitud de reserva está por vencer"),
+    (
+        "booking_vendor_response_deadline.plain",
+        "Una solicitud de reserva del {{ start_date }} al {{ end_date }} vence el {{ respond_by }} si no la respondes.\nAceptar: {{ accept_link }}\nRechazar: {{ decline_link }}",
+    ),
+];
@@ -1,4 +1,5 @@
 use crate::routes::booking_holds::booking_holds_model::BookingHoldStatus;
+use crate::routes::rbac::rbac_model::RbacRole;
 use crate::routes::rentals::rentals_model::Rental;
 use crate::routes::transactions::transactions_model::TransactionType;
 use serde::{Deserialize, Serialize};
@@ -20,6 +21,134 @@ pub enum BookingStatus {
     Confirmed,
     Completed,
     Disputed,
+    Expired,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, sqlx::Type, Clone, Copy, Display, Eq, Hash)]
+#[sqlx(type_name = "booking_party")]
+#[sqlx(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum BookingParty {
+    Vendor,
+    Renter,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, sqlx::Type, Clone, Copy, Display, Eq, Hash)]
+#[sqlx(type_name = "booking_reminder_kind")]
+#[sqlx(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum BookingReminderKind {
+    Start,  // Sent before the start date
+    Return, // Sent before the end date
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, sqlx::Type, Clone, Copy, Display, Eq, Hash)]
+#[sqlx(type_name = "booking_vendor_notification_kind")]
+#[sqlx(rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum BookingVendorNotificationKind {
+    Request,          // Sent when a renter requests a booking
+    ResponseDeadline, // Sent before an unanswered request expires
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, sqlx::Type, Clone, Copy, Display, Eq, Hash)]
+#[sqlx(type_name = "booking_modification_status")]
+#[sqlx(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum BookingModificationStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, sqlx::Type, Clone, Copy, Display, Eq, Hash)]
+#[sqlx(type_name = "booking_dispute_status")]
+#[sqlx(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum BookingDisputeStatus {
+    Open,
+    Resolved,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, sqlx::Type, Clone, Copy, Display, Eq, Hash)]
+#[sqlx(type_name = "booking_dispute_resolution")]
+#[sqlx(rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum BookingDisputeResolution {
+    Completed,
+    Canceled,
+    PartialRefund,
+}
+
+// Platform staff who can act on any booking, e.g. to settle disputes. Vendor employees are never
+// operators for their own vendor's bookings through this role.
+#[derive(Debug, Serialize, Deserialize, PartialEq, sqlx::Type, Clone, Copy, Display, Eq, Hash)]
+#[sqlx(type_name = "booking_operator_role")]
+#[sqlx(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum BookingOperatorRole {
+    Support, // Can resolve disputes
+    Admin,   // Can also see and replay booking jobs and notifications
+}
+
+// Why money has to move outside of the regular accept, cancel and complete flows
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy, Display, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum BookingSettlementReason {
+    Cancellation, // Refunds below 100% from the vendor's cancellation policy
+    DisputeRefund,
+    Modification, // The price delta of an approved modification
+}
+
+// The smallest slot a rental can be booked for. Bookings start and end on slot boundaries, the
+// end date being the start of the last booked slot.
+#[derive(Debug, Serialize, Deserialize, PartialEq, sqlx::Type, Clone, Copy, Display, Eq, Hash)]
+#[sqlx(type_name = "booking_granularity")]
+#[sqlx(rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum BookingGranularity {
+    Hour,
+    HalfDay, // Slots start at midnight and noon
+    Day,
+}
+
+// How often a blackout repeats, following the local wall clock
+#[derive(Debug, Serialize, Deserialize, PartialEq, sqlx::Type, Clone, Copy, Display, Eq, Hash)]
+#[sqlx(type_name = "booking_blackout_recurrence")]
+#[sqlx(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum BookingBlackoutRecurrence {
+    Daily,
+    Weekly,  // e.g. every Sunday, starting on a Sunday
+    Monthly, // Clamped to the last day of shorter months
+    Yearly,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy, Display, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum BookingImportFormat {
+    Csv,
+    Ics,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy, Display, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum BookingImportRowStatus {
+    Created,
+    Skipped,     // Row couldn't be parsed or isn't valid for this vendor
+    Conflicting, // Not enough availability for the row's dates
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -44,11 +173,366 @@ pub struct Booking {
     pub available: Option<bool>,
 }
 
+#[derive(Debug, Serialize, Deserialize, PartialEq, sqlx::Type, Clone, Copy, Display, Eq, Hash)]
+#[sqlx(type_name = "booking_job_outcome")]
+#[sqlx(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum BookingJobOutcome {
+    Succeeded,
+    Failed,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, sqlx::Type, Clone, Copy, Display, Eq, Hash)]
+#[sqlx(type_name = "booking_webhook_event")]
+#[sqlx(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum BookingWebhookEvent {
+    Requested,
+    Accepted,
+    Declined,
+    Canceled,
+    Confirmed,
+    Completed,
+    Disputed,
+    Ping, // Only sent by the test ping endpoint
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, sqlx::Type, Clone, Copy, Display, Eq, Hash)]
+#[sqlx(type_name = "booking_notification_status")]
+#[sqlx(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum BookingNotificationStatus {
+    Pending,
+    Delivered,
+    Dead, // Gave up after too many failed attempts, can be replayed
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, sqlx::Type, Clone, Copy, Display, Eq, Hash)]
+#[sqlx(type_name = "booking_event_status")]
+#[sqlx(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum BookingEventStatus {
+    Pending,
+    Dispatched,
+    Dead, // Gave up after too many failed attempts, later events no longer wait behind it
+}
+
+// Who made a change to a booking, either a logged in user, a renter using an
+// emailed access link or a background job
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(tag = "actor_type", rename_all = "lowercase")]
+pub enum BookingActor {
+    User { user_id: Uuid },
+    Token { token_id: Uuid },
+    System { job: String },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BookingStatusEvent {
+    pub event_id: Uuid,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+    pub booking_id: Uuid,
+    pub previous_status: BookingStatus,
+    pub new_status: BookingStatus,
+    pub actor: BookingActor,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BookingModification {
+    pub modification_id: Uuid,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601")]
+    pub updated_at: OffsetDateTime,
+    pub booking_id: Uuid,
+    pub requested_by: Uuid,
+    pub requested_by_party: BookingParty, // The other party has to approve the modification
+    pub previous_quantity: i32,
+    #[serde(with = "time::serde::iso8601")]
+    pub previous_start_date: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601")]
+    pub previous_end_date: OffsetDateTime,
+    pub previous_total: f64,
+    pub quantity: i32,
+    #[serde(with = "time::serde::iso8601")]
+    pub start_date: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601")]
+    pub end_date: OffsetDateTime,
+    pub total: f64,
+    pub price_delta: f64, // Positive deltas are charged to the renter, negative deltas are refunded
+    pub modification_status: BookingModificationStatus,
+    pub resolved_by: Option<Uuid>,
+    #[serde(default, with = "time::serde::iso8601::option")]
+    pub resolved_at: Option<OffsetDateTime>,
+}
+
+// The booking details a modification changes
+#[derive(Debug, Clone)]
+pub struct BookingChanges {
+    pub quantity: i32,
+    pub start_date: OffsetDateTime,
+    pub end_date: OffsetDateTime,
+    pub total: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BookingDispute {
+    pub dispute_id: Uuid,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601")]
+    pub updated_at: OffsetDateTime,
+    pub booking_id: Uuid,
+    pub opened_by: Uuid,
+    pub opened_by_party: BookingParty,
+    pub previous_status: BookingStatus, // Status the booking was in when the dispute was opened
+    pub reason: String,
+    pub evidence: Vec<String>,
+    pub dispute_status: BookingDisputeStatus,
+    pub resolution: Option<BookingDisputeResolution>,
+    pub refund_amount: Option<f64>,
+    pub resolution_note: Option<String>,
+    pub resolved_by: Option<Uuid>,
+    #[serde(default, with = "time::serde::iso8601::option")]
+    pub resolved_at: Option<OffsetDateTime>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct CancellationPolicyTier {
+    pub min_hours_before_start: i64, // Applies when a booking is canceled at least this long before it starts
+    pub refund_percent: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CancellationPolicy {
+    pub vendor_id: Uuid,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601")]
+    pub updated_at: OffsetDateTime,
+    pub name: String,
+    pub tiers: Vec<CancellationPolicyTier>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CancellationRefund {
+    pub canceled_by: BookingParty,
+    pub policy: Option<CancellationPolicy>, // None when the policy doesn't apply or the vendor has none
+    pub refund_percent: i32,
+    pub refund_amount: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CanceledBooking {
+    #[serde(flatten)]
+    pub booking: Booking,
+    pub refund: CancellationRefund,
+}
+
+// A transaction where the vendor accepted some bookings and declined others,
+// waiting on the renter to confirm the accepted bookings or abandon the request
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PartialBooking {
+    pub transaction_id: Uuid,
+    pub accepted: Vec<Booking>,
+    pub declined: Vec<Booking>,
+    #[serde(with = "time::serde::iso8601")]
+    pub respond_by: OffsetDateTime,
+}
+
+// Per vendor booking configuration, defaults apply when a vendor has no row
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BookingVendorSettings {
+    pub vendor_id: Uuid,
+    pub response_deadline_hours: i32, // How long the vendor has to answer a booking request
+    pub completion_grace_hours: i32, // How long after the end date confirmed bookings are completed
+    pub reminder_lead_hours: i32,    // How long before the start and end dates renters are reminded
+    pub timezone: String, // IANA name, e.g. America/Los_Angeles, days start at its midnight
+}
+
+// An employee of a vendor and their role, vendor facing emails go to employees by role
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BookingVendorEmployee {
+    pub user_id: Uuid,
+    pub email: String,
+    pub role: RbacRole,
+}
+
+// Per renter booking email preferences, defaults apply when a user has no row
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BookingEmailPreferences {
+    pub user_id: Uuid,
+    pub locale: String, // Language of renter emails, one of en, fr or es
+}
+
+// Per rental booking configuration, defaults apply when a rental has no row
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BookingRentalSettings {
+    pub rental_id: Uuid,
+    pub booking_granularity: BookingGranularity,
+    pub timezone: Option<String>,   // None uses the vendor's timezone
+    pub buffer_before_minutes: i32, // e.g. prep time before a booking starts
+    pub buffer_after_minutes: i32,  // e.g. cleaning time after a booking ends
+}
+
+// How long a renter has left to finish checkout before their hold stops counting
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BookingHoldExpiry {
+    pub booking_hold_id: Uuid,
+    pub transaction_id: Uuid,
+    #[serde(with = "time::serde::iso8601")]
+    pub expires_at: OffsetDateTime,
+    pub remaining_seconds: i64,
+}
+
+// The lease and last run of a background booking job
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BookingJobRun {
+    pub job_name: String,
+    pub leased_by: Uuid, // Instance that holds or last held the lease
+    #[serde(with = "time::serde::iso8601")]
+    pub leased_until: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601")]
+    pub last_started_at: OffsetDateTime,
+    #[serde(default, with = "time::serde::iso8601::option")]
+    pub last_finished_at: Option<OffsetDateTime>,
+    pub last_outcome: Option<BookingJobOutcome>,
+    pub last_message: Option<String>,
+}
+
+// A booking event waiting to be dispatched to subscribers
+#[derive(Debug, Clone)]
+pub struct BookingOutboxEvent {
+    pub event_id: Uuid,
+    pub booking_id: Option<Uuid>,
+    pub payload: String,
+    pub attempts: i32,
+}
+
+// A rendered booking email in the outbox. It's written in the same SQL transaction as
+// the change that caused it and delivered by a booking job after that transaction commits.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BookingOutboxNotification {
+    pub notification_id: Uuid,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601")]
+    pub updated_at: OffsetDateTime,
+    pub notification: String, // Kind of notification, e.g. booking_confirmed
+    pub recipient: String,
+    pub subject: String,
+    #[serde(skip_serializing)]
+    pub html_body: String,
+    #[serde(skip_serializing)]
+    pub plain_body: String,
+    pub attachment_filename: Option<String>,
+    pub attachment_content_type: Option<String>,
+    #[serde(skip_serializing)]
+    pub attachment_content: Option<String>,
+    pub notification_status: BookingNotificationStatus,
+    pub attempts: i32,
+    #[serde(with = "time::serde::iso8601")]
+    pub next_attempt_at: OffsetDateTime,
+    pub last_error: Option<String>,
+    #[serde(default, with = "time::serde::iso8601::option")]
+    pub delivered_at: Option<OffsetDateTime>,
+}
+
+// An endpoint a vendor registered to receive booking events. The signing secret is random and
+// stored per webhook, it's only returned when the webhook is created or its secret rotated.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BookingWebhook {
+    pub webhook_id: Uuid,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+    pub vendor_id: Uuid,
+    pub url: String,
+    pub events: Vec<BookingWebhookEvent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateBookingWebhook {
+    pub url: String,
+    pub events: Vec<BookingWebhookEvent>,
+}
+
+// A period a vendor closed a rental, or the whole shop, for e.g. holidays or inventory counts.
+// Recurring blackouts repeat the same period until recur_until, or forever if it's not set.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BookingBlackout {
+    pub blackout_id: Uuid,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+    pub vendor_id: Uuid,
+    pub rental_id: Option<Uuid>, // None closes every rental of the vendor
+    #[serde(with = "time::serde::iso8601")]
+    pub start_date: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601")]
+    pub end_date: OffsetDateTime, // Exclusive
+    pub recurrence: Option<BookingBlackoutRecurrence>,
+    #[serde(default, with = "time::serde::iso8601::option")]
+    pub recur_until: Option<OffsetDateTime>,
+    pub reason: Option<String>,
+}
+
+// A booking event queued for a webhook, doubling as the webhook's delivery log
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BookingWebhookDelivery {
+    pub delivery_id: Uuid,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601")]
+    pub updated_at: OffsetDateTime,
+    pub webhook_id: Uuid,
+    pub url: String,
+    pub event: BookingWebhookEvent,
+    pub payload: String, // The exact JSON body that is signed and sent
+    pub delivery_status: BookingNotificationStatus,
+    pub attempts: i32,
+    #[serde(with = "time::serde::iso8601")]
+    pub next_attempt_at: OffsetDateTime,
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    #[serde(default, with = "time::serde::iso8601::option")]
+    pub delivered_at: Option<OffsetDateTime>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BookingWebhookPayload {
+    pub event: String, // e.g. booking.confirmed
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+    pub booking: Option<Booking>, // None for pings
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Availability {
     #[serde(with = "time::serde::iso8601")]
     pub date: OffsetDateTime,
     pub available_quantity: i32,
+    pub blacked_out: bool,
+}
+
+// A stretch of time over which the available quantity doesn't change
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct AvailabilityRange {
+    #[serde(with = "time::serde::iso8601")]
+    pub start_date: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601")]
+    pub end_date: OffsetDateTime, // Exclusive
+    pub available_quantity: i32,
+    pub booked_quantity: i32,
+    pub held_quantity: i32,
+    pub buffer_quantity: i32, // Units in turnaround before or after a booking or hold
+    pub blacked_out: bool,    // Nothing is available while the vendor closed the rental
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -67,12 +551,36 @@ pub struct GetBookingsQuery {
     #[serde(default, with = "time::serde::iso8601::option")]
     pub end_date: Option<OffsetDateTime>,
     pub booking_status: Option<BookingStatus>,
+    pub booking_statuses: Option<Vec<BookingStatus>>, // Matches any of the statuses
     pub include_rental: Option<bool>, // Whether to include rental details in the response
     pub check_availability: Option<bool>, // Whether to check availability for the booking
     pub page: Option<i32>,
     pub per_page: Option<i32>,
 }
 
+// A subscribable calendar of a vendor's bookings, optionally limited to one rental
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct BookingCalendarFeed {
+    pub vendor_id: Uuid,
+    pub rental_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BookingCalendarFeedLink {
+    pub url: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GetBookingCalendarFeedLinkQuery {
+    pub rental_id: Option<Uuid>,
+}
+
+// Lets renters open links from booking emails without logging in
+#[derive(Debug, Deserialize)]
+pub struct BookingAccessQuery {
+    pub token: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct RequestBooking {
     pub transaction_id: Option<Uuid>,
@@ -88,10 +596,99 @@ pub struct RequestBooking {
     pub end_date: OffsetDateTime,
 }
 
-// TODO: Implement disputes
+#[derive(Debug, Deserialize)]
+pub struct ImportBookingsQuery {
+    pub format: BookingImportFormat,
+    pub transaction_id: Uuid, // External transaction the imported bookings are added to
+    pub rental_id: Option<Uuid>, // Used for rows that don't name a rental
+    pub dry_run: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BookingImportRow {
+    pub row: usize, // Starts at 1, CSV header rows aren't counted
+    pub status: BookingImportRowStatus,
+    pub booking_id: Option<Uuid>,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BookingImportReport {
+    pub dry_run: bool,
+    pub created: usize,
+    pub skipped: usize,
+    pub conflicting: usize,
+    pub rows: Vec<BookingImportRow>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateBookingVendorSettings {
+    pub response_deadline_hours: Option<i32>,
+    pub completion_grace_hours: Option<i32>,
+    pub reminder_lead_hours: Option<i32>,
+    pub timezone: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateBookingEmailPreferences {
+    pub locale: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateBookingRentalSettings {
+    pub booking_granularity: Option<BookingGranularity>,
+    pub timezone: Option<String>,
+    pub buffer_before_minutes: Option<i32>,
+    pub buffer_after_minutes: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateBookingBlackout {
+    pub rental_id: Option<Uuid>,
+    #[serde(with = "time::serde::iso8601")]
+    pub start_date: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601")]
+    pub end_date: OffsetDateTime,
+    pub recurrence: Option<BookingBlackoutRecurrence>,
+    #[serde(default, with = "time::serde::iso8601::option")]
+    pub recur_until: Option<OffsetDateTime>,
+    pub reason: Option<String>,
+    #[serde(default)]
+    pub force: bool, // Create the blackout even if it overlaps existing bookings
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpsertCancellationPolicy {
+    pub name: String,
+    pub tiers: Vec<CancellationPolicyTier>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ModifyBooking {
+    pub quantity: Option<i32>,
+    #[serde(default, with = "time::serde::iso8601::option")]
+    pub start_date: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::iso8601::option")]
+    pub end_date: Option<OffsetDateTime>,
+    pub total: Option<f64>, // For external transactions
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReassignBooking {
+    pub transaction_id: Uuid,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct DisputeBooking {
-    pub vendor_id: Uuid,
+    pub reason: String,
+    pub evidence: Option<Vec<String>>, // Links to photos, documents, etc.
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResolveBookingDispute {
+    pub resolution: BookingDisputeResolution,
+    pub refund_amount: Option<f64>, // Required for partial refunds
+    pub resolution_note: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -1,13 +1,25 @@
+use crate::routes::bookings::bookings_emails::RenderedBookingNotification;
 use crate::routes::bookings::bookings_model::{
-    Booking, BookingStatus, GetBookingsQuery, RequestBooking,
+    Booking, BookingActor, BookingBlackout, BookingBlackoutRecurrence, BookingCalendarFeed,
+    BookingChanges, BookingDispute, BookingDisputeResolution, BookingDisputeStatus,
+    BookingEmailPreferences, BookingEventStatus, BookingGranularity, BookingJobOutcome,
+    BookingJobRun, BookingModification, BookingModificationStatus, BookingNotificationStatus,
+    BookingOperatorRole, BookingOutboxEvent, BookingOutboxNotification, BookingParty,
+    BookingReminderKind, BookingRentalSettings, BookingStatus, BookingStatusEvent,
+    BookingVendorEmployee, BookingVendorNotificationKind, BookingVendorSettings, BookingWebhook,
+    BookingWebhookDelivery, BookingWebhookEvent, CancellationPolicy, CancellationPolicyTier,
+    CreateBookingBlackout, CreateBookingWebhook, DisputeBooking, GetBookingsQuery, RequestBooking,
+    ResolveBookingDispute, UpsertCancellationPolicy,
 };
 use crate::routes::pricing::pricing_model::CalculatePriceRequest;
 use crate::routes::pricing::pricing_service::calculate_price;
+use crate::routes::rbac::rbac_model::RbacRole;
 use crate::routes::transactions::transactions_model::TransactionType;
 use crate::shared::types::{PaginatedResponse, PaginationMeta};
 use crate::utilities::database::db_executor::DbExecutor;
 use anyhow::Context;
 use sqlx::postgres::PgRow;
+use sqlx::types::Json;
 use sqlx::{QueryBuilder, Row};
 use time::OffsetDateTime;
 use uuid::Uuid;
@@ -141,6 +153,14 @@ pub async fn get_bookings_from_database_by_query<'e>(
         query.push_bind(booking_status);
     }
 
+    if let Some(booking_statuses) = &query_params.booking_statuses {
+        if !booking_statuses.is_empty() {
+            query.push(" AND booking_status = ANY(");
+            query.push_bind(booking_statuses);
+            query.push(")");
+        }
+    }
+
     if let Some(start_date) = &query_params.start_date {
         query.push(" AND end_date >= ");
         query.push_bind(start_date);
@@ -261,83 +281,3237 @@ pub async fn get_booking_from_database_by_booking_id<'e>(
 )]
 pub async fn update_booking_status_in_database_by_booking_id<'e>(
     booking_id: &Uuid,
+    previous_status: &BookingStatus,
     booking_status: &BookingStatus,
     executor: &mut DbExecutor<'e>,
-) -> Result<(), anyhow::Error> {
+) -> Result<bool, anyhow::Error> {
+    // Returns false when the status is no longer the previous status, e.g. because a concurrent
+    // update changed it first. The update locks the row, so the check and the write can't interleave.
     let query = sqlx::query!(
         r#"
         UPDATE bookings
         SET
-            booking_status = $2
+            booking_status = $3,
+            updated_at = NOW()
         WHERE booking_id = $1
+            AND booking_status = $2
         "#,
         booking_id,
+        previous_status as &BookingStatus,
         booking_status as &BookingStatus
     );
 
+    let rows_affected = match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to perform a query to update booking status by booking id.")?
+    .rows_affected();
+
+    Ok(rows_affected > 0)
+}
+
+#[tracing::instrument(name = "Update booking transaction id in database", skip(executor))]
+pub async fn update_booking_transaction_id_in_database<'e>(
+    booking_id: &Uuid,
+    transaction_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        UPDATE bookings
+        SET
+            transaction_id = $2,
+            updated_at = NOW()
+        WHERE booking_id = $1
+        "#,
+        booking_id,
+        transaction_id,
+    );
+
     match executor {
         DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
         DbExecutor::Pool(pool) => query.execute(*pool).await,
     }
-    .context("Failed to perform a query to update booking status by booking id.")?;
+    .context("Failed to perform a query to update booking transaction id.")?;
 
     Ok(())
 }
 
-#[tracing::instrument(name = "Get booked quantity by rental id", skip(executor))]
-pub async fn get_booked_quantity_by_rental_id<'e>(
+#[tracing::instrument(name = "Create booking status event in database", skip(executor))]
+pub async fn create_booking_status_event_in_database<'e>(
+    booking_id: &Uuid,
+    previous_status: &BookingStatus,
+    new_status: &BookingStatus,
+    actor: &BookingActor,
+    reason: Option<&str>,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Uuid, anyhow::Error> {
+    let event_id = Uuid::new_v4();
+
+    let (actor_user_id, actor_token_id, actor_job) = match actor {
+        BookingActor::User { user_id } => (Some(*user_id), None, None),
+        BookingActor::Token { token_id } => (None, Some(*token_id), None),
+        BookingActor::System { job } => (None, None, Some(job.as_str())),
+    };
+
+    let query = sqlx::query!(
+        r#"
+        INSERT INTO booking_status_events (
+            event_id,
+            booking_id,
+            previous_status,
+            new_status,
+            actor_user_id,
+            actor_token_id,
+            actor_job,
+            reason
+        )
+        VALUES (
+            $1,
+            $2,
+            $3,
+            $4,
+            $5,
+            $6,
+            $7,
+            $8
+        )
+        "#,
+        event_id,
+        booking_id,
+        previous_status as &BookingStatus,
+        new_status as &BookingStatus,
+        actor_user_id,
+        actor_token_id,
+        actor_job,
+        reason,
+    );
+
+    match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to create new booking status event in the database.")?;
+
+    Ok(event_id)
+}
+
+#[tracing::instrument(
+    name = "Get booking status events from database by booking id",
+    skip(executor)
+)]
+pub async fn get_booking_status_events_from_database_by_booking_id<'e>(
+    booking_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<BookingStatusEvent>, anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        SELECT
+            event_id,
+            created_at,
+            booking_id,
+            previous_status as "previous_status: BookingStatus",
+            new_status as "new_status: BookingStatus",
+            actor_user_id,
+            actor_token_id,
+            actor_job,
+            reason
+        FROM booking_status_events
+        WHERE booking_id = $1
+        ORDER BY created_at ASC
+        "#,
+        booking_id,
+    );
+
+    let events: Vec<BookingStatusEvent> = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_all(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_all(*pool).await,
+    }
+    .context("Failed to perform a query to get booking status events by booking id.")?
+    .into_iter()
+    .map(|row| BookingStatusEvent {
+        event_id: row.event_id,
+        created_at: row.created_at,
+        booking_id: row.booking_id,
+        previous_status: row.previous_status,
+        new_status: row.new_status,
+        actor: match (row.actor_user_id, row.actor_token_id) {
+            (Some(user_id), _) => BookingActor::User { user_id },
+            (None, Some(token_id)) => BookingActor::Token { token_id },
+            (None, None) => BookingActor::System {
+                job: row.actor_job.unwrap_or_default(),
+            },
+        },
+        reason: row.reason,
+    })
+    .collect();
+
+    Ok(events)
+}
+
+#[tracing::instrument(name = "Get booked dates by rental id", skip(executor))]
+pub async fn get_booked_dates_by_rental_id<'e>(
     rental_id: &Uuid,
     booking_id: &Option<Uuid>,
     start_date: &OffsetDateTime,
     end_date: &OffsetDateTime,
     executor: &mut DbExecutor<'e>,
-) -> Result<Vec<(OffsetDateTime, i32)>, anyhow::Error> {
-    let base_sql = r#"
-        WITH dates AS (
-            SELECT generate_series(
-        "#;
+) -> Result<Vec<(OffsetDateTime, OffsetDateTime, i32)>, anyhow::Error> {
+    // Bookings that end after the start date and start before the end date
+    let query = sqlx::query!(
+        r#"
+        SELECT
+            start_date,
+            end_date,
+            quantity
+        FROM bookings
+        WHERE rental_id = $1
+            AND booking_status IN ('requested', 'accepted', 'confirmed', 'completed', 'disputed')
+            AND booking_id IS DISTINCT FROM $2
+            AND end_date > $3
+            AND start_date < $4
+        "#,
+        rental_id,
+        *booking_id,
+        start_date,
+        end_date,
+    );
 
-    let mut query = QueryBuilder::new(base_sql);
+    let booked_dates: Vec<(OffsetDateTime, OffsetDateTime, i32)> = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_all(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_all(*pool).await,
+    }
+    .context("Failed to perform a query to get booked dates by rental id.")?
+    .into_iter()
+    .map(|row| (row.start_date, row.end_date, row.quantity))
+    .collect();
+
+    Ok(booked_dates)
+}
+
+#[tracing::instrument(name = "Create booking dispute in database", skip(executor))]
+pub async fn create_booking_dispute_in_database<'e>(
+    booking: &Booking,
+    opened_by: &Uuid,
+    opened_by_party: &BookingParty,
+    dispute: DisputeBooking,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Uuid, anyhow::Error> {
+    let dispute_id = Uuid::new_v4();
+    let evidence = dispute.evidence.unwrap_or_default();
 
-    query.push_bind(start_date);
-    query.push("::timestamptz, ");
-    query.push_bind(end_date);
-    query.push("::timestamptz, '1 day'::interval) AS date), relevant_bookings AS (SELECT b.start_date, b.end_date, b.quantity FROM bookings b WHERE b.rental_id = ");
-    query.push_bind(rental_id);
-    query.push(
-        " AND b.booking_status IN ('requested', 'accepted', 'confirmed', 'completed', 'disputed')",
+    let query = sqlx::query!(
+        r#"
+        INSERT INTO booking_disputes (
+            dispute_id,
+            booking_id,
+            opened_by,
+            opened_by_party,
+            previous_status,
+            reason,
+            evidence,
+            dispute_status
+        )
+        VALUES (
+            $1,
+            $2,
+            $3,
+            $4,
+            $5,
+            $6,
+            $7,
+            $8
+        )
+        "#,
+        dispute_id,
+        booking.booking_id,
+        opened_by,
+        opened_by_party as &BookingParty,
+        booking.booking_status as BookingStatus,
+        dispute.reason,
+        &evidence,
+        BookingDisputeStatus::Open as BookingDisputeStatus,
     );
 
-    if let Some(exclude_id) = booking_id {
-        query.push(" AND b.booking_id IS DISTINCT FROM ");
-        query.push_bind(exclude_id);
+    match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
     }
+    .context("Failed to create new booking dispute in the database.")?;
+
+    Ok(dispute_id)
+}
 
-    query.push(
+#[tracing::instrument(
+    name = "Get latest booking dispute from database by booking id",
+    skip(executor)
+)]
+pub async fn get_latest_booking_dispute_from_database_by_booking_id<'e>(
+    booking_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Option<BookingDispute>, anyhow::Error> {
+    let query = sqlx::query!(
         r#"
-        )
-        SELECT date, COALESCE(SUM(rb.quantity)::INTEGER, 0) as booked_quantity  -- Cast SUM result to INTEGER
-        FROM dates d
-        LEFT JOIN relevant_bookings rb ON d.date BETWEEN rb.start_date AND rb.end_date
-        GROUP BY date
-        ORDER BY date
-    "#,
+        SELECT
+            dispute_id,
+            created_at,
+            updated_at,
+            booking_id,
+            opened_by,
+            opened_by_party as "opened_by_party: BookingParty",
+            previous_status as "previous_status: BookingStatus",
+            reason,
+            evidence,
+            dispute_status as "dispute_status: BookingDisputeStatus",
+            resolution as "resolution: BookingDisputeResolution",
+            refund_amount,
+            resolution_note,
+            resolved_by,
+            resolved_at
+        FROM booking_disputes
+        WHERE booking_id = $1
+        ORDER BY created_at DESC
+        LIMIT 1
+        "#,
+        booking_id,
     );
 
-    let query = query.build();
+    let dispute: Option<BookingDispute> = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_optional(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_optional(*pool).await,
+    }
+    .context("Failed to perform a query to get booking dispute by booking id.")?
+    .map(|row| BookingDispute {
+        dispute_id: row.dispute_id,
+        created_at: row.created_at,
+        updated_at: row.updated_at,
+        booking_id: row.booking_id,
+        opened_by: row.opened_by,
+        opened_by_party: row.opened_by_party,
+        previous_status: row.previous_status,
+        reason: row.reason,
+        evidence: row.evidence,
+        dispute_status: row.dispute_status,
+        resolution: row.resolution,
+        refund_amount: row.refund_amount,
+        resolution_note: row.resolution_note,
+        resolved_by: row.resolved_by,
+        resolved_at: row.resolved_at,
+    });
+
+    Ok(dispute)
+}
+
+#[tracing::instrument(name = "Resolve booking dispute in database", skip(executor))]
+pub async fn resolve_booking_dispute_in_database<'e>(
+    dispute_id: &Uuid,
+    resolved_by: &Uuid,
+    resolution: &ResolveBookingDispute,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        UPDATE booking_disputes
+        SET
+            dispute_status = $2,
+            resolution = $3,
+            refund_amount = $4,
+            resolution_note = $5,
+            resolved_by = $6,
+            resolved_at = NOW(),
+            updated_at = NOW()
+        WHERE dispute_id = $1
+        "#,
+        dispute_id,
+        BookingDisputeStatus::Resolved as BookingDisputeStatus,
+        resolution.resolution as BookingDisputeResolution,
+        resolution.refund_amount,
+        resolution.resolution_note,
+        resolved_by,
+    );
+
+    match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to perform a query to resolve booking dispute.")?;
+
+    Ok(())
+}
+
+#[tracing::instrument(name = "Get booking operator role from database", skip(executor))]
+pub async fn get_booking_operator_role_from_database_by_user_id<'e>(
+    user_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Option<BookingOperatorRole>, anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        SELECT operator_role as "operator_role: BookingOperatorRole"
+        FROM booking_operators
+        WHERE user_id = $1
+        "#,
+        user_id,
+    );
+
+    let operator = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_optional(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_optional(*pool).await,
+    }
+    .context("Failed to perform a query to get booking operator role.")?;
+
+    Ok(operator.map(|row| row.operator_role))
+}
+
+#[tracing::instrument(
+    name = "Get vendor employees from database by vendor id",
+    skip(executor)
+)]
+pub async fn get_vendor_employees_from_database_by_vendor_id<'e>(
+    vendor_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<BookingVendorEmployee>, anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        SELECT
+            u.user_id,
+            u.email,
+            r.role as "role: RbacRole"
+        FROM vendor_employees r
+        JOIN users u ON u.user_id = r.user_id
+        WHERE r.vendor_id = $1
+        ORDER BY u.email
+        "#,
+        vendor_id,
+    );
 
-    let availability_data: Vec<(OffsetDateTime, i32)> = match executor {
+    let employees = match executor {
         DbExecutor::Transaction(transaction) => query.fetch_all(&mut **transaction).await,
         DbExecutor::Pool(pool) => query.fetch_all(*pool).await,
     }
-    .context("Failed to perform a query to get availability info")?
+    .context("Failed to perform a query to get vendor employees by vendor id.")?
     .into_iter()
-    .map(|record| {
-        let date: OffsetDateTime = record.get("date");
-        let booked_quantity: i32 = record.get("booked_quantity"); // Ensured as i32
-        (date, booked_quantity)
+    .map(|row| BookingVendorEmployee {
+        user_id: row.user_id,
+        email: row.email,
+        role: row.role,
     })
-    .collect::<Vec<(OffsetDateTime, i32)>>();
+    .collect();
+
+    Ok(employees)
+}
+
+#[tracing::instrument(
+    name = "Get expired partial booking transaction ids from database",
+    skip(executor)
+)]
+pub async fn get_expired_partial_booking_transaction_ids_from_database<'e>(
+    responded_before: &OffsetDateTime,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<Uuid>, anyhow::Error> {
+    // Transactions where every booking has been answered, at least one was accepted and at least
+    // one was declined or expired, and the last answer is older than the response window.
+    // Only transactions with an accepted booking are grouped, found through the
+    // bookings (booking_status, transaction_id) index, instead of the whole bookings table.
+    let query = sqlx::query!(
+        r#"
+        SELECT transaction_id
+        FROM bookings
+        WHERE transaction_id IN (
+            SELECT transaction_id
+            FROM bookings
+            WHERE booking_status = 'accepted'
+        )
+        GROUP BY transaction_id
+        HAVING COUNT(*) FILTER (WHERE booking_status = 'accepted') > 0
+            AND COUNT(*) FILTER (WHERE booking_status IN ('declined', 'expired')) > 0
+            AND COUNT(*) FILTER (WHERE booking_status = 'requested') = 0
+            AND MAX(updated_at) < $1
+        "#,
+        responded_before,
+    );
+
+    let transaction_ids: Vec<Uuid> = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_all(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_all(*pool).await,
+    }
+    .context("Failed to perform a query to get expired partial booking transactions")?
+    .into_iter()
+    .map(|row| row.transaction_id)
+    .collect();
+
+    Ok(transaction_ids)
+}
+
+#[tracing::instrument(
+    name = "Update booking details in database by booking id",
+    skip(executor)
+)]
+pub async fn update_booking_details_in_database_by_booking_id<'e>(
+    booking_id: &Uuid,
+    changes: &BookingChanges,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        UPDATE bookings
+        SET
+            quantity = $2,
+            start_date = $3,
+            end_date = $4,
+            total = $5,
+            updated_at = NOW()
+        WHERE booking_id = $1
+        "#,
+        booking_id,
+        changes.quantity,
+        changes.start_date,
+        changes.end_date,
+        changes.total,
+    );
+
+    match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to perform a query to update booking details by booking id.")?;
+
+    Ok(())
+}
+
+#[tracing::instrument(name = "Create booking modification in database", skip(executor))]
+pub async fn create_booking_modification_in_database<'e>(
+    booking: &Booking,
+    requested_by: &Uuid,
+    requested_by_party: &BookingParty,
+    changes: &BookingChanges,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Uuid, anyhow::Error> {
+    let modification_id = Uuid::new_v4();
+    let price_delta = changes.total - booking.total;
+
+    let query = sqlx::query!(
+        r#"
+        INSERT INTO booking_modifications (
+            modification_id,
+            booking_id,
+            requested_by,
+            requested_by_party,
+            previous_quantity,
+            previous_start_date,
+            previous_end_date,
+            previous_total,
+            quantity,
+            start_date,
+            end_date,
+            total,
+            price_delta,
+            modification_status
+        )
+        VALUES (
+            $1,
+            $2,
+            $3,
+            $4,
+            $5,
+            $6,
+            $7,
+            $8,
+            $9,
+            $10,
+            $11,
+            $12,
+            $13,
+            $14
+        )
+        "#,
+        modification_id,
+        booking.booking_id,
+        requested_by,
+        requested_by_party as &BookingParty,
+        booking.quantity,
+        booking.start_date,
+        booking.end_date,
+        booking.total,
+        changes.quantity,
+        changes.start_date,
+        changes.end_date,
+        changes.total,
+        price_delta,
+        BookingModificationStatus::Pending as BookingModificationStatus,
+    );
+
+    match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to create new booking modification in the database.")?;
+
+    Ok(modification_id)
+}
+
+#[tracing::instrument(
+    name = "Get booking modifications from database by booking id",
+    skip(executor)
+)]
+pub async fn get_booking_modifications_from_database_by_booking_id<'e>(
+    booking_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<BookingModification>, anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        SELECT
+            modification_id,
+            created_at,
+            updated_at,
+            booking_id,
+            requested_by,
+            requested_by_party as "requested_by_party: BookingParty",
+            previous_quantity,
+            previous_start_date,
+            previous_end_date,
+            previous_total,
+            quantity,
+            start_date,
+            end_date,
+            total,
+            price_delta,
+            modification_status as "modification_status: BookingModificationStatus",
+            resolved_by,
+            resolved_at
+        FROM booking_modifications
+        WHERE booking_id = $1
+        ORDER BY created_at DESC
+        "#,
+        booking_id,
+    );
+
+    let modifications: Vec<BookingModification> = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_all(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_all(*pool).await,
+    }
+    .context("Failed to perform a query to get booking modifications by booking id.")?
+    .into_iter()
+    .map(|row| BookingModification {
+        modification_id: row.modification_id,
+        created_at: row.created_at,
+        updated_at: row.updated_at,
+        booking_id: row.booking_id,
+        requested_by: row.requested_by,
+        requested_by_party: row.requested_by_party,
+        previous_quantity: row.previous_quantity,
+        previous_start_date: row.previous_start_date,
+        previous_end_date: row.previous_end_date,
+        previous_total: row.previous_total,
+        quantity: row.quantity,
+        start_date: row.start_date,
+        end_date: row.end_date,
+        total: row.total,
+        price_delta: row.price_delta,
+        modification_status: row.modification_status,
+        resolved_by: row.resolved_by,
+        resolved_at: row.resolved_at,
+    })
+    .collect();
+
+    Ok(modifications)
+}
+
+#[tracing::instrument(
+    name = "Update booking modification status in database by modification id",
+    skip(executor)
+)]
+pub async fn update_booking_modification_status_in_database_by_modification_id<'e>(
+    modification_id: &Uuid,
+    modification_status: &BookingModificationStatus,
+    resolved_by: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        UPDATE booking_modifications
+        SET
+            modification_status = $2,
+            resolved_by = $3,
+            resolved_at = NOW(),
+            updated_at = NOW()
+        WHERE modification_id = $1
+        "#,
+        modification_id,
+        modification_status as &BookingModificationStatus,
+        resolved_by,
+    );
+
+    match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to perform a query to update booking modification status.")?;
+
+    Ok(())
+}
+
+#[tracing::instrument(
+    name = "Get cancellation policy from database by vendor id",
+    skip(executor)
+)]
+pub async fn get_cancellation_policy_from_database_by_vendor_id<'e>(
+    vendor_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Option<CancellationPolicy>, anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        SELECT
+            vendor_id,
+            created_at,
+            updated_at,
+            name,
+            tiers as "tiers: Json<Vec<CancellationPolicyTier>>"
+        FROM booking_cancellation_policies
+        WHERE vendor_id = $1
+        "#,
+        vendor_id,
+    );
+
+    let policy: Option<CancellationPolicy> = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_optional(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_optional(*pool).await,
+    }
+    .context("Failed to perform a query to get cancellation policy by vendor id.")?
+    .map(|row| CancellationPolicy {
+        vendor_id: row.vendor_id,
+        created_at: row.created_at,
+        updated_at: row.updated_at,
+        name: row.name,
+        tiers: row.tiers.0,
+    });
+
+    Ok(policy)
+}
+
+#[tracing::instrument(name = "Upsert cancellation policy in database", skip(executor))]
+pub async fn upsert_cancellation_policy_in_database<'e>(
+    vendor_id: &Uuid,
+    policy: &UpsertCancellationPolicy,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        INSERT INTO booking_cancellation_policies (
+            vendor_id,
+            name,
+            tiers
+        )
+        VALUES (
+            $1,
+            $2,
+            $3
+        )
+        ON CONFLICT (vendor_id) DO UPDATE
+        SET
+            name = EXCLUDED.name,
+            tiers = EXCLUDED.tiers,
+            updated_at = NOW()
+        "#,
+        vendor_id,
+        policy.name,
+        Json(&policy.tiers) as _,
+    );
+
+    match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to upsert cancellation policy in the database.")?;
+
+    Ok(())
+}
+
+#[tracing::instrument(name = "Acquire booking job lease in database", skip(executor))]
+pub async fn acquire_booking_job_lease_in_database<'e>(
+    job_name: &str,
+    instance_id: &Uuid,
+    lease_seconds: f64,
+    executor: &mut DbExecutor<'e>,
+) -> Result<bool, anyhow::Error> {
+    // Only takes the lease when no other instance holds an unexpired one
+    let query = sqlx::query!(
+        r#"
+        INSERT INTO booking_job_runs (
+            job_name,
+            leased_by,
+            leased_until,
+            last_started_at
+        )
+        VALUES (
+            $1,
+            $2,
+            NOW() + make_interval(secs => $3),
+            NOW()
+        )
+        ON CONFLICT (job_name) DO UPDATE
+        SET
+            leased_by = EXCLUDED.leased_by,
+            leased_until = EXCLUDED.leased_until,
+            last_started_at = EXCLUDED.last_started_at
+        WHERE booking_job_runs.leased_until < NOW()
+        RETURNING job_name
+        "#,
+        job_name,
+        instance_id,
+        lease_seconds,
+    );
+
+    let acquired = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_optional(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_optional(*pool).await,
+    }
+    .context("Failed to perform a query to acquire booking job lease.")?
+    .is_some();
+
+    Ok(acquired)
+}
+
+#[tracing::instrument(name = "Renew booking job lease in database", skip(executor))]
+pub async fn renew_booking_job_lease_in_database<'e>(
+    job_name: &str,
+    instance_id: &Uuid,
+    lease_seconds: f64,
+    executor: &mut DbExecutor<'e>,
+) -> Result<bool, anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        UPDATE booking_job_runs
+        SET leased_until = NOW() + make_interval(secs => $3)
+        WHERE job_name = $1
+            AND leased_by = $2
+        "#,
+        job_name,
+        instance_id,
+        lease_seconds,
+    );
+
+    let result = match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to perform a query to renew booking job lease.")?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+#[tracing::instrument(name = "Finish booking job run in database", skip(executor))]
+pub async fn finish_booking_job_run_in_database<'e>(
+    job_name: &str,
+    instance_id: &Uuid,
+    outcome: &BookingJobOutcome,
+    message: &str,
+    interval_seconds: f64,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), anyhow::Error> {
+    // Renewals push the lease past the end of the run, so it's set back to one interval after
+    // the start to keep the job on its schedule
+    let query = sqlx::query!(
+        r#"
+        UPDATE booking_job_runs
+        SET
+            leased_until = last_started_at + make_interval(secs => $5),
+            last_finished_at = NOW(),
+            last_outcome = $3,
+            last_message = $4
+        WHERE job_name = $1
+            AND leased_by = $2
+        "#,
+        job_name,
+        instance_id,
+        outcome as &BookingJobOutcome,
+        message,
+        interval_seconds,
+    );
+
+    match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to perform a query to finish booking job run.")?;
+
+    Ok(())
+}
+
+#[tracing::instrument(name = "Get booking job runs from database", skip(executor))]
+pub async fn get_booking_job_runs_from_database<'e>(
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<BookingJobRun>, anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        SELECT
+            job_name,
+            leased_by,
+            leased_until,
+            last_started_at,
+            last_finished_at,
+            last_outcome as "last_outcome: BookingJobOutcome",
+            last_message
+        FROM booking_job_runs
+        ORDER BY job_name
+        "#,
+    );
+
+    let job_runs: Vec<BookingJobRun> = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_all(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_all(*pool).await,
+    }
+    .context("Failed to perform a query to get booking job runs.")?
+    .into_iter()
+    .map(|row| BookingJobRun {
+        job_name: row.job_name,
+        leased_by: row.leased_by,
+        leased_until: row.leased_until,
+        last_started_at: row.last_started_at,
+        last_finished_at: row.last_finished_at,
+        last_outcome: row.last_outcome,
+        last_message: row.last_message,
+    })
+    .collect();
+
+    Ok(job_runs)
+}
+
+#[tracing::instrument(name = "Delete pending booking holds in database", skip(executor))]
+pub async fn delete_pending_booking_holds_in_database<'e>(
+    expired_before: &OffsetDateTime,
+    hold_duration_minutes: i64,
+    executor: &mut DbExecutor<'e>,
+) -> Result<u64, anyhow::Error> {
+    // Keyed on the expiry rather than creation, so extended holds aren't deleted while still active
+    let query = sqlx::query!(
+        r#"
+        DELETE FROM booking_holds
+        WHERE booking_hold_status = 'pending'
+            AND COALESCE(expires_at, created_at + make_interval(mins => $2)) < $1
+        "#,
+        expired_before,
+        hold_duration_minutes as i32,
+    );
+
+    let rows_affected = match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to perform a query to delete pending booking holds.")?
+    .rows_affected();
+
+    Ok(rows_affected)
+}
+
+#[tracing::instrument(
+    name = "Get booking vendor settings from database by vendor id",
+    skip(executor)
+)]
+pub async fn get_booking_vendor_settings_from_database_by_vendor_id<'e>(
+    vendor_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Option<BookingVendorSettings>, anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        SELECT
+            vendor_id,
+            response_deadline_hours,
+            completion_grace_hours,
+            reminder_lead_hours,
+            timezone
+        FROM booking_vendor_settings
+        WHERE vendor_id = $1
+        "#,
+        vendor_id,
+    );
+
+    let settings: Option<BookingVendorSettings> = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_optional(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_optional(*pool).await,
+    }
+    .context("Failed to perform a query to get booking vendor settings by vendor id.")?
+    .map(|row| BookingVendorSettings {
+        vendor_id: row.vendor_id,
+        response_deadline_hours: row.response_deadline_hours,
+        completion_grace_hours: row.completion_grace_hours,
+        reminder_lead_hours: row.reminder_lead_hours,
+        timezone: row.timezone,
+    });
+
+    Ok(settings)
+}
+
+#[tracing::instrument(name = "Upsert booking vendor settings in database", skip(executor))]
+pub async fn upsert_booking_vendor_settings_in_database<'e>(
+    settings: &BookingVendorSettings,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        INSERT INTO booking_vendor_settings (
+            vendor_id,
+            response_deadline_hours,
+            completion_grace_hours,
+            reminder_lead_hours,
+            timezone
+        )
+        VALUES (
+            $1,
+            $2,
+            $3,
+            $4,
+            $5
+        )
+        ON CONFLICT (vendor_id) DO UPDATE
+        SET
+            response_deadline_hours = EXCLUDED.response_deadline_hours,
+            completion_grace_hours = EXCLUDED.completion_grace_hours,
+            reminder_lead_hours = EXCLUDED.reminder_lead_hours,
+            timezone = EXCLUDED.timezone,
+            updated_at = NOW()
+        "#,
+        settings.vendor_id,
+        settings.response_deadline_hours,
+        settings.completion_grace_hours,
+        settings.reminder_lead_hours,
+        settings.timezone,
+    );
+
+    match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to upsert booking vendor settings in the database.")?;
+
+    Ok(())
+}
+
+#[tracing::instrument(
+    name = "Get booking email preferences from database by user id",
+    skip(executor)
+)]
+pub async fn get_booking_email_preferences_from_database_by_user_id<'e>(
+    user_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Option<BookingEmailPreferences>, anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        SELECT
+            user_id,
+            locale
+        FROM booking_email_preferences
+        WHERE user_id = $1
+        "#,
+        user_id,
+    );
+
+    let preferences: Option<BookingEmailPreferences> = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_optional(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_optional(*pool).await,
+    }
+    .context("Failed to perform a query to get booking email preferences by user id.")?
+    .map(|row| BookingEmailPreferences {
+        user_id: row.user_id,
+        locale: row.locale,
+    });
+
+    Ok(preferences)
+}
+
+#[tracing::instrument(name = "Upsert booking email preferences in database", skip(executor))]
+pub async fn upsert_booking_email_preferences_in_database<'e>(
+    preferences: &BookingEmailPreferences,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        INSERT INTO booking_email_preferences (
+            user_id,
+            locale
+        )
+        VALUES (
+            $1,
+            $2
+        )
+        ON CONFLICT (user_id) DO UPDATE
+        SET
+            locale = EXCLUDED.locale,
+            updated_at = NOW()
+        "#,
+        preferences.user_id,
+        preferences.locale,
+    );
+
+    match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to upsert booking email preferences in the database.")?;
+
+    Ok(())
+}
+
+#[tracing::instrument(
+    name = "Get booking rental settings from database by rental id",
+    skip(executor)
+)]
+pub async fn get_booking_rental_settings_from_database_by_rental_id<'e>(
+    rental_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Option<BookingRentalSettings>, anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        SELECT
+            rental_id,
+            booking_granularity as "booking_granularity: BookingGranularity",
+            timezone,
+            buffer_before_minutes,
+            buffer_after_minutes
+        FROM booking_rental_settings
+        WHERE rental_id = $1
+        "#,
+        rental_id,
+    );
+
+    let settings: Option<BookingRentalSettings> = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_optional(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_optional(*pool).await,
+    }
+    .context("Failed to perform a query to get booking rental settings by rental id.")?
+    .map(|row| BookingRentalSettings {
+        rental_id: row.rental_id,
+        booking_granularity: row.booking_granularity,
+        timezone: row.timezone,
+        buffer_before_minutes: row.buffer_before_minutes,
+        buffer_after_minutes: row.buffer_after_minutes,
+    });
+
+    Ok(settings)
+}
+
+#[tracing::instrument(name = "Upsert booking rental settings in database", skip(executor))]
+pub async fn upsert_booking_rental_settings_in_database<'e>(
+    settings: &BookingRentalSettings,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        INSERT INTO booking_rental_settings (
+            rental_id,
+            booking_granularity,
+            timezone,
+            buffer_before_minutes,
+            buffer_after_minutes
+        )
+        VALUES (
+            $1,
+            $2,
+            $3,
+            $4,
+            $5
+        )
+        ON CONFLICT (rental_id) DO UPDATE
+        SET
+            booking_granularity = EXCLUDED.booking_granularity,
+            timezone = EXCLUDED.timezone,
+            buffer_before_minutes = EXCLUDED.buffer_before_minutes,
+            buffer_after_minutes = EXCLUDED.buffer_after_minutes,
+            updated_at = NOW()
+        "#,
+        settings.rental_id,
+        settings.booking_granularity as BookingGranularity,
+        settings.timezone,
+        settings.buffer_before_minutes,
+        settings.buffer_after_minutes,
+    );
+
+    match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to upsert booking rental settings in the database.")?;
+
+    Ok(())
+}
+
+#[tracing::instrument(
+    name = "Get active booking count from database by rental id",
+    skip(executor)
+)]
+pub async fn get_active_booking_count_from_database_by_rental_id<'e>(
+    rental_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<i64, anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        SELECT COUNT(*) as "count!"
+        FROM bookings
+        WHERE rental_id = $1
+            AND booking_status IN ('requested', 'accepted', 'confirmed', 'disputed')
+            AND end_date >= NOW()
+        "#,
+        rental_id,
+    );
+
+    let count = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_one(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_one(*pool).await,
+    }
+    .context("Failed to perform a query to count active bookings by rental id.")?
+    .count;
+
+    Ok(count)
+}
+
+#[tracing::instrument(
+    name = "Get active booking count from database by vendor id",
+    skip(executor)
+)]
+pub async fn get_active_booking_count_from_database_by_vendor_id<'e>(
+    vendor_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<i64, anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        SELECT COUNT(*) as "count!"
+        FROM bookings
+        WHERE vendor_id = $1
+            AND booking_status IN ('requested', 'accepted', 'confirmed', 'disputed')
+            AND end_date >= NOW()
+        "#,
+        vendor_id,
+    );
+
+    let count = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_one(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_one(*pool).await,
+    }
+    .context("Failed to perform a query to count active bookings by vendor id.")?
+    .count;
+
+    Ok(count)
+}
+
+#[tracing::instrument(
+    name = "Get unanswered booking request ids from database",
+    skip(executor)
+)]
+pub async fn get_unanswered_booking_request_ids_from_database<'e>(
+    default_response_deadline_hours: i32,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<Uuid>, anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        SELECT b.booking_id
+        FROM bookings b
+        LEFT JOIN booking_vendor_settings s ON s.vendor_id = b.vendor_id
+        WHERE b.booking_status = 'requested'
+            AND b.created_at + make_interval(
+                hours => COALESCE(s.response_deadline_hours, $1)
+            ) < NOW()
+        ORDER BY b.created_at
+        "#,
+        default_response_deadline_hours,
+    );
+
+    let booking_ids: Vec<Uuid> = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_all(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_all(*pool).await,
+    }
+    .context("Failed to perform a query to get unanswered booking requests")?
+    .into_iter()
+    .map(|row| row.booking_id)
+    .collect();
+
+    Ok(booking_ids)
+}
+
+#[tracing::instrument(name = "Get completable booking ids from database", skip(executor))]
+pub async fn get_completable_booking_ids_from_database<'e>(
+    default_completion_grace_hours: i32,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<Uuid>, anyhow::Error> {
+    // Confirmed bookings past their vendor's grace period without an open dispute
+    let query = sqlx::query!(
+        r#"
+        SELECT b.booking_id
+        FROM bookings b
+        LEFT JOIN booking_vendor_settings s ON s.vendor_id = b.vendor_id
+        WHERE b.booking_status = 'confirmed'
+            AND b.end_date + make_interval(
+                hours => COALESCE(s.completion_grace_hours, $1)
+            ) < NOW()
+            AND NOT EXISTS (
+                SELECT 1
+                FROM booking_disputes d
+                WHERE d.booking_id = b.booking_id
+                    AND d.dispute_status = 'open'
+            )
+        ORDER BY b.end_date
+        "#,
+        default_completion_grace_hours,
+    );
+
+    let booking_ids: Vec<Uuid> = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_all(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_all(*pool).await,
+    }
+    .context("Failed to perform a query to get completable bookings")?
+    .into_iter()
+    .map(|row| row.booking_id)
+    .collect();
+
+    Ok(booking_ids)
+}
+
+#[tracing::instrument(
+    name = "Get booking hold expiry from database by booking hold id",
+    skip(executor)
+)]
+pub async fn get_booking_hold_expiry_from_database_by_booking_hold_id<'e>(
+    booking_hold_id: &Uuid,
+    hold_duration_minutes: i64,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Option<(Uuid, Option<OffsetDateTime>)>, anyhow::Error> {
+    // Pending holds the hold expiry job hasn't reached yet expire the hold duration after creation
+    let query = sqlx::query!(
+        r#"
+        SELECT
+            transaction_id,
+            CASE
+                WHEN booking_hold_status = 'pending'
+                    THEN COALESCE(expires_at, created_at + make_interval(mins => $2))
+                ELSE expires_at
+            END as expires_at
+        FROM booking_holds
+        WHERE booking_hold_id = $1
+        "#,
+        booking_hold_id,
+        hold_duration_minutes as i32,
+    );
+
+    let hold = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_optional(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_optional(*pool).await,
+    }
+    .context("Failed to perform a query to get booking hold expiry by booking hold id.")?
+    .map(|row| (row.transaction_id, row.expires_at));
+
+    Ok(hold)
+}
+
+#[tracing::instrument(name = "Extend booking hold in database", skip(executor))]
+pub async fn extend_booking_hold_in_database<'e>(
+    booking_hold_id: &Uuid,
+    expires_at: &OffsetDateTime,
+    hold_duration_minutes: i64,
+    executor: &mut DbExecutor<'e>,
+) -> Result<bool, anyhow::Error> {
+    // Holds that already expired have released their quantity and can't be extended
+    let query = sqlx::query!(
+        r#"
+        UPDATE booking_holds
+        SET
+            expires_at = $2,
+            updated_at = NOW()
+        WHERE booking_hold_id = $1
+            AND booking_hold_status = 'pending'
+            AND COALESCE(expires_at, created_at + make_interval(mins => $3)) > NOW()
+        "#,
+        booking_hold_id,
+        expires_at,
+        hold_duration_minutes as i32,
+    );
+
+    let rows_affected = match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to perform a query to extend booking hold.")?
+    .rows_affected();
+
+    Ok(rows_affected > 0)
+}
+
+#[tracing::instrument(name = "Set pending booking hold expiries in database", skip(executor))]
+pub async fn set_pending_booking_hold_expiries_in_database<'e>(
+    hold_duration_minutes: i64,
+    executor: &mut DbExecutor<'e>,
+) -> Result<u64, anyhow::Error> {
+    // Holds are created by the booking holds module without an expiry, they expire the hold
+    // duration after they were created
+    let query = sqlx::query!(
+        r#"
+        UPDATE booking_holds
+        SET expires_at = created_at + make_interval(mins => $1)
+        WHERE booking_hold_status = 'pending'
+            AND expires_at IS NULL
+        "#,
+        hold_duration_minutes as i32,
+    );
+
+    let rows_affected = match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to perform a query to set pending booking hold expiries.")?
+    .rows_affected();
+
+    Ok(rows_affected)
+}
+
+#[tracing::instrument(name = "Get expired booking holds from database", skip(executor))]
+pub async fn get_expired_booking_holds_from_database<'e>(
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<(Uuid, Uuid)>, anyhow::Error> {
+    // Returns the booking hold and transaction ids of expired holds that haven't been announced
+    let query = sqlx::query!(
+        r#"
+        SELECT
+            h.booking_hold_id,
+            h.transaction_id
+        FROM booking_holds h
+        WHERE h.booking_hold_status = 'pending'
+            AND h.expires_at <= NOW()
+            AND NOT EXISTS (
+                SELECT 1
+                FROM booking_hold_expirations e
+                WHERE e.booking_hold_id = h.booking_hold_id
+            )
+        ORDER BY h.expires_at
+        "#,
+    );
+
+    let holds: Vec<(Uuid, Uuid)> = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_all(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_all(*pool).await,
+    }
+    .context("Failed to perform a query to get expired booking holds.")?
+    .into_iter()
+    .map(|row| (row.booking_hold_id, row.transaction_id))
+    .collect();
+
+    Ok(holds)
+}
+
+#[tracing::instrument(name = "Create booking hold expiration in database", skip(executor))]
+pub async fn create_booking_hold_expiration_in_database<'e>(
+    booking_hold_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<bool, anyhow::Error> {
+    // Returns false when the expiry was already announced
+    let query = sqlx::query!(
+        r#"
+        INSERT INTO booking_hold_expirations (
+            booking_hold_id
+        )
+        VALUES (
+            $1
+        )
+        ON CONFLICT (booking_hold_id) DO NOTHING
+        "#,
+        booking_hold_id,
+    );
+
+    let rows_affected = match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to insert booking hold expiration into the database.")?
+    .rows_affected();
+
+    Ok(rows_affected > 0)
+}
+
+#[tracing::instrument(name = "Get due booking reminder ids from database", skip(executor))]
+pub async fn get_due_booking_reminder_ids_from_database<'e>(
+    kind: &BookingReminderKind,
+    default_reminder_lead_hours: i32,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<Uuid>, anyhow::Error> {
+    // Confirmed bookings inside their vendor's reminder window that haven't been reminded yet
+    let query = sqlx::query!(
+        r#"
+        SELECT b.booking_id
+        FROM bookings b
+        LEFT JOIN booking_vendor_settings s ON s.vendor_id = b.vendor_id
+        CROSS JOIN LATERAL (
+            SELECT CASE WHEN $1 = 'start' THEN b.start_date ELSE b.end_date END AS due_date
+        ) d
+        WHERE b.booking_status = 'confirmed'
+            AND d.due_date > NOW()
+            AND d.due_date - make_interval(
+                hours => COALESCE(s.reminder_lead_hours, $2)
+            ) <= NOW()
+            AND NOT EXISTS (
+                SELECT 1
+                FROM booking_reminders r
+                WHERE r.booking_id = b.booking_id
+                    AND r.reminder_kind = $1
+            )
+        ORDER BY d.due_date
+        "#,
+        kind as &BookingReminderKind,
+        default_reminder_lead_hours,
+    );
+
+    let booking_ids: Vec<Uuid> = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_all(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_all(*pool).await,
+    }
+    .context("Failed to perform a query to get due booking reminders")?
+    .into_iter()
+    .map(|row| row.booking_id)
+    .collect();
+
+    Ok(booking_ids)
+}
+
+#[tracing::instrument(name = "Create booking reminder in database", skip(executor))]
+pub async fn create_booking_reminder_in_database<'e>(
+    booking_id: &Uuid,
+    kind: &BookingReminderKind,
+    executor: &mut DbExecutor<'e>,
+) -> Result<bool, anyhow::Error> {
+    // Returns false when the reminder was already sent
+    let query = sqlx::query!(
+        r#"
+        INSERT INTO booking_reminders (
+            booking_id,
+            reminder_kind
+        )
+        VALUES (
+            $1,
+            $2
+        )
+        ON CONFLICT (booking_id, reminder_kind) DO NOTHING
+        "#,
+        booking_id,
+        kind as &BookingReminderKind,
+    );
+
+    let rows_affected = match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to insert booking reminder into the database.")?
+    .rows_affected();
+
+    Ok(rows_affected > 0)
+}
+
+#[tracing::instrument(name = "Get pick list vendor ids from database", skip(executor))]
+pub async fn get_pick_list_vendor_ids_from_database<'e>(
+    start_date: &OffsetDateTime,
+    end_date: &OffsetDateTime,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<Uuid>, anyhow::Error> {
+    // Vendors with confirmed bookings starting or ending in the window
+    let query = sqlx::query!(
+        r#"
+        SELECT DISTINCT
+            b.vendor_id
+        FROM bookings b
+        WHERE b.booking_status = 'confirmed'
+            AND (
+                (b.start_date >= $1 AND b.start_date < $2)
+                OR (b.end_date >= $1 AND b.end_date < $2)
+            )
+        "#,
+        start_date,
+        end_date,
+    );
+
+    let vendor_ids: Vec<Uuid> = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_all(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_all(*pool).await,
+    }
+    .context("Failed to perform a query to get pick list vendor ids")?
+    .into_iter()
+    .map(|row| row.vendor_id)
+    .collect();
+
+    Ok(vendor_ids)
+}
+
+#[tracing::instrument(name = "Get due pick list booking ids from database", skip(executor))]
+pub async fn get_due_pick_list_booking_ids_from_database<'e>(
+    vendor_ids: &[Uuid],
+    pick_date: &OffsetDateTime,
+    pick_date_end: &OffsetDateTime,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<(Uuid, Uuid)>, anyhow::Error> {
+    // Confirmed bookings starting or ending on the pick date for vendors without a pick list
+    // that day. The vendors share a timezone, so the day has the same bounds for all of them.
+    let query = sqlx::query!(
+        r#"
+        SELECT
+            b.vendor_id,
+            b.booking_id
+        FROM bookings b
+        WHERE b.vendor_id = ANY($1)
+            AND b.booking_status = 'confirmed'
+            AND (
+                (b.start_date >= $2 AND b.start_date < $3)
+                OR (b.end_date >= $2 AND b.end_date < $3)
+            )
+            AND NOT EXISTS (
+                SELECT 1
+                FROM booking_pick_list_reminders p
+                WHERE p.vendor_id = b.vendor_id
+                    AND p.pick_date = $2
+            )
+        ORDER BY b.vendor_id, b.start_date
+        "#,
+        vendor_ids,
+        pick_date,
+        pick_date_end,
+    );
+
+    let bookings: Vec<(Uuid, Uuid)> = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_all(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_all(*pool).await,
+    }
+    .context("Failed to perform a query to get due pick list bookings")?
+    .into_iter()
+    .map(|row| (row.vendor_id, row.booking_id))
+    .collect();
+
+    Ok(bookings)
+}
+
+#[tracing::instrument(name = "Create booking pick list reminder in database", skip(executor))]
+pub async fn create_booking_pick_list_reminder_in_database<'e>(
+    vendor_id: &Uuid,
+    pick_date: &OffsetDateTime,
+    executor: &mut DbExecutor<'e>,
+) -> Result<bool, anyhow::Error> {
+    // Returns false when the vendor already got a pick list for the day
+    let query = sqlx::query!(
+        r#"
+        INSERT INTO booking_pick_list_reminders (
+            vendor_id,
+            pick_date
+        )
+        VALUES (
+            $1,
+            $2
+        )
+        ON CONFLICT (vendor_id, pick_date) DO NOTHING
+        "#,
+        vendor_id,
+        pick_date,
+    );
+
+    let rows_affected = match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to insert booking pick list reminder into the database.")?
+    .rows_affected();
+
+    Ok(rows_affected > 0)
+}
+
+#[tracing::instrument(
+    name = "Get unnotified booking request ids from database",
+    skip(executor)
+)]
+pub async fn get_unnotified_booking_request_ids_from_database<'e>(
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<Uuid>, anyhow::Error> {
+    // Requests older than a day are left alone so vendors don't get a backlog of stale emails
+    let query = sqlx::query!(
+        r#"
+        SELECT b.booking_id
+        FROM bookings b
+        WHERE b.booking_status = 'requested'
+            AND b.created_at > NOW() - INTERVAL '1 day'
+            AND NOT EXISTS (
+                SELECT 1
+                FROM booking_vendor_notifications n
+                WHERE n.booking_id = b.booking_id
+                    AND n.notification_kind = 'request'
+            )
+        ORDER BY b.created_at
+        "#,
+    );
+
+    let booking_ids: Vec<Uuid> = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_all(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_all(*pool).await,
+    }
+    .context("Failed to perform a query to get unnotified booking requests")?
+    .into_iter()
+    .map(|row| row.booking_id)
+    .collect();
+
+    Ok(booking_ids)
+}
+
+#[tracing::instrument(
+    name = "Get due response deadline booking ids from database",
+    skip(executor)
+)]
+pub async fn get_due_response_deadline_booking_ids_from_database<'e>(
+    default_response_deadline_hours: i32,
+    warning_hours: i32,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<Uuid>, anyhow::Error> {
+    // Unanswered requests whose response deadline is inside the warning window
+    let query = sqlx::query!(
+        r#"
+        SELECT b.booking_id
+        FROM bookings b
+        LEFT JOIN booking_vendor_settings s ON s.vendor_id = b.vendor_id
+        CROSS JOIN LATERAL (
+            SELECT b.created_at + make_interval(
+                hours => COALESCE(s.response_deadline_hours, $1)
+            ) AS deadline
+        ) d
+        WHERE b.booking_status = 'requested'
+            AND d.deadline > NOW()
+            AND d.deadline - make_interval(hours => $2) <= NOW()
+            AND NOT EXISTS (
+                SELECT 1
+                FROM booking_vendor_notifications n
+                WHERE n.booking_id = b.booking_id
+                    AND n.notification_kind = 'response_deadline'
+            )
+        ORDER BY d.deadline
+        "#,
+        default_response_deadline_hours,
+        warning_hours,
+    );
+
+    let booking_ids: Vec<Uuid> = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_all(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_all(*pool).await,
+    }
+    .context("Failed to perform a query to get due response deadline bookings")?
+    .into_iter()
+    .map(|row| row.booking_id)
+    .collect();
+
+    Ok(booking_ids)
+}
+
+#[tracing::instrument(
+    name = "Create booking vendor notification in database",
+    skip(executor)
+)]
+pub async fn create_booking_vendor_notification_in_database<'e>(
+    booking_id: &Uuid,
+    kind: &BookingVendorNotificationKind,
+    executor: &mut DbExecutor<'e>,
+) -> Result<bool, anyhow::Error> {
+    // Returns false when the vendor was already notified
+    let query = sqlx::query!(
+        r#"
+        INSERT INTO booking_vendor_notifications (
+            booking_id,
+            notification_kind
+        )
+        VALUES (
+            $1,
+            $2
+        )
+        ON CONFLICT (booking_id, notification_kind) DO NOTHING
+        "#,
+        booking_id,
+        kind as &BookingVendorNotificationKind,
+    );
+
+    let rows_affected = match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to insert booking vendor notification into the database.")?
+    .rows_affected();
+
+    Ok(rows_affected > 0)
+}
+
+#[tracing::instrument(name = "Create booking access token in database", skip(executor))]
+pub async fn create_booking_access_token_in_database<'e>(
+    token_id: &Uuid,
+    booking_id: &Uuid,
+    expires_at: &OffsetDateTime,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        INSERT INTO booking_access_tokens (
+            token_id,
+            booking_id,
+            expires_at
+        )
+        VALUES (
+            $1,
+            $2,
+            $3
+        )
+        "#,
+        token_id,
+        booking_id,
+        expires_at,
+    );
+
+    match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to insert booking access token into the database.")?;
+
+    Ok(())
+}
+
+#[tracing::instrument(name = "Get booking access token active from database", skip(executor))]
+pub async fn get_booking_access_token_active_from_database<'e>(
+    token_id: &Uuid,
+    booking_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<bool, anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        SELECT token_id
+        FROM booking_access_tokens
+        WHERE token_id = $1
+            AND booking_id = $2
+            AND revoked_at IS NULL
+            AND expires_at > NOW()
+        "#,
+        token_id,
+        booking_id,
+    );
+
+    let token = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_optional(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_optional(*pool).await,
+    }
+    .context("Failed to perform a query to get booking access token.")?;
+
+    Ok(token.is_some())
+}
+
+#[tracing::instrument(name = "Revoke booking access tokens in database", skip(executor))]
+pub async fn revoke_booking_access_tokens_in_database<'e>(
+    booking_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<u64, anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        UPDATE booking_access_tokens
+        SET revoked_at = NOW()
+        WHERE booking_id = $1
+            AND revoked_at IS NULL
+        "#,
+        booking_id,
+    );
+
+    let revoked = match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to revoke booking access tokens in the database.")?
+    .rows_affected();
+
+    Ok(revoked)
+}
+
+#[tracing::instrument(
+    name = "Get active booking calendar feed token id from database",
+    skip(executor)
+)]
+pub async fn get_active_booking_calendar_feed_token_id_from_database<'e>(
+    feed: &BookingCalendarFeed,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Option<Uuid>, anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        SELECT token_id
+        FROM booking_calendar_feed_tokens
+        WHERE vendor_id = $1
+            AND rental_id IS NOT DISTINCT FROM $2
+            AND revoked_at IS NULL
+        ORDER BY created_at DESC
+        LIMIT 1
+        "#,
+        feed.vendor_id,
+        feed.rental_id,
+    );
+
+    let token_id = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_optional(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_optional(*pool).await,
+    }
+    .context("Failed to perform a query to get active booking calendar feed token.")?
+    .map(|row| row.token_id);
+
+    Ok(token_id)
+}
+
+#[tracing::instrument(
+    name = "Create booking calendar feed token in database",
+    skip(executor)
+)]
+pub async fn create_booking_calendar_feed_token_in_database<'e>(
+    token_id: &Uuid,
+    feed: &BookingCalendarFeed,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        INSERT INTO booking_calendar_feed_tokens (
+            token_id,
+            vendor_id,
+            rental_id
+        )
+        VALUES (
+            $1,
+            $2,
+            $3
+        )
+        "#,
+        token_id,
+        feed.vendor_id,
+        feed.rental_id,
+    );
+
+    match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to insert booking calendar feed token into the database.")?;
+
+    Ok(())
+}
+
+#[tracing::instrument(
+    name = "Get booking calendar feed token active from database",
+    skip(executor)
+)]
+pub async fn get_booking_calendar_feed_token_active_from_database<'e>(
+    token_id: &Uuid,
+    feed: &BookingCalendarFeed,
+    executor: &mut DbExecutor<'e>,
+) -> Result<bool, anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        SELECT token_id
+        FROM booking_calendar_feed_tokens
+        WHERE token_id = $1
+            AND vendor_id = $2
+            AND rental_id IS NOT DISTINCT FROM $3
+            AND revoked_at IS NULL
+        "#,
+        token_id,
+        feed.vendor_id,
+        feed.rental_id,
+    );
+
+    let token = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_optional(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_optional(*pool).await,
+    }
+    .context("Failed to perform a query to get booking calendar feed token.")?;
+
+    Ok(token.is_some())
+}
+
+#[tracing::instrument(
+    name = "Revoke booking calendar feed tokens in database",
+    skip(executor)
+)]
+pub async fn revoke_booking_calendar_feed_tokens_in_database<'e>(
+    feed: &BookingCalendarFeed,
+    executor: &mut DbExecutor<'e>,
+) -> Result<u64, anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        UPDATE booking_calendar_feed_tokens
+        SET revoked_at = NOW()
+        WHERE vendor_id = $1
+            AND rental_id IS NOT DISTINCT FROM $2
+            AND revoked_at IS NULL
+        "#,
+        feed.vendor_id,
+        feed.rental_id,
+    );
+
+    let revoked = match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to revoke booking calendar feed tokens in the database.")?
+    .rows_affected();
+
+    Ok(revoked)
+}
+
+#[tracing::instrument(
+    name = "Create booking notification in outbox",
+    skip(rendered, executor)
+)]
+pub async fn create_booking_notification_in_outbox<'e>(
+    notification_id: &Uuid,
+    notification: &str,
+    recipient: &str,
+    rendered: &RenderedBookingNotification,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), anyhow::Error> {
+    let attachment = rendered.attachment.as_ref();
+
+    let query = sqlx::query!(
+        r#"
+        INSERT INTO booking_notification_outbox (
+            notification_id,
+            notification,
+            recipient,
+            subject,
+            html_body,
+            plain_body,
+            attachment_filename,
+            attachment_content_type,
+            attachment_content,
+            notification_status
+        )
+        VALUES (
+            $1,
+            $2,
+            $3,
+            $4,
+            $5,
+            $6,
+            $7,
+            $8,
+            $9,
+            $10
+        )
+        "#,
+        notification_id,
+        notification,
+        recipient,
+        rendered.subject,
+        rendered.html_body,
+        rendered.plain_body,
+        attachment.map(|attachment| attachment.filename.as_str()),
+        attachment.map(|attachment| attachment.content_type.as_str()),
+        attachment.map(|attachment| attachment.content.as_str()),
+        BookingNotificationStatus::Pending as BookingNotificationStatus,
+    );
+
+    match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to insert booking notification into the outbox.")?;
+
+    Ok(())
+}
+
+#[tracing::instrument(name = "Get due booking notifications from outbox", skip(executor))]
+pub async fn get_due_booking_notifications_from_outbox<'e>(
+    limit: i64,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<BookingOutboxNotification>, anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        SELECT
+            notification_id,
+            created_at,
+            updated_at,
+            notification,
+            recipient,
+            subject,
+            html_body,
+            plain_body,
+            attachment_filename,
+            attachment_content_type,
+            attachment_content,
+            notification_status as "notification_status: BookingNotificationStatus",
+            attempts,
+            next_attempt_at,
+            last_error,
+            delivered_at
+        FROM booking_notification_outbox
+        WHERE notification_status = 'pending'
+            AND next_attempt_at <= NOW()
+        ORDER BY next_attempt_at
+        LIMIT $1
+        "#,
+        limit,
+    );
+
+    let notifications: Vec<BookingOutboxNotification> = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_all(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_all(*pool).await,
+    }
+    .context("Failed to perform a query to get due booking notifications.")?
+    .into_iter()
+    .map(|row| BookingOutboxNotification {
+        notification_id: row.notification_id,
+        created_at: row.created_at,
+        updated_at: row.updated_at,
+        notification: row.notification,
+        recipient: row.recipient,
+        subject: row.subject,
+        html_body: row.html_body,
+        plain_body: row.plain_body,
+        attachment_filename: row.attachment_filename,
+        attachment_content_type: row.attachment_content_type,
+        attachment_content: row.attachment_content,
+        notification_status: row.notification_status,
+        attempts: row.attempts,
+        next_attempt_at: row.next_attempt_at,
+        last_error: row.last_error,
+        delivered_at: row.delivered_at,
+    })
+    .collect();
+
+    Ok(notifications)
+}
+
+#[tracing::instrument(name = "Get dead booking notifications from outbox", skip(executor))]
+pub async fn get_dead_booking_notifications_from_outbox<'e>(
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<BookingOutboxNotification>, anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        SELECT
+            notification_id,
+            created_at,
+            updated_at,
+            notification,
+            recipient,
+            subject,
+            html_body,
+            plain_body,
+            attachment_filename,
+            attachment_content_type,
+            attachment_content,
+            notification_status as "notification_status: BookingNotificationStatus",
+            attempts,
+            next_attempt_at,
+            last_error,
+            delivered_at
+        FROM booking_notification_outbox
+        WHERE notification_status = 'dead'
+        ORDER BY updated_at DESC
+        "#,
+    );
+
+    let notifications: Vec<BookingOutboxNotification> = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_all(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_all(*pool).await,
+    }
+    .context("Failed to perform a query to get dead booking notifications.")?
+    .into_iter()
+    .map(|row| BookingOutboxNotification {
+        notification_id: row.notification_id,
+        created_at: row.created_at,
+        updated_at: row.updated_at,
+        notification: row.notification,
+        recipient: row.recipient,
+        subject: row.subject,
+        html_body: row.html_body,
+        plain_body: row.plain_body,
+        attachment_filename: row.attachment_filename,
+        attachment_content_type: row.attachment_content_type,
+        attachment_content: row.attachment_content,
+        notification_status: row.notification_status,
+        attempts: row.attempts,
+        next_attempt_at: row.next_attempt_at,
+        last_error: row.last_error,
+        delivered_at: row.delivered_at,
+    })
+    .collect();
+
+    Ok(notifications)
+}
+
+#[tracing::instrument(name = "Claim booking notification in outbox", skip(executor))]
+pub async fn claim_booking_notification_in_outbox<'e>(
+    notification_id: &Uuid,
+    claim_seconds: f64,
+    executor: &mut DbExecutor<'e>,
+) -> Result<bool, anyhow::Error> {
+    // Pushes the next attempt past the send, so the row isn't picked up again while it's being
+    // sent. Returns false when the notification is no longer due.
+    let query = sqlx::query!(
+        r#"
+        UPDATE booking_notification_outbox
+        SET
+            next_attempt_at = NOW() + make_interval(secs => $2),
+            updated_at = NOW()
+        WHERE notification_id = $1
+            AND notification_status = 'pending'
+            AND next_attempt_at <= NOW()
+        "#,
+        notification_id,
+        claim_seconds,
+    );
+
+    let rows_affected = match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to claim booking notification in the outbox.")?
+    .rows_affected();
+
+    Ok(rows_affected > 0)
+}
+
+#[tracing::instrument(
+    name = "Update booking notification delivered in outbox",
+    skip(executor)
+)]
+pub async fn update_booking_notification_delivered_in_outbox<'e>(
+    notification_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        UPDATE booking_notification_outbox
+        SET
+            notification_status = $2,
+            attempts = attempts + 1,
+            delivered_at = NOW(),
+            updated_at = NOW()
+        WHERE notification_id = $1
+        "#,
+        notification_id,
+        BookingNotificationStatus::Delivered as BookingNotificationStatus,
+    );
+
+    match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to mark booking notification as delivered in the outbox.")?;
+
+    Ok(())
+}
+
+#[tracing::instrument(name = "Update booking notification failed in outbox", skip(executor))]
+pub async fn update_booking_notification_failed_in_outbox<'e>(
+    notification_id: &Uuid,
+    notification_status: &BookingNotificationStatus,
+    next_attempt_at: &OffsetDateTime,
+    last_error: &str,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        UPDATE booking_notification_outbox
+        SET
+            notification_status = $2,
+            attempts = attempts + 1,
+            next_attempt_at = $3,
+            last_error = $4,
+            updated_at = NOW()
+        WHERE notification_id = $1
+        "#,
+        notification_id,
+        notification_status as &BookingNotificationStatus,
+        next_attempt_at,
+        last_error,
+    );
+
+    match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to mark booking notification as failed in the outbox.")?;
+
+    Ok(())
+}
+
+#[tracing::instrument(name = "Replay dead booking notification in outbox", skip(executor))]
+pub async fn replay_dead_booking_notification_in_outbox<'e>(
+    notification_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<bool, anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        UPDATE booking_notification_outbox
+        SET
+            notification_status = $2,
+            attempts = 0,
+            next_attempt_at = NOW(),
+            updated_at = NOW()
+        WHERE notification_id = $1
+            AND notification_status = 'dead'
+        "#,
+        notification_id,
+        BookingNotificationStatus::Pending as BookingNotificationStatus,
+    );
+
+    let rows_affected = match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to replay dead booking notification in the outbox.")?
+    .rows_affected();
+
+    Ok(rows_affected > 0)
+}
+
+#[tracing::instrument(name = "Get booking calendar sequence from database", skip(executor))]
+pub async fn get_booking_calendar_sequence_from_database<'e>(
+    booking_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<i32, anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        SELECT sequence
+        FROM booking_calendar_sequences
+        WHERE booking_id = $1
+        "#,
+        booking_id,
+    );
+
+    let sequence = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_optional(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_optional(*pool).await,
+    }
+    .context("Failed to perform a query to get booking calendar sequence.")?
+    .map(|row| row.sequence)
+    .unwrap_or(0);
+
+    Ok(sequence)
+}
+
+#[tracing::instrument(
+    name = "Increment booking calendar sequence in database",
+    skip(executor)
+)]
+pub async fn increment_booking_calendar_sequence_in_database<'e>(
+    booking_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<i32, anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        INSERT INTO booking_calendar_sequences (
+            booking_id,
+            sequence
+        )
+        VALUES (
+            $1,
+            1
+        )
+        ON CONFLICT (booking_id) DO UPDATE
+        SET
+            sequence = booking_calendar_sequences.sequence + 1,
+            updated_at = NOW()
+        RETURNING sequence
+        "#,
+        booking_id,
+    );
+
+    let sequence = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_one(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_one(*pool).await,
+    }
+    .context("Failed to increment booking calendar sequence in the database.")?
+    .sequence;
+
+    Ok(sequence)
+}
+
+#[tracing::instrument(
+    name = "Get booking calendar fingerprint from database",
+    skip(executor)
+)]
+pub async fn get_booking_calendar_fingerprint_from_database<'e>(
+    feed: &BookingCalendarFeed,
+    booking_statuses: &[BookingStatus],
+    since: &OffsetDateTime,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(i64, Option<OffsetDateTime>), anyhow::Error> {
+    // Cheap enough to run on every poll, the feed is only rebuilt when this changes
+    let query = sqlx::query!(
+        r#"
+        SELECT
+            COUNT(*) AS "booking_count!",
+            MAX(updated_at) AS last_updated_at
+        FROM bookings
+        WHERE vendor_id = $1
+            AND ($2::uuid IS NULL OR rental_id = $2)
+            AND booking_status = ANY($3)
+            AND end_date >= $4
+        "#,
+        feed.vendor_id,
+        feed.rental_id,
+        booking_statuses as &[BookingStatus],
+        since,
+    );
+
+    let row = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_one(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_one(*pool).await,
+    }
+    .context("Failed to perform a query to get booking calendar fingerprint.")?;
+
+    Ok((row.booking_count, row.last_updated_at))
+}
+
+#[tracing::instrument(name = "Create booking webhook in database", skip(executor))]
+pub async fn create_booking_webhook_in_database<'e>(
+    webhook_id: &Uuid,
+    vendor_id: &Uuid,
+    webhook: &CreateBookingWebhook,
+    secret: &str,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        INSERT INTO booking_webhooks (
+            webhook_id,
+            vendor_id,
+            url,
+            events,
+            secret
+        )
+        VALUES (
+            $1,
+            $2,
+            $3,
+            $4,
+            $5
+        )
+        "#,
+        webhook_id,
+        vendor_id,
+        webhook.url,
+        &webhook.events as &[BookingWebhookEvent],
+        secret,
+    );
+
+    match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to insert booking webhook into the database.")?;
+
+    Ok(())
+}
+
+#[tracing::instrument(
+    name = "Get booking webhooks from database by vendor id",
+    skip(executor)
+)]
+pub async fn get_booking_webhooks_from_database_by_vendor_id<'e>(
+    vendor_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<BookingWebhook>, anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        SELECT
+            webhook_id,
+            created_at,
+            vendor_id,
+            url,
+            events as "events: Vec<BookingWebhookEvent>"
+        FROM booking_webhooks
+        WHERE vendor_id = $1
+        ORDER BY created_at
+        "#,
+        vendor_id,
+    );
+
+    let webhooks: Vec<BookingWebhook> = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_all(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_all(*pool).await,
+    }
+    .context("Failed to perform a query to get booking webhooks by vendor id.")?
+    .into_iter()
+    .map(|row| BookingWebhook {
+        webhook_id: row.webhook_id,
+        created_at: row.created_at,
+        vendor_id: row.vendor_id,
+        url: row.url,
+        events: row.events,
+        secret: None,
+    })
+    .collect();
+
+    Ok(webhooks)
+}
+
+#[tracing::instrument(
+    name = "Get booking webhook from database by webhook id",
+    skip(executor)
+)]
+pub async fn get_booking_webhook_from_database_by_webhook_id<'e>(
+    webhook_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Option<BookingWebhook>, anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        SELECT
+            webhook_id,
+            created_at,
+            vendor_id,
+            url,
+            events as "events: Vec<BookingWebhookEvent>"
+        FROM booking_webhooks
+        WHERE webhook_id = $1
+        "#,
+        webhook_id,
+    );
+
+    let webhook: Option<BookingWebhook> = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_optional(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_optional(*pool).await,
+    }
+    .context("Failed to perform a query to get booking webhook by webhook id.")?
+    .map(|row| BookingWebhook {
+        webhook_id: row.webhook_id,
+        created_at: row.created_at,
+        vendor_id: row.vendor_id,
+        url: row.url,
+        events: row.events,
+        secret: None,
+    });
+
+    Ok(webhook)
+}
+
+#[tracing::instrument(
+    name = "Get booking webhook ids from database by event",
+    skip(executor)
+)]
+pub async fn get_booking_webhook_ids_from_database_by_event<'e>(
+    vendor_id: &Uuid,
+    event: &BookingWebhookEvent,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<Uuid>, anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        SELECT webhook_id
+        FROM booking_webhooks
+        WHERE vendor_id = $1
+            AND $2 = ANY(events)
+        "#,
+        vendor_id,
+        event as &BookingWebhookEvent,
+    );
+
+    let webhook_ids: Vec<Uuid> = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_all(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_all(*pool).await,
+    }
+    .context("Failed to perform a query to get booking webhooks by event.")?
+    .into_iter()
+    .map(|row| row.webhook_id)
+    .collect();
+
+    Ok(webhook_ids)
+}
+
+#[tracing::instrument(
+    name = "Get booking webhook secret from database by webhook id",
+    skip(executor)
+)]
+pub async fn get_booking_webhook_secret_from_database_by_webhook_id<'e>(
+    webhook_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Option<String>, anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        SELECT secret
+        FROM booking_webhooks
+        WHERE webhook_id = $1
+        "#,
+        webhook_id,
+    );
+
+    let secret = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_optional(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_optional(*pool).await,
+    }
+    .context("Failed to perform a query to get booking webhook secret by webhook id.")?
+    .map(|row| row.secret);
+
+    Ok(secret)
+}
+
+#[tracing::instrument(
+    name = "Update booking webhook secret in database",
+    skip(secret, executor)
+)]
+pub async fn update_booking_webhook_secret_in_database<'e>(
+    webhook_id: &Uuid,
+    secret: &str,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        UPDATE booking_webhooks
+        SET secret = $2
+        WHERE webhook_id = $1
+        "#,
+        webhook_id,
+        secret,
+    );
+
+    match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to update booking webhook secret in the database.")?;
+
+    Ok(())
+}
+
+#[tracing::instrument(name = "Delete booking webhook in database", skip(executor))]
+pub async fn delete_booking_webhook_in_database<'e>(
+    webhook_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), anyhow::Error> {
+    // Deliveries are deleted with the webhook
+    let query = sqlx::query!(
+        r#"
+        DELETE FROM booking_webhooks
+        WHERE webhook_id = $1
+        "#,
+        webhook_id,
+    );
+
+    match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to delete booking webhook from the database.")?;
+
+    Ok(())
+}
+
+#[tracing::instrument(
+    name = "Create booking webhook delivery in database",
+    skip(payload, executor)
+)]
+pub async fn create_booking_webhook_delivery_in_database<'e>(
+    delivery_id: &Uuid,
+    webhook_id: &Uuid,
+    event: &BookingWebhookEvent,
+    payload: &str,
+    next_attempt_at: &OffsetDateTime,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        INSERT INTO booking_webhook_deliveries (
+            delivery_id,
+            webhook_id,
+            event,
+            payload,
+            delivery_status,
+            next_attempt_at
+        )
+        VALUES (
+            $1,
+            $2,
+            $3,
+            $4,
+            $5,
+            $6
+        )
+        "#,
+        delivery_id,
+        webhook_id,
+        event as &BookingWebhookEvent,
+        payload,
+        BookingNotificationStatus::Pending as BookingNotificationStatus,
+        next_attempt_at,
+    );
+
+    match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to insert booking webhook delivery into the database.")?;
+
+    Ok(())
+}
+
+#[tracing::instrument(
+    name = "Get due booking webhook deliveries from database",
+    skip(executor)
+)]
+pub async fn get_due_booking_webhook_deliveries_from_database<'e>(
+    limit: i64,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<BookingWebhookDelivery>, anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        SELECT
+            d.delivery_id,
+            d.created_at,
+            d.updated_at,
+            d.webhook_id,
+            w.url,
+            d.event as "event: BookingWebhookEvent",
+            d.payload,
+            d.delivery_status as "delivery_status: BookingNotificationStatus",
+            d.attempts,
+            d.next_attempt_at,
+            d.response_status,
+            d.last_error,
+            d.delivered_at
+        FROM booking_webhook_deliveries d
+        JOIN booking_webhooks w ON w.webhook_id = d.webhook_id
+        WHERE d.delivery_status = 'pending'
+            AND d.next_attempt_at <= NOW()
+        ORDER BY d.next_attempt_at
+        LIMIT $1
+        "#,
+        limit,
+    );
+
+    let deliveries: Vec<BookingWebhookDelivery> = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_all(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_all(*pool).await,
+    }
+    .context("Failed to perform a query to get due booking webhook deliveries.")?
+    .into_iter()
+    .map(|row| BookingWebhookDelivery {
+        delivery_id: row.delivery_id,
+        created_at: row.created_at,
+        updated_at: row.updated_at,
+        webhook_id: row.webhook_id,
+        url: row.url,
+        event: row.event,
+        payload: row.payload,
+        delivery_status: row.delivery_status,
+        attempts: row.attempts,
+        next_attempt_at: row.next_attempt_at,
+        response_status: row.response_status,
+        last_error: row.last_error,
+        delivered_at: row.delivered_at,
+    })
+    .collect();
+
+    Ok(deliveries)
+}
+
+#[tracing::instrument(
+    name = "Get booking webhook deliveries from database by webhook id",
+    skip(executor)
+)]
+pub async fn get_booking_webhook_deliveries_from_database_by_webhook_id<'e>(
+    webhook_id: &Uuid,
+    limit: i64,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<BookingWebhookDelivery>, anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        SELECT
+            d.delivery_id,
+            d.created_at,
+            d.updated_at,
+            d.webhook_id,
+            w.url,
+            d.event as "event: BookingWebhookEvent",
+            d.payload,
+            d.delivery_status as "delivery_status: BookingNotificationStatus",
+            d.attempts,
+            d.next_attempt_at,
+            d.response_status,
+            d.last_error,
+            d.delivered_at
+        FROM booking_webhook_deliveries d
+        JOIN booking_webhooks w ON w.webhook_id = d.webhook_id
+        WHERE d.webhook_id = $1
+        ORDER BY d.created_at DESC
+        LIMIT $2
+        "#,
+        webhook_id,
+        limit,
+    );
+
+    let deliveries: Vec<BookingWebhookDelivery> = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_all(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_all(*pool).await,
+    }
+    .context("Failed to perform a query to get booking webhook deliveries by webhook id.")?
+    .into_iter()
+    .map(|row| BookingWebhookDelivery {
+        delivery_id: row.delivery_id,
+        created_at: row.created_at,
+        updated_at: row.updated_at,
+        webhook_id: row.webhook_id,
+        url: row.url,
+        event: row.event,
+        payload: row.payload,
+        delivery_status: row.delivery_status,
+        attempts: row.attempts,
+        next_attempt_at: row.next_attempt_at,
+        response_status: row.response_status,
+        last_error: row.last_error,
+        delivered_at: row.delivered_at,
+    })
+    .collect();
+
+    Ok(deliveries)
+}
+
+#[tracing::instrument(name = "Update booking webhook delivery in database", skip(executor))]
+pub async fn update_booking_webhook_delivery_in_database<'e>(
+    delivery_id: &Uuid,
+    delivery_status: &BookingNotificationStatus,
+    response_status: Option<i32>,
+    next_attempt_at: &OffsetDateTime,
+    last_error: Option<&str>,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        UPDATE booking_webhook_deliveries
+        SET
+            delivery_status = $2,
+            attempts = attempts + 1,
+            response_status = $3,
+            next_attempt_at = $4,
+            last_error = $5,
+            delivered_at = CASE WHEN $2 = 'delivered' THEN NOW() ELSE delivered_at END,
+            updated_at = NOW()
+        WHERE delivery_id = $1
+        "#,
+        delivery_id,
+        delivery_status as &BookingNotificationStatus,
+        response_status,
+        next_attempt_at,
+        last_error,
+    );
+
+    match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to update booking webhook delivery in the database.")?;
+
+    Ok(())
+}
+
+#[tracing::instrument(name = "Create booking event in database", skip(payload, executor))]
+pub async fn create_booking_event_in_database<'e>(
+    event_id: &Uuid,
+    event_type: &str,
+    booking_id: Option<Uuid>,
+    payload: &str,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        INSERT INTO booking_events (
+            event_id,
+            event_type,
+            booking_id,
+            payload
+        )
+        VALUES (
+            $1,
+            $2,
+            $3,
+            $4
+        )
+        "#,
+        event_id,
+        event_type,
+        booking_id,
+        payload,
+    );
+
+    match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to insert booking event into the database.")?;
+
+    Ok(())
+}
+
+#[tracing::instrument(name = "Get undispatched booking events from database", skip(executor))]
+pub async fn get_undispatched_booking_events_from_database<'e>(
+    limit: i64,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<BookingOutboxEvent>, anyhow::Error> {
+    // Oldest first so subscribers see a booking's events in the order they happened. Events
+    // wait while an earlier event of the same booking is still pending.
+    let query = sqlx::query!(
+        r#"
+        SELECT
+            e.event_id,
+            e.booking_id,
+            e.payload,
+            e.attempts
+        FROM booking_events e
+        WHERE e.event_status = 'pending'
+            AND e.next_attempt_at <= NOW()
+            AND NOT EXISTS (
+                SELECT 1
+                FROM booking_events earlier
+                WHERE earlier.booking_id = e.booking_id
+                    AND earlier.event_status = 'pending'
+                    AND earlier.created_at < e.created_at
+            )
+        ORDER BY e.created_at
+        LIMIT $1
+        "#,
+        limit,
+    );
+
+    let events: Vec<BookingOutboxEvent> = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_all(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_all(*pool).await,
+    }
+    .context("Failed to perform a query to get undispatched booking events.")?
+    .into_iter()
+    .map(|row| BookingOutboxEvent {
+        event_id: row.event_id,
+        booking_id: row.booking_id,
+        payload: row.payload,
+        attempts: row.attempts,
+    })
+    .collect();
+
+    Ok(events)
+}
+
+#[tracing::instrument(name = "Update booking event dispatched in database", skip(executor))]
+pub async fn update_booking_event_dispatched_in_database<'e>(
+    event_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        UPDATE booking_events
+        SET
+            event_status = $2,
+            dispatched_at = NOW()
+        WHERE event_id = $1
+        "#,
+        event_id,
+        BookingEventStatus::Dispatched as BookingEventStatus,
+    );
+
+    match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to mark booking event as dispatched in the database.")?;
+
+    Ok(())
+}
+
+#[tracing::instrument(name = "Update booking event failed in database", skip(executor))]
+pub async fn update_booking_event_failed_in_database<'e>(
+    event_id: &Uuid,
+    event_status: &BookingEventStatus,
+    next_attempt_at: &OffsetDateTime,
+    last_error: &str,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        UPDATE booking_events
+        SET
+            event_status = $2,
+            attempts = attempts + 1,
+            next_attempt_at = $3,
+            last_error = $4
+        WHERE event_id = $1
+        "#,
+        event_id,
+        event_status as &BookingEventStatus,
+        next_attempt_at,
+        last_error,
+    );
+
+    match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to mark booking event as failed in the database.")?;
+
+    Ok(())
+}
+
+#[tracing::instrument(name = "Create booking blackout in database", skip(executor))]
+pub async fn create_booking_blackout_in_database<'e>(
+    blackout_id: &Uuid,
+    vendor_id: &Uuid,
+    blackout: &CreateBookingBlackout,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        INSERT INTO booking_blackouts (
+            blackout_id,
+            vendor_id,
+            rental_id,
+            start_date,
+            end_date,
+            recurrence,
+            recur_until,
+            reason
+        )
+        VALUES (
+            $1,
+            $2,
+            $3,
+            $4,
+            $5,
+            $6,
+            $7,
+            $8
+        )
+        "#,
+        blackout_id,
+        vendor_id,
+        blackout.rental_id,
+        blackout.start_date,
+        blackout.end_date,
+        blackout.recurrence as Option<BookingBlackoutRecurrence>,
+        blackout.recur_until,
+        blackout.reason,
+    );
+
+    match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to insert booking blackout into the database.")?;
+
+    Ok(())
+}
+
+#[tracing::instrument(
+    name = "Get booking blackouts from database by vendor id",
+    skip(executor)
+)]
+pub async fn get_booking_blackouts_from_database_by_vendor_id<'e>(
+    vendor_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<BookingBlackout>, anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        SELECT
+            blackout_id,
+            created_at,
+            vendor_id,
+            rental_id,
+            start_date,
+            end_date,
+            recurrence as "recurrence: BookingBlackoutRecurrence",
+            recur_until,
+            reason
+        FROM booking_blackouts
+        WHERE vendor_id = $1
+        ORDER BY start_date
+        "#,
+        vendor_id,
+    );
+
+    let blackouts: Vec<BookingBlackout> = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_all(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_all(*pool).await,
+    }
+    .context("Failed to perform a query to get booking blackouts by vendor id.")?
+    .into_iter()
+    .map(|row| BookingBlackout {
+        blackout_id: row.blackout_id,
+        created_at: row.created_at,
+        vendor_id: row.vendor_id,
+        rental_id: row.rental_id,
+        start_date: row.start_date,
+        end_date: row.end_date,
+        recurrence: row.recurrence,
+        recur_until: row.recur_until,
+        reason: row.reason,
+    })
+    .collect();
+
+    Ok(blackouts)
+}
+
+#[tracing::instrument(
+    name = "Get booking blackout from database by blackout id",
+    skip(executor)
+)]
+pub async fn get_booking_blackout_from_database_by_blackout_id<'e>(
+    blackout_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Option<BookingBlackout>, anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        SELECT
+            blackout_id,
+            created_at,
+            vendor_id,
+            rental_id,
+            start_date,
+            end_date,
+            recurrence as "recurrence: BookingBlackoutRecurrence",
+            recur_until,
+            reason
+        FROM booking_blackouts
+        WHERE blackout_id = $1
+        "#,
+        blackout_id,
+    );
+
+    let blackout: Option<BookingBlackout> = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_optional(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_optional(*pool).await,
+    }
+    .context("Failed to perform a query to get booking blackout by blackout id.")?
+    .map(|row| BookingBlackout {
+        blackout_id: row.blackout_id,
+        created_at: row.created_at,
+        vendor_id: row.vendor_id,
+        rental_id: row.rental_id,
+        start_date: row.start_date,
+        end_date: row.end_date,
+        recurrence: row.recurrence,
+        recur_until: row.recur_until,
+        reason: row.reason,
+    });
+
+    Ok(blackout)
+}
+
+#[tracing::instrument(
+    name = "Get booking blackouts from database by rental id",
+    skip(executor)
+)]
+pub async fn get_booking_blackouts_from_database_by_rental_id<'e>(
+    vendor_id: &Uuid,
+    rental_id: &Uuid,
+    start_date: &OffsetDateTime,
+    end_date: &OffsetDateTime,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<BookingBlackout>, anyhow::Error> {
+    // Vendor wide blackouts close the rental too. Recurring blackouts can still have
+    // occurrences in the window as long as they haven't stopped recurring before it.
+    let query = sqlx::query!(
+        r#"
+        SELECT
+            blackout_id,
+            created_at,
+            vendor_id,
+            rental_id,
+            start_date,
+            end_date,
+            recurrence as "recurrence: BookingBlackoutRecurrence",
+            recur_until,
+            reason
+        FROM booking_blackouts
+        WHERE vendor_id = $1
+            AND (rental_id IS NULL OR rental_id = $2)
+            AND start_date < $4
+            AND (
+                end_date > $3
+                OR (
+                    recurrence IS NOT NULL
+                    AND (recur_until IS NULL OR recur_until + (end_date - start_date) > $3)
+                )
+            )
+        "#,
+        vendor_id,
+        rental_id,
+        start_date,
+        end_date,
+    );
+
+    let blackouts: Vec<BookingBlackout> = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_all(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_all(*pool).await,
+    }
+    .context("Failed to perform a query to get booking blackouts by rental id.")?
+    .into_iter()
+    .map(|row| BookingBlackout {
+        blackout_id: row.blackout_id,
+        created_at: row.created_at,
+        vendor_id: row.vendor_id,
+        rental_id: row.rental_id,
+        start_date: row.start_date,
+        end_date: row.end_date,
+        recurrence: row.recurrence,
+        recur_until: row.recur_until,
+        reason: row.reason,
+    })
+    .collect();
+
+    Ok(blackouts)
+}
+
+#[tracing::instrument(name = "Delete booking blackout in database", skip(executor))]
+pub async fn delete_booking_blackout_in_database<'e>(
+    blackout_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), anyhow::Error> {
+    let query = sqlx::query!(
+        r#"
+        DELETE FROM booking_blackouts
+        WHERE blackout_id = $1
+        "#,
+        blackout_id,
+    );
+
+    match executor {
+        DbExecutor::Transaction(transaction) => query.execute(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.execute(*pool).await,
+    }
+    .context("Failed to delete booking blackout from the database.")?;
+
+    Ok(())
+}
+
+#[tracing::instrument(
+    name = "Get active booking dates from database by vendor id",
+    skip(executor)
+)]
+pub async fn get_active_booking_dates_from_database_by_vendor_id<'e>(
+    vendor_id: &Uuid,
+    rental_id: &Option<Uuid>,
+    start_date: &OffsetDateTime,
+    end_date: &Option<OffsetDateTime>,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<(Uuid, OffsetDateTime, OffsetDateTime)>, anyhow::Error> {
+    // Active bookings of the rental, or every rental without one, that end after the start date
+    // and start before the end date if there is one
+    let query = sqlx::query!(
+        r#"
+        SELECT
+            rental_id,
+            start_date,
+            end_date
+        FROM bookings
+        WHERE vendor_id = $1
+            AND ($2::uuid IS NULL OR rental_id = $2)
+            AND booking_status IN ('requested', 'accepted', 'confirmed', 'disputed')
+            AND end_date >= NOW()
+            AND end_date > $3
+            AND ($4::timestamptz IS NULL OR start_date < $4)
+        "#,
+        vendor_id,
+        *rental_id,
+        start_date,
+        *end_date,
+    );
+
+    let booked_dates: Vec<(Uuid, OffsetDateTime, OffsetDateTime)> = match executor {
+        DbExecutor::Transaction(transaction) => query.fetch_all(&mut **transaction).await,
+        DbExecutor::Pool(pool) => query.fetch_all(*pool).await,
+    }
+    .context("Failed to perform a query to get active booking dates by vendor id.")?
+    .into_iter()
+    .map(|row| (row.rental_id, row.start_date, row.end_date))
+    .collect();
 
-    Ok(availability_data)
+    Ok(booked_dates)
 }
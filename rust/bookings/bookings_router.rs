@@ -1,29 +1,181 @@
 use crate::routes::bookings::bookings_handler::{
-    handle_accept_booking, handle_cancel_booking, handle_check_availability,
-    handle_complete_booking, handle_decline_booking, handle_get_availabilities,
-    handle_get_availability, handle_get_booking,
+    handle_abandon_partial_booking, handle_accept_booking, handle_approve_booking_modification,
+    handle_cancel_booking, handle_check_availability, handle_complete_booking,
+    handle_confirm_partial_booking, handle_create_booking_blackout, handle_create_booking_webhook,
+    handle_decline_booking, handle_delete_booking_blackout, handle_delete_booking_webhook,
+    handle_dispute_booking, handle_extend_booking_hold, handle_get_availabilities,
+    handle_get_availability, handle_get_availability_ranges, handle_get_booking,
+    handle_get_booking_blackouts, handle_get_booking_calendar_feed_link,
+    handle_get_booking_dispute, handle_get_booking_email_preferences, handle_get_booking_history,
+    handle_get_booking_hold_expiry, handle_get_booking_job_runs, handle_get_booking_modifications,
+    handle_get_booking_rental_settings, handle_get_booking_vendor_settings,
+    handle_get_booking_webhook_deliveries, handle_get_booking_webhooks,
+    handle_get_cancellation_policy, handle_get_dead_booking_notifications,
+    handle_get_partial_booking, handle_get_rental_booking_calendar_feed,
+    handle_get_vendor_booking_calendar_feed, handle_import_bookings, handle_modify_booking,
+    handle_ping_booking_webhook, handle_reassign_booking, handle_reject_booking_modification,
+    handle_replay_dead_booking_notification, handle_resolve_booking_dispute,
+    handle_revoke_booking_access_tokens, handle_revoke_booking_calendar_feed_tokens,
+    handle_rotate_booking_webhook_secret, handle_update_booking_email_preferences,
+    handle_update_booking_rental_settings, handle_update_booking_vendor_settings,
+    handle_upsert_cancellation_policy,
 };
 use crate::startup::AppState;
 use crate::utilities::middleware::require_auth::require_auth_middleware;
-use axum::routing::{get, patch};
+use axum::routing::{delete, get, patch, post, put};
 use axum::{middleware, Router};
 use std::sync::Arc;
 
 pub fn bookings_router() -> Router<Arc<AppState>> {
     Router::new()
         // .route("/bookings", get(handle_get_bookings_by_query))
-        .route("/bookings/:id", get(handle_get_booking))
+        .route("/bookings/:id", patch(handle_modify_booking))
+        .route("/bookings/:id/history", get(handle_get_booking_history))
         // .route("/bookings", post(handle_request_booking))
         // .route("/bookings/request", post(handle_request_bookings))
         .route("/bookings/:id/accept", patch(handle_accept_booking))
         .route("/bookings/:id/decline", patch(handle_decline_booking))
-        .route("/bookings/:id/cancel", patch(handle_cancel_booking))
         .route("/bookings/:id/complete", patch(handle_complete_booking))
+        .route("/bookings/:id/reassign", patch(handle_reassign_booking))
+        .route(
+            "/bookings/:id/access/revoke",
+            patch(handle_revoke_booking_access_tokens),
+        )
+        .route(
+            "/bookings/:id/modifications",
+            get(handle_get_booking_modifications),
+        )
+        .route(
+            "/bookings/:id/modifications/:modification_id/approve",
+            patch(handle_approve_booking_modification),
+        )
+        .route(
+            "/bookings/:id/modifications/:modification_id/reject",
+            patch(handle_reject_booking_modification),
+        )
+        .route(
+            "/bookings/:id/dispute",
+            get(handle_get_booking_dispute).patch(handle_dispute_booking),
+        )
+        .route(
+            "/bookings/:id/dispute/resolve",
+            patch(handle_resolve_booking_dispute),
+        )
+        .route(
+            "/bookings/transactions/:transaction_id/partial",
+            get(handle_get_partial_booking),
+        )
+        .route(
+            "/bookings/transactions/:transaction_id/partial/confirm",
+            patch(handle_confirm_partial_booking),
+        )
+        .route(
+            "/bookings/transactions/:transaction_id/partial/abandon",
+            patch(handle_abandon_partial_booking),
+        )
+        .route(
+            "/bookings/vendors/:vendor_id/cancellation-policy",
+            put(handle_upsert_cancellation_policy),
+        )
+        .route(
+            "/bookings/vendors/:vendor_id/calendar-feed",
+            get(handle_get_booking_calendar_feed_link),
+        )
+        .route(
+            "/bookings/vendors/:vendor_id/calendar-feed/revoke",
+            patch(handle_revoke_booking_calendar_feed_tokens),
+        )
+        .route(
+            "/bookings/vendors/:vendor_id/import",
+            post(handle_import_bookings),
+        )
+        .route(
+            "/bookings/vendors/:vendor_id/webhooks",
+            get(handle_get_booking_webhooks).post(handle_create_booking_webhook),
+        )
+        .route(
+            "/bookings/vendors/:vendor_id/blackouts",
+            get(handle_get_booking_blackouts).post(handle_create_booking_blackout),
+        )
+        .route(
+            "/bookings/vendors/:vendor_id/settings",
+            get(handle_get_booking_vendor_settings).patch(handle_update_booking_vendor_settings),
+        )
+        .route(
+            "/bookings/rentals/:rental_id/settings",
+            patch(handle_update_booking_rental_settings),
+        )
+        .route(
+            "/bookings/email-preferences",
+            get(handle_get_booking_email_preferences)
+                .patch(handle_update_booking_email_preferences),
+        )
+        .route(
+            "/bookings/holds/:booking_hold_id",
+            get(handle_get_booking_hold_expiry),
+        )
+        .route(
+            "/bookings/holds/:booking_hold_id/extend",
+            patch(handle_extend_booking_hold),
+        )
+        .route(
+            "/bookings/blackouts/:blackout_id",
+            delete(handle_delete_booking_blackout),
+        )
+        .route("/bookings/jobs", get(handle_get_booking_job_runs))
+        .route(
+            "/bookings/notifications/dead",
+            get(handle_get_dead_booking_notifications),
+        )
+        .route(
+            "/bookings/notifications/:notification_id/replay",
+            patch(handle_replay_dead_booking_notification),
+        )
+        .route(
+            "/bookings/webhooks/:webhook_id",
+            delete(handle_delete_booking_webhook),
+        )
+        .route(
+            "/bookings/webhooks/:webhook_id/deliveries",
+            get(handle_get_booking_webhook_deliveries),
+        )
+        .route(
+            "/bookings/webhooks/:webhook_id/ping",
+            post(handle_ping_booking_webhook),
+        )
+        .route(
+            "/bookings/webhooks/:webhook_id/rotate-secret",
+            post(handle_rotate_booking_webhook_secret),
+        )
         .layer(middleware::from_fn(require_auth_middleware))
+        // Renters can also use the access token from their booking emails
+        .route("/bookings/:id", get(handle_get_booking))
+        .route("/bookings/:id/cancel", patch(handle_cancel_booking))
         .route("/bookings/availability", get(handle_get_availability))
+        .route(
+            "/bookings/availability/ranges",
+            get(handle_get_availability_ranges),
+        )
         .route("/bookings/availabilities", get(handle_get_availabilities))
         .route(
             "/bookings/availability/:quantity",
             get(handle_check_availability),
         )
+        .route(
+            "/bookings/vendors/:vendor_id/cancellation-policy",
+            get(handle_get_cancellation_policy),
+        )
+        .route(
+            "/bookings/rentals/:rental_id/settings",
+            get(handle_get_booking_rental_settings),
+        )
+        // Calendar feeds are authorized by the token in their url
+        .route(
+            "/bookings/calendar/:file",
+            get(handle_get_vendor_booking_calendar_feed),
+        )
+        .route(
+            "/bookings/calendar/:vendor_id/rentals/:file",
+            get(handle_get_rental_booking_calendar_feed),
+        )
 }
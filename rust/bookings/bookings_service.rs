@@ -1,30 +1,138 @@
+use crate::routes::auth::credentials::UserEmail;
 use crate::routes::booking_holds::booking_holds_model::{BookingHoldStatus, GetBookingHoldsQuery};
 use crate::routes::booking_holds::booking_holds_service::get_booking_holds_by_query;
+use crate::routes::bookings::bookings_calendar::{
+    build_calendar, CalendarEvent, CalendarInvite, CalendarMethod,
+};
+use crate::routes::bookings::bookings_emails::{
+    render_booking_notification, send_booking_notification, BookingEmailDetails,
+    BookingNotification,
+};
+use crate::routes::bookings::bookings_events::{BookingEvent, BookingEventBus};
+use crate::routes::bookings::bookings_import::{
+    parse_bookings_csv, parse_bookings_ics, ParsedImportRow,
+};
+use crate::routes::bookings::bookings_intervals::{
+    blackout_intervals, expand_availability_ranges, min_available_quantity,
+    sweep_availability_ranges, BookingInterval, BookingIntervalKind,
+};
+use crate::routes::bookings::bookings_locales::BookingLocale;
 use crate::routes::bookings::bookings_model::{
-    Availabilities, Availability, Booking, BookingStatus, GetAvailabilitiesQuery,
-    GetAvailabilityQuery, GetBookingsQuery, RequestBooking,
+    Availabilities, Availability, AvailabilityRange, Booking, BookingActor, BookingBlackout,
+    BookingCalendarFeed, BookingCalendarFeedLink, BookingChanges, BookingDispute,
+    BookingDisputeResolution, BookingDisputeStatus, BookingEmailPreferences, BookingEventStatus,
+    BookingGranularity, BookingHoldExpiry, BookingImportFormat, BookingImportReport,
+    BookingImportRow, BookingImportRowStatus, BookingJobOutcome, BookingJobRun,
+    BookingModification, BookingModificationStatus, BookingNotificationStatus, BookingOperatorRole,
+    BookingOutboxEvent, BookingOutboxNotification, BookingParty, BookingReminderKind,
+    BookingRentalSettings, BookingSettlementReason, BookingStatus, BookingStatusEvent,
+    BookingVendorEmployee, BookingVendorNotificationKind, BookingVendorSettings, BookingWebhook,
+    BookingWebhookDelivery, BookingWebhookEvent, CanceledBooking, CancellationPolicy,
+    CreateBookingBlackout, CreateBookingWebhook, DisputeBooking, GetAvailabilitiesQuery,
+    GetAvailabilityQuery, GetBookingsQuery, ImportBookingsQuery, ModifyBooking, PartialBooking,
+    RequestBooking, ResolveBookingDispute, UpdateBookingEmailPreferences,
+    UpdateBookingRentalSettings, UpdateBookingVendorSettings, UpsertCancellationPolicy,
 };
 use crate::routes::bookings::bookings_repo::{
-    create_booking_in_database, get_booked_quantity_by_rental_id,
-    get_booking_from_database_by_booking_id, get_bookings_from_database_by_query,
-    update_booking_status_in_database_by_booking_id,
+    acquire_booking_job_lease_in_database, claim_booking_notification_in_outbox,
+    create_booking_access_token_in_database, create_booking_blackout_in_database,
+    create_booking_calendar_feed_token_in_database, create_booking_dispute_in_database,
+    create_booking_event_in_database, create_booking_hold_expiration_in_database,
+    create_booking_in_database, create_booking_modification_in_database,
+    create_booking_notification_in_outbox, create_booking_pick_list_reminder_in_database,
+    create_booking_reminder_in_database, create_booking_status_event_in_database,
+    create_booking_vendor_notification_in_database, create_booking_webhook_delivery_in_database,
+    create_booking_webhook_in_database, delete_booking_blackout_in_database,
+    delete_booking_webhook_in_database, delete_pending_booking_holds_in_database,
+    extend_booking_hold_in_database, finish_booking_job_run_in_database,
+    get_active_booking_calendar_feed_token_id_from_database,
+    get_active_booking_count_from_database_by_rental_id,
+    get_active_booking_count_from_database_by_vendor_id,
+    get_active_booking_dates_from_database_by_vendor_id, get_booked_dates_by_rental_id,
+    get_booking_access_token_active_from_database,
+    get_booking_blackout_from_database_by_blackout_id,
+    get_booking_blackouts_from_database_by_rental_id,
+    get_booking_blackouts_from_database_by_vendor_id,
+    get_booking_calendar_feed_token_active_from_database,
+    get_booking_calendar_fingerprint_from_database, get_booking_calendar_sequence_from_database,
+    get_booking_email_preferences_from_database_by_user_id,
+    get_booking_from_database_by_booking_id,
+    get_booking_hold_expiry_from_database_by_booking_hold_id, get_booking_job_runs_from_database,
+    get_booking_modifications_from_database_by_booking_id,
+    get_booking_operator_role_from_database_by_user_id,
+    get_booking_rental_settings_from_database_by_rental_id,
+    get_booking_status_events_from_database_by_booking_id,
+    get_booking_vendor_settings_from_database_by_vendor_id,
+    get_booking_webhook_deliveries_from_database_by_webhook_id,
+    get_booking_webhook_from_database_by_webhook_id,
+    get_booking_webhook_ids_from_database_by_event,
+    get_booking_webhook_secret_from_database_by_webhook_id,
+    get_booking_webhooks_from_database_by_vendor_id, get_bookings_from_database_by_query,
+    get_cancellation_policy_from_database_by_vendor_id, get_completable_booking_ids_from_database,
+    get_dead_booking_notifications_from_outbox, get_due_booking_notifications_from_outbox,
+    get_due_booking_reminder_ids_from_database, get_due_booking_webhook_deliveries_from_database,
+    get_due_pick_list_booking_ids_from_database,
+    get_due_response_deadline_booking_ids_from_database, get_expired_booking_holds_from_database,
+    get_expired_partial_booking_transaction_ids_from_database,
+    get_latest_booking_dispute_from_database_by_booking_id, get_pick_list_vendor_ids_from_database,
+    get_unanswered_booking_request_ids_from_database,
+    get_undispatched_booking_events_from_database,
+    get_unnotified_booking_request_ids_from_database,
+    get_vendor_employees_from_database_by_vendor_id,
+    increment_booking_calendar_sequence_in_database, renew_booking_job_lease_in_database,
+    replay_dead_booking_notification_in_outbox, resolve_booking_dispute_in_database,
+    revoke_booking_access_tokens_in_database, revoke_booking_calendar_feed_tokens_in_database,
+    set_pending_booking_hold_expiries_in_database,
+    update_booking_details_in_database_by_booking_id, update_booking_event_dispatched_in_database,
+    update_booking_event_failed_in_database,
+    update_booking_modification_status_in_database_by_modification_id,
+    update_booking_notification_delivered_in_outbox, update_booking_notification_failed_in_outbox,
+    update_booking_status_in_database_by_booking_id, update_booking_transaction_id_in_database,
+    update_booking_webhook_delivery_in_database, update_booking_webhook_secret_in_database,
+    upsert_booking_email_preferences_in_database, upsert_booking_rental_settings_in_database,
+    upsert_booking_vendor_settings_in_database, upsert_cancellation_policy_in_database,
 };
 use crate::routes::bookings::bookings_utils::{
-    build_booking_details, calculate_availability_from_merged_bookings,
-    merge_booked_quantities_and_holds,
+    align_to_booking_granularity, build_booking_details, calculate_cancellation_refund,
+    calculate_notification_retry_delay, calendar_event_dates, calendar_event_status,
+    generate_booking_webhook_secret, group_bookings_by_status, next_booking_slot,
+    parse_booking_timezone, sign_booking_access_token, sign_booking_calendar_feed_token,
+    validate_booking_blackout, validate_booking_granularity, validate_booking_status_transition,
+    validate_cancellation_policy, verify_booking_access_token_signature,
+    verify_booking_calendar_feed_token_signature, BOOKING_ACCESS_TOKEN_VALID_DAYS_AFTER_END,
+    BOOKING_EVENT_DISPATCH_BATCH_SIZE, BOOKING_EVENT_MAX_ATTEMPTS, BOOKING_HOLD_DURATION_MINUTES,
+    CALENDAR_FEED_HISTORY_DAYS, DEFAULT_BOOKING_GRANULARITY, DEFAULT_BOOKING_TIMEZONE,
+    DEFAULT_COMPLETION_GRACE_HOURS, DEFAULT_REMINDER_LEAD_HOURS, DEFAULT_RESPONSE_DEADLINE_HOURS,
+    MAX_BOOKING_SLOT_HOURS, NOTIFICATION_CLAIM_SECONDS, NOTIFICATION_DELIVERY_BATCH_SIZE,
+    NOTIFICATION_MAX_ATTEMPTS, PARTIAL_BOOKING_RESPONSE_HOURS,
+    PENDING_BOOKING_HOLD_RETENTION_HOURS, RESPONSE_DEADLINE_WARNING_HOURS,
+    WEBHOOK_DELIVERY_BATCH_SIZE, WEBHOOK_DELIVERY_LOG_SIZE, WEBHOOK_MAX_ATTEMPTS,
+    WEBHOOK_PING_DELAY_MINUTES,
 };
+use crate::routes::bookings::bookings_webhooks::{
+    build_booking_webhook_payload, send_booking_webhook, validate_booking_webhook_url,
+};
+use crate::routes::pricing::pricing_model::CalculatePriceRequest;
+use crate::routes::pricing::pricing_service::calculate_price;
 use crate::routes::rentals::rentals_service::get_rental_by_rental_id;
-use crate::routes::transactions::transactions_model::TransactionType;
+use crate::routes::transactions::transactions_model::{Transaction, TransactionType};
 use crate::routes::transactions::transactions_service::{
     get_transaction_by_transaction_id, handle_transaction_accept_decline,
-    handle_transaction_cancel_booking, handle_transaction_complete,
+    handle_transaction_booking_settlement, handle_transaction_cancel_booking,
+    handle_transaction_cancel_booking_with_refund, handle_transaction_complete,
 };
+use crate::routes::transactions::transactions_utils::build_transaction_email_details;
+use crate::routes::vendors::vendors_service::get_vendor_by_vendor_id;
 use crate::shared::types::PaginatedResponse;
 use crate::startup::AppState;
 use crate::utilities::database::db_executor::DbExecutor;
 use crate::utilities::errors::AppError;
+use anyhow::Context;
+use secrecy::ExposeSecret;
 use std::collections::HashMap;
 use std::sync::Arc;
+use time::{Duration, OffsetDateTime};
+use time_tz::{OffsetDateTimeExt, TimeZone, Tz};
 use uuid::Uuid;
 
 #[tracing::instrument(name = "Request booking", skip(executor))]
@@ -32,6 +140,23 @@ pub async fn request_booking<'e>(
     request: RequestBooking,
     executor: &mut DbExecutor<'e>,
 ) -> Result<Booking, AppError> {
+    validate_booking_request(&request)?;
+
+    let availability_query: GetAvailabilityQuery = GetAvailabilityQuery {
+        rental_id: request.rental_id,
+        start_date: request.start_date,
+        end_date: request.end_date,
+        exclude_transaction_id: request.transaction_id,
+        exclude_booking_id: None,
+        // Don't consider pending booking holds, only blocked
+        booking_hold_status: Some(BookingHoldStatus::Blocked),
+    };
+    check_availability(request.quantity, availability_query, executor).await?;
+
+    create_requested_booking(request, executor).await
+}
+
+fn validate_booking_request(request: &RequestBooking) -> Result<(), AppError> {
     match request.transaction_type {
         TransactionType::External => {
             if request.pricing_id.is_some() {
@@ -59,20 +184,25 @@ pub async fn request_booking<'e>(
         }
     }
 
-    let availability_query: GetAvailabilityQuery = GetAvailabilityQuery {
-        rental_id: request.rental_id,
-        start_date: request.start_date,
-        end_date: request.end_date,
-        exclude_transaction_id: request.transaction_id,
-        exclude_booking_id: None,
-        // Don't consider pending booking holds, only blocked
-        booking_hold_status: Some(BookingHoldStatus::Blocked),
-    };
-    check_availability(request.quantity, availability_query, executor).await?;
+    Ok(())
+}
 
+// Callers check availability first
+async fn create_requested_booking<'e>(
+    request: RequestBooking,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Booking, AppError> {
     let booking_id = create_booking_in_database(request, executor).await?;
     let booking = get_booking_by_booking_id(&booking_id, executor).await?;
 
+    publish_booking_event(
+        BookingEvent::Created {
+            booking: booking.clone(),
+        },
+        executor,
+    )
+    .await?;
+
     Ok(booking)
 }
 
@@ -82,12 +212,53 @@ pub async fn check_availability<'e>(
     query_params: GetAvailabilityQuery,
     executor: &mut DbExecutor<'e>,
 ) -> Result<Vec<Availability>, AppError> {
-    let availability = get_availability(query_params, executor).await?;
+    let (settings, timezone) = get_rental_booking_slots(&query_params.rental_id, executor).await?;
+    let granularity = settings.booking_granularity;
+    validate_booking_granularity(
+        &granularity,
+        timezone,
+        &query_params.start_date,
+        &query_params.end_date,
+    )?;
 
-    // Check if the requested quantity is available
-    let is_available = availability
-        .iter()
-        .all(|a| a.available_quantity >= quantity);
+    // The requested units also need to be free for their own turnaround, so the window reaches
+    // over the buffers on both sides of the requested slots
+    let (buffer_before, buffer_after) = booking_buffers(&settings);
+    let (window_start, window_end) = availability_window(&query_params, &granularity, timezone);
+    let ranges = build_availability_ranges(
+        &query_params,
+        &settings,
+        timezone,
+        window_start - buffer_before,
+        window_end + buffer_after,
+        executor,
+    )
+    .await?;
+
+    // Check if the requested quantity is available at every point of the requested slots and
+    // their buffers
+    let requested = BookingInterval::from_slots(
+        query_params.start_date,
+        query_params.end_date,
+        quantity,
+        BookingIntervalKind::Booked,
+        &granularity,
+        timezone,
+    );
+    let (requested_start, requested_end) = (
+        requested.start - buffer_before,
+        requested.end + buffer_after,
+    );
+    if ranges.iter().any(|range| {
+        range.blacked_out && range.start_date < requested_end && range.end_date > requested_start
+    }) {
+        return Err(AppError::ValidationError(String::from(
+            "Rental is closed for a blackout during the requested dates.",
+        )));
+    }
+
+    let is_available = !min_available_quantity(&ranges, requested_start, requested_end)
+        .is_some_and(|available_quantity| available_quantity < quantity);
 
     if !is_available {
         return Err(AppError::ValidationError(String::from(
@@ -95,6 +266,9 @@ pub async fn check_availability<'e>(
         )));
     }
 
+    let availability =
+        expand_availability_ranges(&ranges, window_start, window_end, &granularity, timezone);
+
     Ok(availability)
 }
 
@@ -103,26 +277,119 @@ pub async fn get_availability<'e>(
     query_params: GetAvailabilityQuery,
     executor: &mut DbExecutor<'e>,
 ) -> Result<Vec<Availability>, AppError> {
+    let (settings, timezone) = get_rental_booking_slots(&query_params.rental_id, executor).await?;
+    let granularity = settings.booking_granularity;
+
+    let (window_start, window_end) = availability_window(&query_params, &granularity, timezone);
+    let ranges = build_availability_ranges(
+        &query_params,
+        &settings,
+        timezone,
+        window_start,
+        window_end,
+        executor,
+    )
+    .await?;
+
+    Ok(expand_availability_ranges(
+        &ranges,
+        window_start,
+        window_end,
+        &granularity,
+        timezone,
+    ))
+}
+
+#[tracing::instrument(name = "Get availability ranges", skip(executor))]
+pub async fn get_availability_ranges<'e>(
+    query_params: GetAvailabilityQuery,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<AvailabilityRange>, AppError> {
+    let (settings, timezone) = get_rental_booking_slots(&query_params.rental_id, executor).await?;
+
+    let (window_start, window_end) =
+        availability_window(&query_params, &settings.booking_granularity, timezone);
+
+    build_availability_ranges(
+        &query_params,
+        &settings,
+        timezone,
+        window_start,
+        window_end,
+        executor,
+    )
+    .await
+}
+
+// Every slot from the one the start date falls in to the one the end date falls in
+fn availability_window(
+    query_params: &GetAvailabilityQuery,
+    granularity: &BookingGranularity,
+    timezone: &Tz,
+) -> (OffsetDateTime, OffsetDateTime) {
+    let window_start = align_to_booking_granularity(query_params.start_date, granularity, timezone);
+    let window_end = next_booking_slot(
+        align_to_booking_granularity(query_params.end_date, granularity, timezone),
+        granularity,
+        timezone,
+    );
+
+    (window_start, window_end)
+}
+
+fn booking_buffers(settings: &BookingRentalSettings) -> (Duration, Duration) {
+    (
+        Duration::minutes(settings.buffer_before_minutes.into()),
+        Duration::minutes(settings.buffer_after_minutes.into()),
+    )
+}
+
+async fn build_availability_ranges<'e>(
+    query_params: &GetAvailabilityQuery,
+    settings: &BookingRentalSettings,
+    timezone: &Tz,
+    window_start: OffsetDateTime,
+    window_end: OffsetDateTime,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<AvailabilityRange>, AppError> {
     // Fetch total quantity available for the rental item
     let rental = get_rental_by_rental_id(&query_params.rental_id, executor).await?;
     let total_quantity = rental.quantity;
 
-    // Fetch booked quantities for each day within the date range
-    let booked_quantities = get_booked_quantity_by_rental_id(
+    let granularity = &settings.booking_granularity;
+    let (buffer_before, buffer_after) = booking_buffers(settings);
+
+    // Bookings and holds whose last slot starts before the window can still overlap it, and
+    // the buffers reach past both ends of a booking
+    let lookup_start = window_start - Duration::hours(MAX_BOOKING_SLOT_HOURS) - buffer_after;
+    let lookup_end = window_end + buffer_before;
+
+    let mut intervals: Vec<BookingInterval> = get_booked_dates_by_rental_id(
         &query_params.rental_id,
         &query_params.exclude_booking_id,
-        &query_params.start_date,
-        &query_params.end_date,
+        &lookup_start,
+        &lookup_end,
         executor,
     )
-    .await?;
+    .await?
+    .into_iter()
+    .map(|(start_date, end_date, quantity)| {
+        BookingInterval::from_slots(
+            start_date,
+            end_date,
+            quantity,
+            BookingIntervalKind::Booked,
+            granularity,
+            timezone,
+        )
+    })
+    .collect();
 
-    // Fetch booking holds for each day within the date range
     let booking_holds = get_booking_holds_by_query(
         &GetBookingHoldsQuery {
             rental_id: Some(query_params.rental_id),
-            start_date: Some(query_params.start_date),
-            end_date: Some(query_params.end_date),
+            start_date: Some(lookup_start),
+            end_date: Some(lookup_end),
             exclude_transaction_id: query_params.exclude_transaction_id,
             booking_hold_status: query_params.booking_hold_status,
             per_page: Some(10000),
@@ -133,13 +400,52 @@ pub async fn get_availability<'e>(
     .await?
     .data;
 
-    // Merge booked quantities and booking holds
-    let merged_bookings = merge_booked_quantities_and_holds(booked_quantities, booking_holds);
+    // TODO: We should still delete payment intents of pending holds that are older than 24 hours.
+    //  The holds themselves are cleaned up by the booking jobs.
 
-    // Calculate availability from merged data
-    let availability = calculate_availability_from_merged_bookings(merged_bookings, total_quantity);
+    // Expired holds no longer lock in quantity
+    let now = OffsetDateTime::now_utc();
+    intervals.extend(
+        booking_holds
+            .into_iter()
+            .filter(|hold| !hold.expires_at.is_some_and(|expires_at| expires_at <= now))
+            .map(|hold| {
+                BookingInterval::from_slots(
+                    hold.start_date,
+                    hold.end_date,
+                    hold.quantity,
+                    BookingIntervalKind::Held,
+                    granularity,
+                    timezone,
+                )
+            }),
+    );
 
-    Ok(availability)
+    // Units stay occupied for the turnaround before and after every booking and hold
+    let buffers: Vec<BookingInterval> = intervals
+        .iter()
+        .flat_map(|interval| interval.buffers(buffer_before, buffer_after))
+        .collect();
+    intervals.extend(buffers);
+
+    // Blackouts of the rental and the whole vendor close it regardless of quantity
+    let blackouts = get_booking_blackouts_from_database_by_rental_id(
+        &rental.vendor_id,
+        &query_params.rental_id,
+        &window_start,
+        &window_end,
+        executor,
+    )
+    .await?;
+    intervals.extend(
+        blackouts
+            .iter()
+            .flat_map(|blackout| blackout_intervals(blackout, window_start, window_end, timezone)),
+    );
+
+    let ranges = sweep_availability_ranges(&intervals, window_start, window_end, total_quantity);
+
+    Ok(ranges)
 }
 
 #[tracing::instrument(name = "Get availabilities", skip(executor))]
@@ -169,11 +475,18 @@ pub async fn get_availabilities<'e>(
 #[tracing::instrument(name = "Accept booking", skip(state, executor))]
 pub async fn accept_booking<'e>(
     booking_id: &Uuid,
+    actor: &BookingActor,
     state: Arc<AppState>,
     executor: &mut DbExecutor<'e>,
 ) -> Result<Booking, AppError> {
-    let booking =
-        update_booking_status_by_booking_id(booking_id, &BookingStatus::Accepted, executor).await?;
+    let booking = update_booking_status_by_booking_id(
+        booking_id,
+        &BookingStatus::Accepted,
+        actor,
+        None,
+        executor,
+    )
+    .await?;
 
     // TODO: Ideally this should be done outside of the "accept" functionality.
     //  Start a cron job that checks for eligible confirm/decline/partial transactions
@@ -187,11 +500,18 @@ pub async fn accept_booking<'e>(
 #[tracing::instrument(name = "Decline booking", skip(state, executor))]
 pub async fn decline_booking<'e>(
     booking_id: &Uuid,
+    actor: &BookingActor,
     state: Arc<AppState>,
     executor: &mut DbExecutor<'e>,
 ) -> Result<Booking, AppError> {
-    let booking =
-        update_booking_status_by_booking_id(booking_id, &BookingStatus::Declined, executor).await?;
+    let booking = update_booking_status_by_booking_id(
+        booking_id,
+        &BookingStatus::Declined,
+        actor,
+        None,
+        executor,
+    )
+    .await?;
 
     let transaction = get_transaction_by_transaction_id(&booking.transaction_id, executor).await?;
     handle_transaction_accept_decline(&transaction, state, executor).await?;
@@ -199,156 +519,3023 @@ pub async fn decline_booking<'e>(
     Ok(booking)
 }
 
-// TODO: Something to consider here is if its a user or a vendor cancelation
-// If its user, we should refund but keep the deposit
-// If its vendor, we refund everything
-#[tracing::instrument(name = "Cancel booking", skip(state, executor))]
-pub async fn cancel_booking<'e>(
-    booking: Booking,
+#[tracing::instrument(name = "Expire booking", skip(state, executor))]
+pub async fn expire_booking<'e>(
+    booking_id: &Uuid,
+    actor: &BookingActor,
     state: Arc<AppState>,
     executor: &mut DbExecutor<'e>,
 ) -> Result<Booking, AppError> {
-    let previous_status = booking.booking_status;
-
     let booking = update_booking_status_by_booking_id(
-        &booking.booking_id,
-        &BookingStatus::Canceled,
+        booking_id,
+        &BookingStatus::Expired,
+        actor,
+        Some("Vendor did not respond before the response deadline"),
         executor,
     )
     .await?;
 
+    // Expired bookings are refunded and the renter notified the same way as declined bookings
     let transaction = get_transaction_by_transaction_id(&booking.transaction_id, executor).await?;
-    if transaction.transaction_type == TransactionType::External {
-        return Ok(booking); // Early return for external bookings because there is no refund or notification necessary
-    }
-
-    handle_transaction_cancel_booking(&transaction, &booking, &previous_status, state, executor)
-        .await?;
+    handle_transaction_accept_decline(&transaction, state, executor).await?;
 
     Ok(booking)
 }
 
-#[tracing::instrument(name = "Confirm booking", skip(bookings, executor))]
-pub async fn confirm_bookings<'e>(
-    bookings: &[Booking],
-    executor: &mut DbExecutor<'e>,
-) -> Result<Vec<Booking>, AppError> {
-    let mut updated_bookings = Vec::new();
+#[tracing::instrument(name = "Expire unanswered booking requests", skip(state))]
+pub async fn expire_unanswered_booking_requests(state: Arc<AppState>) -> Result<usize, AppError> {
+    let mut executor = DbExecutor::Pool(&state.db_pool);
+    let booking_ids = get_unanswered_booking_request_ids_from_database(
+        DEFAULT_RESPONSE_DEADLINE_HOURS,
+        &mut executor,
+    )
+    .await?;
 
-    for booking in bookings {
-        let updated_booking = confirm_booking(booking, executor).await?;
-        updated_bookings.push(updated_booking);
+    let actor = BookingActor::System {
+        job: String::from("booking_request_expiry"),
+    };
+    let mut expired = 0;
+    for booking_id in booking_ids.iter() {
+        let mut item_executor = begin_booking_job_item(&state).await?;
+        let result = expire_unanswered_booking_request(
+            booking_id,
+            &actor,
+            state.clone(),
+            &mut item_executor,
+        )
+        .await;
+        if finish_booking_job_item(item_executor, booking_id, result)
+            .await?
+            .is_some()
+        {
+            expired += 1;
+        }
     }
 
-    Ok(updated_bookings)
+    Ok(expired)
 }
 
-#[tracing::instrument(name = "Confirm booking", skip(booking, executor))]
-pub async fn confirm_booking<'e>(
-    booking: &Booking,
+async fn expire_unanswered_booking_request<'e>(
+    booking_id: &Uuid,
+    actor: &BookingActor,
+    state: Arc<AppState>,
     executor: &mut DbExecutor<'e>,
 ) -> Result<Booking, AppError> {
-    let booking = update_booking_status_by_booking_id(
-        &booking.booking_id,
-        &BookingStatus::Confirmed,
-        executor,
-    )
-    .await?;
+    let booking = get_booking_by_booking_id(booking_id, executor).await?;
+    validate_booking_status_transition(booking.booking_status, BookingStatus::Expired)?;
 
-    Ok(booking)
+    expire_booking(booking_id, actor, state, executor).await
 }
 
-#[tracing::instrument(name = "Complete booking", skip(state, executor))]
-pub async fn complete_booking<'e>(
+// Renter cancellations are refunded according to the vendor's cancellation policy,
+// vendor cancellations are always refunded in full
+#[tracing::instrument(name = "Cancel booking", skip(state, executor))]
+pub async fn cancel_booking<'e>(
     booking: Booking,
+    canceled_by: &BookingParty,
+    actor: &BookingActor,
     state: Arc<AppState>,
     executor: &mut DbExecutor<'e>,
-) -> Result<Booking, AppError> {
+) -> Result<CanceledBooking, AppError> {
+    let previous_status = booking.booking_status;
+
+    let policy = get_cancellation_policy_by_vendor_id(&booking.vendor_id, executor).await?;
+    let refund =
+        calculate_cancellation_refund(&booking, canceled_by, policy, OffsetDateTime::now_utc());
+
     let booking = update_booking_status_by_booking_id(
         &booking.booking_id,
-        &BookingStatus::Completed,
+        &BookingStatus::Canceled,
+        actor,
+        None,
         executor,
     )
     .await?;
 
-    let transaction = get_transaction_by_transaction_id(&booking.transaction_id, executor).await?;
-    if transaction.transaction_type == TransactionType::External {
-        return Ok(booking); // Early return for external bookings because there is no payout necessary
+    // Only confirmed bookings were sent a calendar invite that needs canceling
+    if previous_status == BookingStatus::Confirmed {
+        increment_booking_calendar_sequence_in_database(&booking.booking_id, executor).await?;
     }
 
-    handle_transaction_complete(
-        &booking.transaction_id,
-        &booking.vendor_id,
-        state.clone(),
+    publish_booking_event(
+        BookingEvent::Canceled {
+            booking: booking.clone(),
+            previous_status,
+            canceled_by: *canceled_by,
+        },
         executor,
     )
     .await?;
 
-    Ok(booking)
+    let transaction = get_transaction_by_transaction_id(&booking.transaction_id, executor).await?;
+    if transaction.transaction_type == TransactionType::External {
+        return Ok(CanceledBooking { booking, refund }); // Early return for external bookings because there is no refund necessary
+    }
+
+    // Every cancellation goes through the transactions module so it can release the booking,
+    // with the refund from the vendor's cancellation policy when it isn't a full refund
+    if refund.refund_percent == 100 {
+        handle_transaction_cancel_booking(
+            &transaction,
+            &booking,
+            &previous_status,
+            state.clone(),
+            executor,
+        )
+        .await?;
+    } else {
+        handle_transaction_cancel_booking_with_refund(
+            &transaction,
+            &booking,
+            &previous_status,
+            refund.refund_amount,
+            state.clone(),
+            executor,
+        )
+        .await?;
+    }
+
+    Ok(CanceledBooking { booking, refund })
 }
 
-#[tracing::instrument(name = "Get all bookings by query", skip(executor))]
-pub async fn get_bookings_by_query<'e>(
-    query_params: &GetBookingsQuery,
+#[tracing::instrument(name = "Get booking vendor settings by vendor id", skip(executor))]
+pub async fn get_booking_vendor_settings_by_vendor_id<'e>(
+    vendor_id: &Uuid,
     executor: &mut DbExecutor<'e>,
-) -> Result<PaginatedResponse<Booking>, AppError> {
-    let mut bookings_response = get_bookings_from_database_by_query(query_params, executor).await?;
-
-    let include_rental = query_params.include_rental == Some(true);
-    let include_availability = query_params.check_availability == Some(true);
-    bookings_response.data = build_booking_details(
-        bookings_response.data,
-        include_rental,
-        include_availability,
-        executor,
-    )
-    .await?;
+) -> Result<BookingVendorSettings, AppError> {
+    let settings = get_booking_vendor_settings_from_database_by_vendor_id(vendor_id, executor)
+        .await?
+        .unwrap_or(BookingVendorSettings {
+            vendor_id: *vendor_id,
+            response_deadline_hours: DEFAULT_RESPONSE_DEADLINE_HOURS,
+            completion_grace_hours: DEFAULT_COMPLETION_GRACE_HOURS,
+            reminder_lead_hours: DEFAULT_REMINDER_LEAD_HOURS,
+            timezone: String::from(DEFAULT_BOOKING_TIMEZONE),
+        });
 
-    Ok(bookings_response)
+    Ok(settings)
 }
 
-#[tracing::instrument(name = "Get booking by booking id", skip(executor))]
-pub async fn get_booking_by_booking_id<'e>(
-    booking_id: &Uuid,
+#[tracing::instrument(name = "Update booking vendor settings", skip(executor))]
+pub async fn update_booking_vendor_settings<'e>(
+    vendor_id: &Uuid,
+    update: UpdateBookingVendorSettings,
     executor: &mut DbExecutor<'e>,
-) -> Result<Booking, AppError> {
-    let booking_option = match get_booking_from_database_by_booking_id(booking_id, executor).await {
-        Err(e) => {
-            tracing::error!("Failed to get booking by booking id: {}", e);
-            return Err(AppError::UnexpectedError(e));
+) -> Result<BookingVendorSettings, AppError> {
+    let mut settings = get_booking_vendor_settings_by_vendor_id(vendor_id, executor).await?;
+
+    if let Some(response_deadline_hours) = update.response_deadline_hours {
+        if response_deadline_hours <= 0 {
+            return Err(AppError::ValidationError(String::from(
+                "Response deadline must be at least 1 hour",
+            )));
         }
-        Ok(x) => x,
-    };
+        settings.response_deadline_hours = response_deadline_hours;
+    }
 
-    let mut booking = match booking_option {
-        None => {
-            tracing::error!("Booking not found for booking id: {}", booking_id);
-            return Err(AppError::DoesNotExistError(String::from(
-                "Booking not found",
+    if let Some(completion_grace_hours) = update.completion_grace_hours {
+        if completion_grace_hours < 0 {
+            return Err(AppError::ValidationError(String::from(
+                "Completion grace period cannot be negative",
             )));
         }
-        Some(booking) => booking,
+        settings.completion_grace_hours = completion_grace_hours;
+    }
+
+    if let Some(reminder_lead_hours) = update.reminder_lead_hours {
+        if reminder_lead_hours <= 0 {
+            return Err(AppError::ValidationError(String::from(
+                "Reminder lead time must be at least 1 hour",
+            )));
+        }
+        settings.reminder_lead_hours = reminder_lead_hours;
+    }
+
+    if let Some(timezone) = update.timezone {
+        parse_booking_timezone(&timezone)?;
+        // Existing bookings of rentals without their own timezone are aligned to this one
+        if timezone != settings.timezone
+            && get_active_booking_count_from_database_by_vendor_id(vendor_id, executor).await? > 0
+        {
+            return Err(AppError::ValidationError(String::from(
+                "Timezone cannot change while the vendor has active bookings",
+            )));
+        }
+        settings.timezone = timezone;
+    }
+
+    upsert_booking_vendor_settings_in_database(&settings, executor).await?;
+
+    Ok(settings)
+}
+
+#[tracing::instrument(name = "Get booking email preferences by user id", skip(executor))]
+pub async fn get_booking_email_preferences_by_user_id<'e>(
+    user_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<BookingEmailPreferences, AppError> {
+    let preferences = get_booking_email_preferences_from_database_by_user_id(user_id, executor)
+        .await?
+        .unwrap_or(BookingEmailPreferences {
+            user_id: *user_id,
+            locale: String::from(BookingLocale::default().as_str()),
+        });
+
+    Ok(preferences)
+}
+
+#[tracing::instrument(name = "Update booking email preferences", skip(executor))]
+pub async fn update_booking_email_preferences<'e>(
+    user_id: &Uuid,
+    update: UpdateBookingEmailPreferences,
+    executor: &mut DbExecutor<'e>,
+) -> Result<BookingEmailPreferences, AppError> {
+    let mut preferences = get_booking_email_preferences_by_user_id(user_id, executor).await?;
+
+    if let Some(locale) = update.locale {
+        let locale = BookingLocale::from_tag(&locale).ok_or(AppError::ValidationError(
+            String::from("Locale must be one of en, fr or es"),
+        ))?;
+        preferences.locale = String::from(locale.as_str());
+    }
+
+    upsert_booking_email_preferences_in_database(&preferences, executor).await?;
+
+    Ok(preferences)
+}
+
+// Renter emails are formatted from the transaction's bookings in the renter's preferred locale
+#[tracing::instrument(name = "Build booking email details", skip(transaction, executor))]
+async fn build_booking_email_details<'e>(
+    transaction: &Transaction,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(UserEmail, BookingEmailDetails), AppError> {
+    let (user_email, params) = build_transaction_email_details(transaction, executor).await?;
+
+    let bookings_query = GetBookingsQuery {
+        transaction_ids: Some(vec![transaction.transaction_id]),
+        per_page: Some(10000),
+        ..Default::default()
+    };
+    let bookings = get_bookings_by_query(&bookings_query, executor).await?.data;
+    let (start_date, end_date) = match (
+        bookings.iter().map(|booking| booking.start_date).min(),
+        bookings.iter().map(|booking| booking.end_date).max(),
+    ) {
+        (Some(start_date), Some(end_date)) => (start_date, end_date),
+        _ => {
+            return Err(AppError::DoesNotExistError(String::from(
+                "Transaction has no bookings",
+            )))
+        }
     };
 
-    let rental_id = booking.rental_id;
-    let rental = get_rental_by_rental_id(&rental_id, executor).await?;
-    booking.rental = Some(rental);
+    let locale = match transaction.user_id {
+        Some(user_id) => get_booking_email_preferences_from_database_by_user_id(&user_id, executor)
+            .await?
+            .map(|preferences| preferences.locale),
+        None => None,
+    };
 
-    Ok(booking)
+    let rentals = serde_json::to_value(&params.rentals)
+        .context("Failed to serialize booking email rentals")?;
+    let details = BookingEmailDetails {
+        confirmation_code: params.confirmation_code,
+        rentals,
+        locale,
+        start_date,
+        end_date,
+        total: bookings.iter().map(|booking| booking.total).sum(),
+    };
+
+    Ok((user_email, details))
 }
 
-#[tracing::instrument(name = "Update booking by booking id", skip(executor))]
-pub async fn update_booking_status_by_booking_id<'e>(
-    booking_id: &Uuid,
-    booking_status: &BookingStatus,
+#[tracing::instrument(name = "Get booking rental settings by rental id", skip(executor))]
+pub async fn get_booking_rental_settings_by_rental_id<'e>(
+    rental_id: &Uuid,
     executor: &mut DbExecutor<'e>,
-) -> Result<Booking, AppError> {
-    // TODO: For updates, we should setup a transaction instead and use "FOR UPDATE"
-    // at the end of the select clause. This will lock the row until the transaction is committed.
+) -> Result<BookingRentalSettings, AppError> {
+    let settings = get_booking_rental_settings_from_database_by_rental_id(rental_id, executor)
+        .await?
+        .unwrap_or(BookingRentalSettings {
+            rental_id: *rental_id,
+            booking_granularity: DEFAULT_BOOKING_GRANULARITY,
+            timezone: None,
+            buffer_before_minutes: 0,
+            buffer_after_minutes: 0,
+        });
 
-    update_booking_status_in_database_by_booking_id(booking_id, booking_status, executor).await?;
+    Ok(settings)
+}
 
-    let booking_new = get_booking_by_booking_id(booking_id, executor).await?;
+#[tracing::instrument(name = "Update booking rental settings", skip(executor))]
+pub async fn update_booking_rental_settings<'e>(
+    rental_id: &Uuid,
+    update: UpdateBookingRentalSettings,
+    executor: &mut DbExecutor<'e>,
+) -> Result<BookingRentalSettings, AppError> {
+    let mut settings = get_booking_rental_settings_by_rental_id(rental_id, executor).await?;
 
-    Ok(booking_new)
+    if let Some(booking_granularity) = update.booking_granularity {
+        // Existing bookings are stored as slots of the current granularity
+        if booking_granularity != settings.booking_granularity
+            && get_active_booking_count_from_database_by_rental_id(rental_id, executor).await? > 0
+        {
+            return Err(AppError::ValidationError(String::from(
+                "Booking granularity cannot change while the rental has active bookings",
+            )));
+        }
+        settings.booking_granularity = booking_granularity;
+    }
+
+    if let Some(timezone) = update.timezone {
+        parse_booking_timezone(&timezone)?;
+        // Existing bookings are aligned to the current timezone's slots
+        if settings.timezone.as_ref() != Some(&timezone)
+            && get_active_booking_count_from_database_by_rental_id(rental_id, executor).await? > 0
+        {
+            return Err(AppError::ValidationError(String::from(
+                "Timezone cannot change while the rental has active bookings",
+            )));
+        }
+        settings.timezone = Some(timezone);
+    }
+
+    if let Some(buffer_before_minutes) = update.buffer_before_minutes {
+        if buffer_before_minutes < 0 {
+            return Err(AppError::ValidationError(String::from(
+                "Buffer before bookings cannot be negative",
+            )));
+        }
+        settings.buffer_before_minutes = buffer_before_minutes;
+    }
+
+    if let Some(buffer_after_minutes) = update.buffer_after_minutes {
+        if buffer_after_minutes < 0 {
+            return Err(AppError::ValidationError(String::from(
+                "Buffer after bookings cannot be negative",
+            )));
+        }
+        settings.buffer_after_minutes = buffer_after_minutes;
+    }
+
+    upsert_booking_rental_settings_in_database(&settings, executor).await?;
+
+    Ok(settings)
+}
+
+// The settings and timezone a rental's slots follow, rentals use their vendor's timezone unless
+// they set their own
+#[tracing::instrument(name = "Get rental booking slots", skip(executor))]
+pub async fn get_rental_booking_slots<'e>(
+    rental_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(BookingRentalSettings, &'static Tz), AppError> {
+    let settings = get_booking_rental_settings_by_rental_id(rental_id, executor).await?;
+
+    let timezone = match &settings.timezone {
+        Some(timezone) => parse_booking_timezone(timezone)?,
+        None => {
+            let rental = get_rental_by_rental_id(rental_id, executor).await?;
+            get_vendor_booking_timezone(&rental.vendor_id, executor).await?
+        }
+    };
+
+    Ok((settings, timezone))
+}
+
+#[tracing::instrument(name = "Get rental booking timezone", skip(executor))]
+pub async fn get_rental_booking_timezone<'e>(
+    rental_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<&'static Tz, AppError> {
+    let (_, timezone) = get_rental_booking_slots(rental_id, executor).await?;
+
+    Ok(timezone)
+}
+
+#[tracing::instrument(name = "Get vendor booking timezone", skip(executor))]
+pub async fn get_vendor_booking_timezone<'e>(
+    vendor_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<&'static Tz, AppError> {
+    let settings = get_booking_vendor_settings_by_vendor_id(vendor_id, executor).await?;
+
+    parse_booking_timezone(&settings.timezone)
+}
+
+#[tracing::instrument(name = "Create booking blackout", skip(executor))]
+pub async fn create_booking_blackout<'e>(
+    vendor_id: &Uuid,
+    blackout: CreateBookingBlackout,
+    executor: &mut DbExecutor<'e>,
+) -> Result<BookingBlackout, AppError> {
+    validate_booking_blackout(&blackout)?;
+
+    if let Some(rental_id) = &blackout.rental_id {
+        let rental = get_rental_by_rental_id(rental_id, executor).await?;
+        if rental.vendor_id != *vendor_id {
+            return Err(AppError::ValidationError(String::from(
+                "Rental does not belong to this vendor",
+            )));
+        }
+    }
+
+    let blackout_id = Uuid::new_v4();
+    let new_blackout = BookingBlackout {
+        blackout_id,
+        created_at: OffsetDateTime::now_utc(),
+        vendor_id: *vendor_id,
+        rental_id: blackout.rental_id,
+        start_date: blackout.start_date,
+        end_date: blackout.end_date,
+        recurrence: blackout.recurrence,
+        recur_until: blackout.recur_until,
+        reason: blackout.reason.clone(),
+    };
+
+    // Closing over existing bookings is the vendor's call, they have to force it
+    let overlapping_bookings = count_bookings_overlapping_blackout(&new_blackout, executor).await?;
+    if overlapping_bookings > 0 && !blackout.force {
+        return Err(AppError::ValidationError(format!(
+            "Blackout overlaps {} existing bookings, set force to create it anyway",
+            overlapping_bookings
+        )));
+    }
+
+    create_booking_blackout_in_database(&blackout_id, vendor_id, &blackout, executor).await?;
+
+    get_booking_blackout_by_blackout_id(&blackout_id, executor).await
+}
+
+#[tracing::instrument(name = "Get booking blackouts by vendor id", skip(executor))]
+pub async fn get_booking_blackouts_by_vendor_id<'e>(
+    vendor_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<BookingBlackout>, AppError> {
+    let blackouts = get_booking_blackouts_from_database_by_vendor_id(vendor_id, executor).await?;
+
+    Ok(blackouts)
+}
+
+#[tracing::instrument(name = "Get booking blackout by blackout id", skip(executor))]
+pub async fn get_booking_blackout_by_blackout_id<'e>(
+    blackout_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<BookingBlackout, AppError> {
+    match get_booking_blackout_from_database_by_blackout_id(blackout_id, executor).await? {
+        None => Err(AppError::DoesNotExistError(String::from(
+            "Booking blackout not found",
+        ))),
+        Some(blackout) => Ok(blackout),
+    }
+}
+
+#[tracing::instrument(name = "Delete booking blackout", skip(executor))]
+pub async fn delete_booking_blackout<'e>(
+    blackout_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), AppError> {
+    delete_booking_blackout_in_database(blackout_id, executor).await?;
+
+    Ok(())
+}
+
+// Active bookings with any slot inside an occurrence of the blackout. Each booking is checked in
+// its own rental's slots and timezone, like availability is.
+async fn count_bookings_overlapping_blackout<'e>(
+    blackout: &BookingBlackout,
+    executor: &mut DbExecutor<'e>,
+) -> Result<usize, AppError> {
+    let last_occurrence_end = match blackout.recurrence {
+        None => Some(blackout.end_date),
+        Some(_) => blackout
+            .recur_until
+            .map(|recur_until| recur_until + (blackout.end_date - blackout.start_date)),
+    };
+
+    // Bookings whose last slot starts before the blackout can still overlap it
+    let booked_dates = get_active_booking_dates_from_database_by_vendor_id(
+        &blackout.vendor_id,
+        &blackout.rental_id,
+        &(blackout.start_date - Duration::hours(MAX_BOOKING_SLOT_HOURS)),
+        &last_occurrence_end,
+        executor,
+    )
+    .await?;
+
+    let mut rental_slots: HashMap<Uuid, (BookingRentalSettings, &'static Tz)> = HashMap::new();
+    let mut overlapping_bookings = 0;
+    for (rental_id, start_date, end_date) in booked_dates {
+        if !rental_slots.contains_key(&rental_id) {
+            let slots = get_rental_booking_slots(&rental_id, executor).await?;
+            rental_slots.insert(rental_id, slots);
+        }
+        let (settings, timezone) = &rental_slots[&rental_id];
+
+        let booking = BookingInterval::from_slots(
+            start_date,
+            end_date,
+            1,
+            BookingIntervalKind::Booked,
+            &settings.booking_granularity,
+            timezone,
+        );
+        if !blackout_intervals(blackout, booking.start, booking.end, timezone).is_empty() {
+            overlapping_bookings += 1;
+        }
+    }
+
+    Ok(overlapping_bookings)
+}
+
+#[tracing::instrument(name = "Get cancellation policy by vendor id", skip(executor))]
+pub async fn get_cancellation_policy_by_vendor_id<'e>(
+    vendor_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Option<CancellationPolicy>, AppError> {
+    let policy = get_cancellation_policy_from_database_by_vendor_id(vendor_id, executor).await?;
+
+    Ok(policy)
+}
+
+#[tracing::instrument(name = "Upsert cancellation policy", skip(executor))]
+pub async fn upsert_cancellation_policy<'e>(
+    vendor_id: &Uuid,
+    policy: UpsertCancellationPolicy,
+    executor: &mut DbExecutor<'e>,
+) -> Result<CancellationPolicy, AppError> {
+    validate_cancellation_policy(&policy)?;
+
+    upsert_cancellation_policy_in_database(vendor_id, &policy, executor).await?;
+
+    let policy = get_cancellation_policy_by_vendor_id(vendor_id, executor)
+        .await?
+        .expect("Cancellation policy missing after upsert");
+
+    Ok(policy)
+}
+
+#[tracing::instrument(name = "Confirm booking", skip(bookings, executor))]
+pub async fn confirm_bookings<'e>(
+    bookings: &[Booking],
+    actor: &BookingActor,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<Booking>, AppError> {
+    let mut updated_bookings = Vec::new();
+
+    for booking in bookings {
+        let updated_booking = confirm_booking(booking, actor, executor).await?;
+        updated_bookings.push(updated_booking);
+    }
+
+    Ok(updated_bookings)
+}
+
+#[tracing::instrument(name = "Confirm booking", skip(booking, executor))]
+pub async fn confirm_booking<'e>(
+    booking: &Booking,
+    actor: &BookingActor,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Booking, AppError> {
+    let booking = update_booking_status_by_booking_id(
+        &booking.booking_id,
+        &BookingStatus::Confirmed,
+        actor,
+        None,
+        executor,
+    )
+    .await?;
+
+    Ok(booking)
+}
+
+#[tracing::instrument(name = "Get partial booking", skip(executor))]
+pub async fn get_partial_booking<'e>(
+    transaction_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<PartialBooking, AppError> {
+    let bookings_query = GetBookingsQuery {
+        transaction_ids: Some(vec![*transaction_id]),
+        include_rental: Some(true),
+        per_page: Some(10000),
+        ..Default::default()
+    };
+    let bookings = get_bookings_by_query(&bookings_query, executor).await?.data;
+
+    let mut bookings_by_status = group_bookings_by_status(bookings);
+    let requested = bookings_by_status
+        .remove(&BookingStatus::Requested)
+        .unwrap_or_default();
+    let accepted = bookings_by_status
+        .remove(&BookingStatus::Accepted)
+        .unwrap_or_default();
+    // Requests the vendor let expire count as declined
+    let declined: Vec<Booking> = [BookingStatus::Declined, BookingStatus::Expired]
+        .iter()
+        .flat_map(|status| bookings_by_status.remove(status).unwrap_or_default())
+        .collect();
+
+    if !requested.is_empty() || accepted.is_empty() || declined.is_empty() {
+        return Err(AppError::ValidationError(String::from(
+            "Transaction is not awaiting a partial booking response",
+        )));
+    }
+
+    let last_response = accepted
+        .iter()
+        .chain(declined.iter())
+        .map(|booking| booking.updated_at)
+        .max()
+        .expect("Partial booking has no bookings");
+
+    Ok(PartialBooking {
+        transaction_id: *transaction_id,
+        accepted,
+        declined,
+        respond_by: last_response + Duration::hours(PARTIAL_BOOKING_RESPONSE_HOURS),
+    })
+}
+
+#[tracing::instrument(name = "Confirm partial booking", skip(executor))]
+pub async fn confirm_partial_booking<'e>(
+    partial_booking: PartialBooking,
+    actor: &BookingActor,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<Booking>, AppError> {
+    if partial_booking.respond_by < OffsetDateTime::now_utc() {
+        return Err(AppError::ValidationError(String::from(
+            "The time to respond to this partial booking has passed.",
+        )));
+    }
+
+    confirm_bookings(&partial_booking.accepted, actor, executor).await
+}
+
+#[tracing::instrument(name = "Abandon partial booking", skip(state, executor))]
+pub async fn abandon_partial_booking<'e>(
+    partial_booking: PartialBooking,
+    actor: &BookingActor,
+    state: Arc<AppState>,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<Booking>, AppError> {
+    let mut canceled_bookings = Vec::new();
+
+    for booking in partial_booking.accepted {
+        validate_booking_status_transition(booking.booking_status, BookingStatus::Canceled)?;
+        // The vendor declined part of the request, so the renter is refunded in full
+        let canceled_booking = cancel_booking(
+            booking,
+            &BookingParty::Vendor,
+            actor,
+            state.clone(),
+            executor,
+        )
+        .await?;
+        canceled_bookings.push(canceled_booking.booking);
+    }
+
+    Ok(canceled_bookings)
+}
+
+// Abandons partial bookings the renter never responded to so the accepted items are refunded
+#[tracing::instrument(name = "Resolve expired partial bookings", skip(state))]
+pub async fn resolve_expired_partial_bookings(state: Arc<AppState>) -> Result<usize, AppError> {
+    let mut executor = DbExecutor::Pool(&state.db_pool);
+    let responded_before =
+        OffsetDateTime::now_utc() - Duration::hours(PARTIAL_BOOKING_RESPONSE_HOURS);
+    let transaction_ids =
+        get_expired_partial_booking_transaction_ids_from_database(&responded_before, &mut executor)
+            .await?;
+
+    let actor = BookingActor::System {
+        job: String::from("partial_booking_timeout"),
+    };
+    let mut resolved = 0;
+    for transaction_id in transaction_ids.iter() {
+        let mut item_executor = begin_booking_job_item(&state).await?;
+        let result = match get_partial_booking(transaction_id, &mut item_executor).await {
+            Ok(partial_booking) => {
+                abandon_partial_booking(partial_booking, &actor, state.clone(), &mut item_executor)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        if finish_booking_job_item(item_executor, transaction_id, result)
+            .await?
+            .is_some()
+        {
+            resolved += 1;
+        }
+    }
+
+    Ok(resolved)
+}
+
+#[tracing::instrument(name = "Complete booking", skip(state, executor))]
+pub async fn complete_booking<'e>(
+    booking: Booking,
+    actor: &BookingActor,
+    state: Arc<AppState>,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Booking, AppError> {
+    let booking = update_booking_status_by_booking_id(
+        &booking.booking_id,
+        &BookingStatus::Completed,
+        actor,
+        None,
+        executor,
+    )
+    .await?;
+
+    let transaction = get_transaction_by_transaction_id(&booking.transaction_id, executor).await?;
+    if transaction.transaction_type == TransactionType::External {
+        return Ok(booking); // Early return for external bookings because there is no payout necessary
+    }
+
+    handle_transaction_complete(
+        &booking.transaction_id,
+        &booking.vendor_id,
+        state.clone(),
+        executor,
+    )
+    .await?;
+
+    Ok(booking)
+}
+
+#[tracing::instrument(name = "Modify booking", skip(state, executor))]
+pub async fn modify_booking<'e>(
+    booking: Booking,
+    modification: ModifyBooking,
+    requested_by: &Uuid,
+    requested_by_party: &BookingParty,
+    state: Arc<AppState>,
+    executor: &mut DbExecutor<'e>,
+) -> Result<BookingModification, AppError> {
+    match booking.booking_status {
+        BookingStatus::Requested | BookingStatus::Accepted | BookingStatus::Confirmed => {}
+        _ => {
+            return Err(AppError::ValidationError(String::from(
+                "Only requested, accepted or confirmed bookings can be modified",
+            )));
+        }
+    }
+
+    let has_pending_modification =
+        get_booking_modifications_by_booking_id(&booking.booking_id, executor)
+            .await?
+            .iter()
+            .any(|m| m.modification_status == BookingModificationStatus::Pending);
+    if has_pending_modification {
+        return Err(AppError::ValidationError(String::from(
+            "Booking already has a pending modification",
+        )));
+    }
+
+    let quantity = modification.quantity.unwrap_or(booking.quantity);
+    let start_date = modification.start_date.unwrap_or(booking.start_date);
+    let end_date = modification.end_date.unwrap_or(booking.end_date);
+
+    if quantity <= 0 {
+        return Err(AppError::ValidationError(String::from(
+            "Quantity must be greater than 0",
+        )));
+    }
+    if start_date > end_date {
+        return Err(AppError::ValidationError(String::from(
+            "Start date must be before end date",
+        )));
+    }
+
+    let total = match booking.pricing_id {
+        Some(pricing_id) => {
+            if modification.total.is_some() {
+                return Err(AppError::ValidationError(String::from(
+                    "Non-external bookings cannot have a total",
+                )));
+            }
+            let pricing = calculate_price(
+                CalculatePriceRequest {
+                    rental_id: booking.rental_id,
+                    vendor_id: booking.vendor_id,
+                    pricing_id,
+                    start_date,
+                    end_date,
+                    quantity,
+                },
+                executor,
+            )
+            .await?;
+            pricing.total
+        }
+        None => modification.total.unwrap_or(booking.total), // External bookings are priced by the vendor
+    };
+
+    let changes = BookingChanges {
+        quantity,
+        start_date,
+        end_date,
+        total,
+    };
+    if changes.quantity == booking.quantity
+        && changes.start_date == booking.start_date
+        && changes.end_date == booking.end_date
+        && changes.total == booking.total
+    {
+        return Err(AppError::ValidationError(String::from(
+            "Modification does not change the booking",
+        )));
+    }
+
+    check_booking_changes_availability(&booking, &changes, executor).await?;
+
+    let modification_id = create_booking_modification_in_database(
+        &booking,
+        requested_by,
+        requested_by_party,
+        &changes,
+        executor,
+    )
+    .await?;
+
+    // Vendors have already committed inventory to accepted and confirmed bookings, so changes
+    // requested by the renter need their approval. Renters pay the new total, so changes requested
+    // by the vendor always need the renter's approval.
+    let requires_approval = match requested_by_party {
+        BookingParty::Renter => matches!(
+            booking.booking_status,
+            BookingStatus::Accepted | BookingStatus::Confirmed
+        ),
+        BookingParty::Vendor => true,
+    };
+
+    let modification = get_booking_modification_by_modification_id(
+        &booking.booking_id,
+        &modification_id,
+        executor,
+    )
+    .await?;
+
+    // Availability was checked above, so auto-approved changes are applied right away
+    if !requires_approval {
+        return apply_booking_modification(booking, modification, requested_by, state, executor)
+            .await;
+    }
+
+    publish_booking_event(
+        BookingEvent::ModificationRequested {
+            modification: modification.clone(),
+        },
+        executor,
+    )
+    .await?;
+
+    Ok(modification)
+}
+
+#[tracing::instrument(name = "Approve booking modification", skip(state, executor))]
+pub async fn approve_booking_modification<'e>(
+    booking: Booking,
+    modification: BookingModification,
+    resolved_by: &Uuid,
+    resolved_by_party: &BookingParty,
+    state: Arc<AppState>,
+    executor: &mut DbExecutor<'e>,
+) -> Result<BookingModification, AppError> {
+    if modification.modification_status != BookingModificationStatus::Pending {
+        return Err(AppError::ValidationError(String::from(
+            "Only pending modifications can be approved",
+        )));
+    }
+    if *resolved_by_party == modification.requested_by_party {
+        return Err(AppError::ValidationError(String::from(
+            "Modifications must be approved by the other party",
+        )));
+    }
+
+    let changes = BookingChanges {
+        quantity: modification.quantity,
+        start_date: modification.start_date,
+        end_date: modification.end_date,
+        total: modification.total,
+    };
+
+    // Availability may have changed since the modification was requested
+    check_booking_changes_availability(&booking, &changes, executor).await?;
+
+    apply_booking_modification(booking, modification, resolved_by, state, executor).await
+}
+
+// Applies a pending modification whose availability has already been checked
+async fn apply_booking_modification<'e>(
+    booking: Booking,
+    modification: BookingModification,
+    resolved_by: &Uuid,
+    state: Arc<AppState>,
+    executor: &mut DbExecutor<'e>,
+) -> Result<BookingModification, AppError> {
+    let changes = BookingChanges {
+        quantity: modification.quantity,
+        start_date: modification.start_date,
+        end_date: modification.end_date,
+        total: modification.total,
+    };
+
+    update_booking_details_in_database_by_booking_id(&booking.booking_id, &changes, executor)
+        .await?;
+    update_booking_modification_status_in_database_by_modification_id(
+        &modification.modification_id,
+        &BookingModificationStatus::Approved,
+        resolved_by,
+        executor,
+    )
+    .await?;
+
+    let transaction = get_transaction_by_transaction_id(&booking.transaction_id, executor).await?;
+
+    // Requested bookings haven't been charged yet, so they're charged the new total on acceptance
+    if transaction.transaction_type != TransactionType::External
+        && matches!(
+            booking.booking_status,
+            BookingStatus::Accepted | BookingStatus::Confirmed
+        )
+        && modification.price_delta != 0.0
+    {
+        let booking = get_booking_by_booking_id(&booking.booking_id, executor).await?;
+        handle_transaction_booking_settlement(
+            &transaction,
+            &booking,
+            modification.price_delta,
+            &BookingSettlementReason::Modification,
+            state,
+            executor,
+        )
+        .await?;
+    }
+
+    // Confirmed bookings already have a calendar invite that needs updating
+    if booking.booking_status == BookingStatus::Confirmed {
+        increment_booking_calendar_sequence_in_database(&booking.booking_id, executor).await?;
+    }
+
+    let modification = get_booking_modification_by_modification_id(
+        &booking.booking_id,
+        &modification.modification_id,
+        executor,
+    )
+    .await?;
+
+    let booking = get_booking_by_booking_id(&booking.booking_id, executor).await?;
+    publish_booking_event(
+        BookingEvent::Modified {
+            booking,
+            modification: modification.clone(),
+        },
+        executor,
+    )
+    .await?;
+
+    Ok(modification)
+}
+
+#[tracing::instrument(name = "Reject booking modification", skip(executor))]
+pub async fn reject_booking_modification<'e>(
+    modification: BookingModification,
+    resolved_by: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<BookingModification, AppError> {
+    if modification.modification_status != BookingModificationStatus::Pending {
+        return Err(AppError::ValidationError(String::from(
+            "Only pending modifications can be rejected",
+        )));
+    }
+
+    update_booking_modification_status_in_database_by_modification_id(
+        &modification.modification_id,
+        &BookingModificationStatus::Rejected,
+        resolved_by,
+        executor,
+    )
+    .await?;
+
+    let modification = get_booking_modification_by_modification_id(
+        &modification.booking_id,
+        &modification.modification_id,
+        executor,
+    )
+    .await?;
+
+    publish_booking_event(
+        BookingEvent::ModificationRejected {
+            modification: modification.clone(),
+        },
+        executor,
+    )
+    .await?;
+
+    Ok(modification)
+}
+
+async fn check_booking_changes_availability<'e>(
+    booking: &Booking,
+    changes: &BookingChanges,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<Availability>, AppError> {
+    let availability_query: GetAvailabilityQuery = GetAvailabilityQuery {
+        rental_id: booking.rental_id,
+        start_date: changes.start_date,
+        end_date: changes.end_date,
+        exclude_transaction_id: None,
+        exclude_booking_id: Some(booking.booking_id),
+        // Don't consider pending booking holds, only blocked
+        booking_hold_status: Some(BookingHoldStatus::Blocked),
+    };
+
+    check_availability(changes.quantity, availability_query, executor).await
+}
+
+#[tracing::instrument(name = "Get booking modifications by booking id", skip(executor))]
+pub async fn get_booking_modifications_by_booking_id<'e>(
+    booking_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<BookingModification>, AppError> {
+    let modifications =
+        get_booking_modifications_from_database_by_booking_id(booking_id, executor).await?;
+
+    Ok(modifications)
+}
+
+#[tracing::instrument(name = "Get booking modification by modification id", skip(executor))]
+pub async fn get_booking_modification_by_modification_id<'e>(
+    booking_id: &Uuid,
+    modification_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<BookingModification, AppError> {
+    let modification = get_booking_modifications_by_booking_id(booking_id, executor)
+        .await?
+        .into_iter()
+        .find(|m| m.modification_id == *modification_id);
+
+    match modification {
+        None => {
+            tracing::error!(
+                "Booking modification not found for modification id: {}",
+                modification_id
+            );
+            Err(AppError::DoesNotExistError(String::from(
+                "Booking modification not found",
+            )))
+        }
+        Some(modification) => Ok(modification),
+    }
+}
+
+#[tracing::instrument(name = "Dispute booking", skip(executor))]
+pub async fn dispute_booking<'e>(
+    booking: Booking,
+    opened_by: &Uuid,
+    opened_by_party: &BookingParty,
+    dispute: DisputeBooking,
+    executor: &mut DbExecutor<'e>,
+) -> Result<BookingDispute, AppError> {
+    if dispute.reason.trim().is_empty() {
+        return Err(AppError::ValidationError(String::from(
+            "A reason is required to dispute a booking",
+        )));
+    }
+
+    let reason = dispute.reason.clone();
+    create_booking_dispute_in_database(&booking, opened_by, opened_by_party, dispute, executor)
+        .await?;
+    update_booking_status_by_booking_id(
+        &booking.booking_id,
+        &BookingStatus::Disputed,
+        &BookingActor::User {
+            user_id: *opened_by,
+        },
+        Some(reason.as_str()),
+        executor,
+    )
+    .await?;
+
+    let dispute = get_open_booking_dispute_by_booking_id(&booking.booking_id, executor).await?;
+
+    Ok(dispute)
+}
+
+#[tracing::instrument(name = "Resolve booking dispute", skip(state, executor))]
+pub async fn resolve_booking_dispute<'e>(
+    booking: Booking,
+    resolved_by: &Uuid,
+    resolution: ResolveBookingDispute,
+    state: Arc<AppState>,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Booking, AppError> {
+    let dispute = get_open_booking_dispute_by_booking_id(&booking.booking_id, executor).await?;
+
+    match (resolution.resolution, resolution.refund_amount) {
+        (BookingDisputeResolution::PartialRefund, Some(refund_amount)) => {
+            if refund_amount <= 0.0 || refund_amount >= booking.total {
+                return Err(AppError::ValidationError(String::from(
+                    "Partial refund amount must be greater than 0 and less than the booking total",
+                )));
+            }
+        }
+        (BookingDisputeResolution::PartialRefund, None) => {
+            return Err(AppError::ValidationError(String::from(
+                "Partial refunds must have a refund amount",
+            )));
+        }
+        (_, Some(_)) => {
+            return Err(AppError::ValidationError(String::from(
+                "Only partial refunds can have a refund amount",
+            )));
+        }
+        (_, None) => {}
+    }
+
+    let booking_status = match resolution.resolution {
+        BookingDisputeResolution::Canceled => BookingStatus::Canceled,
+        BookingDisputeResolution::Completed | BookingDisputeResolution::PartialRefund => {
+            BookingStatus::Completed
+        }
+    };
+    validate_booking_status_transition(booking.booking_status, booking_status)?;
+
+    let booking = update_booking_status_by_booking_id(
+        &booking.booking_id,
+        &booking_status,
+        &BookingActor::User {
+            user_id: *resolved_by,
+        },
+        resolution.resolution_note.as_deref(),
+        executor,
+    )
+    .await?;
+
+    // The dispute is only marked resolved once the refund or payout went through, all in the
+    // same SQL transaction
+    let transaction = get_transaction_by_transaction_id(&booking.transaction_id, executor).await?;
+    if transaction.transaction_type != TransactionType::External {
+        settle_booking_dispute(
+            &booking,
+            &dispute,
+            &resolution,
+            &transaction,
+            state,
+            executor,
+        )
+        .await?;
+    }
+
+    resolve_booking_dispute_in_database(&dispute.dispute_id, resolved_by, &resolution, executor)
+        .await?;
+
+    Ok(booking)
+}
+
+async fn settle_booking_dispute<'e>(
+    booking: &Booking,
+    dispute: &BookingDispute,
+    resolution: &ResolveBookingDispute,
+    transaction: &Transaction,
+    state: Arc<AppState>,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), AppError> {
+    match resolution.resolution {
+        BookingDisputeResolution::Canceled => {
+            // Disputes resolved by canceling refund the renter in full. The previous status tells
+            // the transactions module whether the vendor was already paid out.
+            handle_transaction_cancel_booking(
+                transaction,
+                booking,
+                &dispute.previous_status,
+                state,
+                executor,
+            )
+            .await?;
+        }
+        BookingDisputeResolution::Completed | BookingDisputeResolution::PartialRefund => {
+            if let Some(refund_amount) = resolution.refund_amount {
+                handle_transaction_booking_settlement(
+                    transaction,
+                    booking,
+                    -refund_amount,
+                    &BookingSettlementReason::DisputeRefund,
+                    state.clone(),
+                    executor,
+                )
+                .await?;
+            }
+
+            // Bookings disputed after completion have already been paid out
+            if dispute.previous_status == BookingStatus::Confirmed {
+                handle_transaction_complete(
+                    &booking.transaction_id,
+                    &booking.vendor_id,
+                    state,
+                    executor,
+                )
+                .await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[tracing::instrument(name = "Get open booking dispute by booking id", skip(executor))]
+pub async fn get_open_booking_dispute_by_booking_id<'e>(
+    booking_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<BookingDispute, AppError> {
+    let dispute = get_booking_dispute_by_booking_id(booking_id, executor).await?;
+
+    if dispute.dispute_status != BookingDisputeStatus::Open {
+        return Err(AppError::DoesNotExistError(String::from(
+            "Open booking dispute not found",
+        )));
+    }
+
+    Ok(dispute)
+}
+
+#[tracing::instrument(name = "Get booking dispute by booking id", skip(executor))]
+pub async fn get_booking_dispute_by_booking_id<'e>(
+    booking_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<BookingDispute, AppError> {
+    let dispute_option =
+        match get_latest_booking_dispute_from_database_by_booking_id(booking_id, executor).await {
+            Err(e) => {
+                tracing::error!("Failed to get booking dispute by booking id: {}", e);
+                return Err(AppError::UnexpectedError(e));
+            }
+            Ok(x) => x,
+        };
+
+    match dispute_option {
+        None => {
+            tracing::error!("Booking dispute not found for booking id: {}", booking_id);
+            Err(AppError::DoesNotExistError(String::from(
+                "Booking dispute not found",
+            )))
+        }
+        Some(dispute) => Ok(dispute),
+    }
+}
+
+#[tracing::instrument(name = "Get booking operator role", skip(executor))]
+pub async fn get_booking_operator_role<'e>(
+    user_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Option<BookingOperatorRole>, AppError> {
+    match get_booking_operator_role_from_database_by_user_id(user_id, executor).await {
+        Err(e) => {
+            tracing::error!("Failed to get booking operator role: {}", e);
+            Err(AppError::UnexpectedError(e))
+        }
+        Ok(role) => Ok(role),
+    }
+}
+
+#[tracing::instrument(name = "Get vendor employees by vendor id", skip(executor))]
+pub async fn get_vendor_employees_by_vendor_id<'e>(
+    vendor_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<BookingVendorEmployee>, AppError> {
+    match get_vendor_employees_from_database_by_vendor_id(vendor_id, executor).await {
+        Err(e) => {
+            tracing::error!("Failed to get vendor employees by vendor id: {}", e);
+            Err(AppError::UnexpectedError(e))
+        }
+        Ok(employees) => Ok(employees),
+    }
+}
+
+#[tracing::instrument(name = "Complete finished bookings", skip(state))]
+pub async fn complete_finished_bookings(state: Arc<AppState>) -> Result<usize, AppError> {
+    let mut executor = DbExecutor::Pool(&state.db_pool);
+    let booking_ids =
+        get_completable_booking_ids_from_database(DEFAULT_COMPLETION_GRACE_HOURS, &mut executor)
+            .await?;
+
+    let actor = BookingActor::System {
+        job: String::from("booking_completion"),
+    };
+    let mut completed = 0;
+    for booking_id in booking_ids.iter() {
+        let mut item_executor = begin_booking_job_item(&state).await?;
+        let result =
+            complete_finished_booking(booking_id, &actor, state.clone(), &mut item_executor).await;
+        if finish_booking_job_item(item_executor, booking_id, result)
+            .await?
+            .is_some()
+        {
+            completed += 1;
+        }
+    }
+
+    Ok(completed)
+}
+
+async fn complete_finished_booking<'e>(
+    booking_id: &Uuid,
+    actor: &BookingActor,
+    state: Arc<AppState>,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Booking, AppError> {
+    let booking = get_booking_by_booking_id(booking_id, executor).await?;
+    validate_booking_status_transition(booking.booking_status, BookingStatus::Completed)?;
+
+    complete_booking(booking, actor, state, executor).await
+}
+
+#[tracing::instrument(name = "Send due booking reminders", skip(state))]
+pub async fn send_due_booking_reminders(state: Arc<AppState>) -> Result<usize, AppError> {
+    let mut executor = DbExecutor::Pool(&state.db_pool);
+    let mut sent = 0;
+
+    for kind in [BookingReminderKind::Start, BookingReminderKind::Return] {
+        let booking_ids = get_due_booking_reminder_ids_from_database(
+            &kind,
+            DEFAULT_REMINDER_LEAD_HOURS,
+            &mut executor,
+        )
+        .await?;
+
+        for booking_id in booking_ids.iter() {
+            let mut item_executor = begin_booking_job_item(&state).await?;
+            let result =
+                send_due_booking_reminder(booking_id, &kind, state.clone(), &mut item_executor)
+                    .await;
+            if let Some(true) = finish_booking_job_item(item_executor, booking_id, result).await? {
+                sent += 1;
+            }
+        }
+    }
+
+    Ok(sent)
+}
+
+// Returns whether the reminder was queued, false when it had already been sent
+async fn send_due_booking_reminder<'e>(
+    booking_id: &Uuid,
+    kind: &BookingReminderKind,
+    state: Arc<AppState>,
+    executor: &mut DbExecutor<'e>,
+) -> Result<bool, AppError> {
+    // Recorded before sending so a reminder is never sent twice
+    let created = create_booking_reminder_in_database(booking_id, kind, executor).await?;
+    if !created {
+        return Ok(false);
+    }
+
+    let booking = get_booking_by_booking_id(booking_id, executor).await?;
+    let transaction = get_transaction_by_transaction_id(&booking.transaction_id, executor).await?;
+    let (user_email, details) = build_booking_email_details(&transaction, executor).await?;
+    let access_link = Some(issue_booking_access_link(&booking, state.clone(), executor).await?);
+    let timezone = get_rental_booking_timezone(&booking.rental_id, executor).await?;
+
+    let notification = match kind {
+        BookingReminderKind::Start => BookingNotification::Reminder {
+            details,
+            access_link,
+            calendar_invite: Some(
+                build_booking_calendar_invite(&[booking], CalendarMethod::Request, executor)
+                    .await?,
+            ),
+        },
+        BookingReminderKind::Return => BookingNotification::ReturnReminder {
+            details,
+            access_link,
+        },
+    };
+    queue_booking_notification(state, user_email, notification, timezone, executor).await?;
+
+    Ok(true)
+}
+
+// Vendors get one email a day ahead listing the next day's pickups and returns to get ready.
+// The next day follows each vendor's timezone, so vendors are grouped by it.
+#[tracing::instrument(name = "Send booking pick lists", skip(state))]
+pub async fn send_booking_pick_lists(state: Arc<AppState>) -> Result<usize, AppError> {
+    let mut executor = DbExecutor::Pool(&state.db_pool);
+    let now = OffsetDateTime::now_utc();
+
+    // The next local day ends less than three days from now in every timezone
+    let vendor_ids =
+        get_pick_list_vendor_ids_from_database(&now, &(now + Duration::days(3)), &mut executor)
+            .await?;
+
+    let mut vendor_ids_by_timezone: HashMap<&str, (&'static Tz, Vec<Uuid>)> = HashMap::new();
+    for vendor_id in vendor_ids {
+        let timezone = get_vendor_booking_timezone(&vendor_id, &mut executor).await?;
+        vendor_ids_by_timezone
+            .entry(timezone.name())
+            .or_insert_with(|| (timezone, Vec::new()))
+            .1
+            .push(vendor_id);
+    }
+
+    let mut sent = 0;
+    for (timezone, vendor_ids) in vendor_ids_by_timezone.values() {
+        let today = align_to_booking_granularity(now, &BookingGranularity::Day, timezone);
+        let pick_date = next_booking_slot(today, &BookingGranularity::Day, timezone);
+        let pick_date_end = next_booking_slot(pick_date, &BookingGranularity::Day, timezone);
+        let due_bookings = get_due_pick_list_booking_ids_from_database(
+            vendor_ids,
+            &pick_date,
+            &pick_date_end,
+            &mut executor,
+        )
+        .await?;
+
+        let mut booking_ids_by_vendor: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+        for (vendor_id, booking_id) in due_bookings {
+            booking_ids_by_vendor
+                .entry(vendor_id)
+                .or_default()
+                .push(booking_id);
+        }
+
+        for (vendor_id, booking_ids) in booking_ids_by_vendor.iter() {
+            let mut item_executor = begin_booking_job_item(&state).await?;
+            let result = send_booking_pick_list(
+                vendor_id,
+                booking_ids,
+                &pick_date,
+                &pick_date_end,
+                state.clone(),
+                &mut item_executor,
+            )
+            .await;
+            if let Some(true) = finish_booking_job_item(item_executor, vendor_id, result).await? {
+                sent += 1;
+            }
+        }
+    }
+
+    Ok(sent)
+}
+
+// Returns whether the pick list was queued, false when it had already been sent for the day
+async fn send_booking_pick_list<'e>(
+    vendor_id: &Uuid,
+    booking_ids: &[Uuid],
+    pick_date: &OffsetDateTime,
+    pick_date_end: &OffsetDateTime,
+    state: Arc<AppState>,
+    executor: &mut DbExecutor<'e>,
+) -> Result<bool, AppError> {
+    let created =
+        create_booking_pick_list_reminder_in_database(vendor_id, pick_date, executor).await?;
+    if !created {
+        return Ok(false);
+    }
+
+    let mut bookings = Vec::new();
+    for booking_id in booking_ids.iter() {
+        bookings.push(get_booking_by_booking_id(booking_id, executor).await?);
+    }
+    let bookings = build_booking_details(bookings, true, false, executor).await?;
+
+    // A booking that starts and ends on the same day is both a pickup and a return
+    let is_on_pick_date = |date: &OffsetDateTime| date >= pick_date && date < pick_date_end;
+    let pickups = bookings
+        .iter()
+        .filter(|booking| is_on_pick_date(&booking.start_date))
+        .cloned()
+        .collect();
+    let returns = bookings
+        .iter()
+        .filter(|booking| is_on_pick_date(&booking.end_date))
+        .cloned()
+        .collect();
+
+    queue_vendor_booking_notification(
+        state,
+        vendor_id,
+        BookingNotification::PickList {
+            pick_date: *pick_date,
+            pickups,
+            returns,
+        },
+        executor,
+    )
+    .await?;
+
+    Ok(true)
+}
+
+#[tracing::instrument(
+    name = "Send vendor booking request notifications",
+    skip(state, executor)
+)]
+pub async fn send_vendor_booking_request_notifications(
+    state: Arc<AppState>,
+) -> Result<usize, AppError> {
+    let mut executor = DbExecutor::Pool(&state.db_pool);
+    let mut sent = 0;
+
+    for kind in [
+        BookingVendorNotificationKind::Request,
+        BookingVendorNotificationKind::ResponseDeadline,
+    ] {
+        let booking_ids = match kind {
+            BookingVendorNotificationKind::Request => {
+                get_unnotified_booking_request_ids_from_database(&mut executor).await?
+            }
+            BookingVendorNotificationKind::ResponseDeadline => {
+                get_due_response_deadline_booking_ids_from_database(
+                    DEFAULT_RESPONSE_DEADLINE_HOURS,
+                    RESPONSE_DEADLINE_WARNING_HOURS,
+                    &mut executor,
+                )
+                .await?
+            }
+        };
+
+        for booking_id in booking_ids.iter() {
+            let mut item_executor = begin_booking_job_item(&state).await?;
+            let result = send_vendor_booking_request_notification(
+                booking_id,
+                &kind,
+                state.clone(),
+                &mut item_executor,
+            )
+            .await;
+            if let Some(true) = finish_booking_job_item(item_executor, booking_id, result).await? {
+                sent += 1;
+            }
+        }
+    }
+
+    Ok(sent)
+}
+
+// Returns whether the notification was queued, false when the vendor had already been notified
+async fn send_vendor_booking_request_notification<'e>(
+    booking_id: &Uuid,
+    kind: &BookingVendorNotificationKind,
+    state: Arc<AppState>,
+    executor: &mut DbExecutor<'e>,
+) -> Result<bool, AppError> {
+    // Recorded before sending so vendors are never notified twice
+    let created =
+        create_booking_vendor_notification_in_database(booking_id, kind, executor).await?;
+    if !created {
+        return Ok(false);
+    }
+
+    let booking = get_booking_by_booking_id(booking_id, executor).await?;
+    let vendor_id = booking.vendor_id;
+    let notification = match kind {
+        BookingVendorNotificationKind::Request => BookingNotification::VendorRequested { booking },
+        BookingVendorNotificationKind::ResponseDeadline => {
+            let settings =
+                get_booking_vendor_settings_by_vendor_id(&booking.vendor_id, executor).await?;
+            let respond_by =
+                booking.created_at + Duration::hours(i64::from(settings.response_deadline_hours));
+            BookingNotification::VendorResponseDeadline {
+                booking,
+                respond_by,
+            }
+        }
+    };
+    queue_vendor_booking_notification(state, &vendor_id, notification, executor).await?;
+
+    Ok(true)
+}
+
+#[tracing::instrument(name = "Import bookings", skip(content, executor))]
+pub async fn import_bookings<'e>(
+    vendor_id: &Uuid,
+    query_params: &ImportBookingsQuery,
+    content: &str,
+    executor: &mut DbExecutor<'e>,
+) -> Result<BookingImportReport, AppError> {
+    let transaction =
+        get_transaction_by_transaction_id(&query_params.transaction_id, executor).await?;
+    if transaction.transaction_type != TransactionType::External {
+        return Err(AppError::ValidationError(String::from(
+            "Bookings can only be imported into an external transaction",
+        )));
+    }
+
+    // Plain dates in the file are days in the vendor's timezone
+    let timezone = get_vendor_booking_timezone(vendor_id, executor).await?;
+    let parsed_rows = match query_params.format {
+        BookingImportFormat::Csv => parse_bookings_csv(
+            content,
+            vendor_id,
+            &query_params.transaction_id,
+            query_params.rental_id,
+            timezone,
+        ),
+        BookingImportFormat::Ics => parse_bookings_ics(
+            content,
+            vendor_id,
+            &query_params.transaction_id,
+            query_params.rental_id,
+            timezone,
+        ),
+    };
+
+    let mut rows = Vec::new();
+    for (index, parsed_row) in parsed_rows.into_iter().enumerate() {
+        let (status, booking_id, message) =
+            match import_booking_row(vendor_id, parsed_row, executor).await? {
+                Ok(booking) => (
+                    BookingImportRowStatus::Created,
+                    Some(booking.booking_id),
+                    None,
+                ),
+                Err((status, message)) => (status, None, Some(message)),
+            };
+        rows.push(BookingImportRow {
+            row: index + 1,
+            status,
+            booking_id,
+            message,
+        });
+    }
+
+    let count =
+        |status: BookingImportRowStatus| rows.iter().filter(|row| row.status == status).count();
+    Ok(BookingImportReport {
+        dry_run: query_params.dry_run == Some(true),
+        created: count(BookingImportRowStatus::Created),
+        skipped: count(BookingImportRowStatus::Skipped),
+        conflicting: count(BookingImportRowStatus::Conflicting),
+        rows,
+    })
+}
+
+// Row problems are reported back instead of failing the import, only database errors are returned
+async fn import_booking_row<'e>(
+    vendor_id: &Uuid,
+    parsed_row: ParsedImportRow,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Result<Booking, (BookingImportRowStatus, String)>, AppError> {
+    let request = match parsed_row {
+        Ok(request) => request,
+        Err(message) => return Ok(Err((BookingImportRowStatus::Skipped, message))),
+    };
+
+    match get_rental_by_rental_id(&request.rental_id, executor).await {
+        Ok(rental) if rental.vendor_id == *vendor_id => {}
+        Ok(_) => {
+            return Ok(Err((
+                BookingImportRowStatus::Skipped,
+                String::from("Rental does not belong to this vendor"),
+            )))
+        }
+        Err(AppError::DoesNotExistError(message)) => {
+            return Ok(Err((BookingImportRowStatus::Skipped, message)))
+        }
+        Err(e) => return Err(e),
+    }
+
+    if let Err(AppError::ValidationError(message)) = validate_booking_request(&request) {
+        return Ok(Err((BookingImportRowStatus::Skipped, message)));
+    }
+
+    // Unlike request_booking, bookings already imported into the transaction count against availability.
+    // This is the row's only availability check, any date it fails on counts as a conflict
+    let availability_query = GetAvailabilityQuery {
+        rental_id: request.rental_id,
+        start_date: request.start_date,
+        end_date: request.end_date,
+        exclude_transaction_id: None,
+        exclude_booking_id: None,
+        booking_hold_status: Some(BookingHoldStatus::Blocked),
+    };
+    match check_availability(request.quantity, availability_query, executor).await {
+        Ok(_) => {}
+        Err(AppError::ValidationError(message)) => {
+            return Ok(Err((BookingImportRowStatus::Conflicting, message)))
+        }
+        Err(e) => return Err(e),
+    }
+
+    let booking = create_requested_booking(request, executor).await?;
+
+    Ok(Ok(booking))
+}
+
+#[tracing::instrument(name = "Get all bookings by query", skip(executor))]
+pub async fn get_bookings_by_query<'e>(
+    query_params: &GetBookingsQuery,
+    executor: &mut DbExecutor<'e>,
+) -> Result<PaginatedResponse<Booking>, AppError> {
+    let mut bookings_response = get_bookings_from_database_by_query(query_params, executor).await?;
+
+    let include_rental = query_params.include_rental == Some(true);
+    let include_availability = query_params.check_availability == Some(true);
+    bookings_response.data = build_booking_details(
+        bookings_response.data,
+        include_rental,
+        include_availability,
+        executor,
+    )
+    .await?;
+
+    Ok(bookings_response)
+}
+
+#[tracing::instrument(name = "Get booking by booking id", skip(executor))]
+pub async fn get_booking_by_booking_id<'e>(
+    booking_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Booking, AppError> {
+    let booking_option = match get_booking_from_database_by_booking_id(booking_id, executor).await {
+        Err(e) => {
+            tracing::error!("Failed to get booking by booking id: {}", e);
+            return Err(AppError::UnexpectedError(e));
+        }
+        Ok(x) => x,
+    };
+
+    let mut booking = match booking_option {
+        None => {
+            tracing::error!("Booking not found for booking id: {}", booking_id);
+            return Err(AppError::DoesNotExistError(String::from(
+                "Booking not found",
+            )));
+        }
+        Some(booking) => booking,
+    };
+
+    let rental_id = booking.rental_id;
+    let rental = get_rental_by_rental_id(&rental_id, executor).await?;
+    booking.rental = Some(rental);
+
+    Ok(booking)
+}
+
+#[tracing::instrument(name = "Update booking by booking id", skip(executor))]
+pub async fn update_booking_status_by_booking_id<'e>(
+    booking_id: &Uuid,
+    booking_status: &BookingStatus,
+    actor: &BookingActor,
+    reason: Option<&str>,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Booking, AppError> {
+    // Callers check the transition against their own earlier read, so it's checked again here
+    // against the status the update is conditional on. Concurrent cancellations, completions or
+    // expiries of the same booking can't all succeed and settle it twice.
+    let previous_status = get_booking_by_booking_id(booking_id, executor)
+        .await?
+        .booking_status;
+    validate_booking_status_transition(previous_status, *booking_status)?;
+
+    let updated = update_booking_status_in_database_by_booking_id(
+        booking_id,
+        &previous_status,
+        booking_status,
+        executor,
+    )
+    .await?;
+    if !updated {
+        return Err(AppError::ValidationError(String::from(
+            "Booking status was changed by another request",
+        )));
+    }
+
+    create_booking_status_event_in_database(
+        booking_id,
+        &previous_status,
+        booking_status,
+        actor,
+        reason,
+        executor,
+    )
+    .await?;
+
+    let booking_new = get_booking_by_booking_id(booking_id, executor).await?;
+
+    publish_booking_event(
+        BookingEvent::StatusChanged {
+            booking: booking_new.clone(),
+            previous_status,
+            actor: actor.clone(),
+        },
+        executor,
+    )
+    .await?;
+
+    Ok(booking_new)
+}
+
+#[tracing::instrument(name = "Get booking status history by booking id", skip(executor))]
+pub async fn get_booking_status_history_by_booking_id<'e>(
+    booking_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<BookingStatusEvent>, AppError> {
+    let events =
+        get_booking_status_events_from_database_by_booking_id(booking_id, executor).await?;
+
+    Ok(events)
+}
+
+// Pending holds belong to checkouts that were never finished
+#[tracing::instrument(name = "Delete stale pending booking holds", skip(state))]
+pub async fn delete_stale_pending_booking_holds(state: Arc<AppState>) -> Result<u64, AppError> {
+    let mut executor = DbExecutor::Pool(&state.db_pool);
+
+    let expired_before =
+        OffsetDateTime::now_utc() - Duration::hours(PENDING_BOOKING_HOLD_RETENTION_HOURS);
+    let deleted = delete_pending_booking_holds_in_database(
+        &expired_before,
+        BOOKING_HOLD_DURATION_MINUTES,
+        &mut executor,
+    )
+    .await?;
+
+    Ok(deleted)
+}
+
+// Gives new pending holds their expiry and announces the ones that ran out, long before the
+// stale hold cleanup deletes them
+#[tracing::instrument(name = "Expire booking holds", skip(state))]
+pub async fn expire_booking_holds(state: Arc<AppState>) -> Result<usize, AppError> {
+    let mut executor = DbExecutor::Pool(&state.db_pool);
+    set_pending_booking_hold_expiries_in_database(BOOKING_HOLD_DURATION_MINUTES, &mut executor)
+        .await?;
+
+    let expired_holds = get_expired_booking_holds_from_database(&mut executor).await?;
+
+    let mut expired = 0;
+    for (booking_hold_id, transaction_id) in expired_holds.iter() {
+        let mut item_executor = begin_booking_job_item(&state).await?;
+        let result =
+            announce_expired_booking_hold(booking_hold_id, transaction_id, &mut item_executor)
+                .await;
+        if let Some(true) = finish_booking_job_item(item_executor, booking_hold_id, result).await? {
+            expired += 1;
+        }
+    }
+
+    Ok(expired)
+}
+
+// Returns whether the event was published, false when the expiry was already announced
+async fn announce_expired_booking_hold<'e>(
+    booking_hold_id: &Uuid,
+    transaction_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<bool, AppError> {
+    let created = create_booking_hold_expiration_in_database(booking_hold_id, executor).await?;
+    if !created {
+        return Ok(false);
+    }
+
+    publish_booking_event(
+        BookingEvent::HoldExpired {
+            booking_hold_id: *booking_hold_id,
+            transaction_id: *transaction_id,
+        },
+        executor,
+    )
+    .await?;
+
+    Ok(true)
+}
+
+#[tracing::instrument(name = "Get booking hold expiry", skip(executor))]
+pub async fn get_booking_hold_expiry<'e>(
+    booking_hold_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<BookingHoldExpiry, AppError> {
+    let (transaction_id, expires_at) = get_booking_hold_expiry_from_database_by_booking_hold_id(
+        booking_hold_id,
+        BOOKING_HOLD_DURATION_MINUTES,
+        executor,
+    )
+    .await?
+    .ok_or(AppError::DoesNotExistError(String::from(
+        "Booking hold does not exist",
+    )))?;
+    let expires_at = expires_at.ok_or(AppError::ValidationError(String::from(
+        "Booking hold does not expire",
+    )))?;
+
+    let remaining_seconds = (expires_at - OffsetDateTime::now_utc())
+        .whole_seconds()
+        .max(0);
+
+    Ok(BookingHoldExpiry {
+        booking_hold_id: *booking_hold_id,
+        transaction_id,
+        expires_at,
+        remaining_seconds,
+    })
+}
+
+#[tracing::instrument(name = "Extend booking hold", skip(executor))]
+pub async fn extend_booking_hold<'e>(
+    booking_hold_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<BookingHoldExpiry, AppError> {
+    let expires_at = OffsetDateTime::now_utc() + Duration::minutes(BOOKING_HOLD_DURATION_MINUTES);
+    let extended = extend_booking_hold_in_database(
+        booking_hold_id,
+        &expires_at,
+        BOOKING_HOLD_DURATION_MINUTES,
+        executor,
+    )
+    .await?;
+    if !extended {
+        return Err(AppError::ValidationError(String::from(
+            "Only pending booking holds that haven't expired can be extended",
+        )));
+    }
+
+    get_booking_hold_expiry(booking_hold_id, executor).await
+}
+
+#[tracing::instrument(name = "Issue booking access link", skip(state, executor))]
+pub async fn issue_booking_access_link<'e>(
+    booking: &Booking,
+    state: Arc<AppState>,
+    executor: &mut DbExecutor<'e>,
+) -> Result<String, AppError> {
+    let token_id = Uuid::new_v4();
+    let expires_at = booking.end_date + Duration::days(BOOKING_ACCESS_TOKEN_VALID_DAYS_AFTER_END);
+    create_booking_access_token_in_database(&token_id, &booking.booking_id, &expires_at, executor)
+        .await?;
+
+    let token = sign_booking_access_token(
+        &token_id,
+        &booking.booking_id,
+        &expires_at,
+        state.configuration.application.hmac_secret.expose_secret(),
+    );
+
+    Ok(format!(
+        "{}/bookings/{}?token={}",
+        state.configuration.client.base_url, booking.booking_id, token
+    ))
+}
+
+#[tracing::instrument(name = "Verify booking access token", skip(token, state, executor))]
+pub async fn verify_booking_access_token<'e>(
+    token: &str,
+    booking_id: &Uuid,
+    state: Arc<AppState>,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Uuid, AppError> {
+    let token_id = verify_booking_access_token_signature(
+        token,
+        booking_id,
+        state.configuration.application.hmac_secret.expose_secret(),
+    )?;
+
+    let active =
+        get_booking_access_token_active_from_database(&token_id, booking_id, executor).await?;
+    if !active {
+        return Err(AppError::ValidationError(String::from(
+            "Booking access token has been revoked",
+        )));
+    }
+
+    Ok(token_id)
+}
+
+// Moves a booking to another transaction, and so to that transaction's renter. Links sent to the
+// previous renter are revoked in the same SQL transaction.
+#[tracing::instrument(name = "Reassign booking", skip(executor))]
+pub async fn reassign_booking<'e>(
+    booking: &Booking,
+    transaction_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Booking, AppError> {
+    if !matches!(
+        booking.booking_status,
+        BookingStatus::Requested | BookingStatus::Accepted | BookingStatus::Confirmed
+    ) {
+        return Err(AppError::ValidationError(format!(
+            "Bookings that are {} can't be reassigned",
+            booking.booking_status
+        )));
+    }
+    if booking.transaction_id == *transaction_id {
+        return Err(AppError::ValidationError(String::from(
+            "Booking already belongs to this transaction",
+        )));
+    }
+
+    update_booking_transaction_id_in_database(&booking.booking_id, transaction_id, executor)
+        .await?;
+    revoke_booking_access_tokens(&booking.booking_id, executor).await?;
+
+    get_booking_by_booking_id(&booking.booking_id, executor).await
+}
+
+// Called when a booking is reassigned so links sent to the previous renter stop working
+#[tracing::instrument(name = "Revoke booking access tokens", skip(executor))]
+pub async fn revoke_booking_access_tokens<'e>(
+    booking_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<u64, AppError> {
+    let revoked = revoke_booking_access_tokens_in_database(booking_id, executor).await?;
+
+    Ok(revoked)
+}
+
+/// Builds a calendar invite with an event for each booking, used by the confirmed,
+/// reminder, updated and canceled emails. Transactions can attach it to the confirmed email.
+#[tracing::instrument(name = "Build booking calendar invite", skip(bookings, executor))]
+pub async fn build_booking_calendar_invite<'e>(
+    bookings: &[Booking],
+    method: CalendarMethod,
+    executor: &mut DbExecutor<'e>,
+) -> Result<CalendarInvite, AppError> {
+    let bookings = build_booking_details(bookings.to_vec(), true, false, executor).await?;
+
+    let mut events = Vec::new();
+    for booking in bookings.iter() {
+        let vendor = get_vendor_by_vendor_id(&booking.vendor_id, executor).await?;
+        let sequence =
+            get_booking_calendar_sequence_from_database(&booking.booking_id, executor).await?;
+        let rental_name = booking
+            .rental
+            .as_ref()
+            .map(|rental| rental.name.clone())
+            .unwrap_or(String::from("Rental"));
+
+        let (settings, timezone) = get_rental_booking_slots(&booking.rental_id, executor).await?;
+        let (start_date, end_date, all_day) =
+            calendar_event_dates(booking, &settings.booking_granularity, timezone);
+
+        events.push(CalendarEvent {
+            uid: format!("{}@bookings", booking.booking_id),
+            sequence,
+            start_date,
+            end_date,
+            all_day,
+            summary: format!("{} x{}", rental_name, booking.quantity),
+            description: Some(format!("Booking {}", booking.booking_id)),
+            location: vendor.address.clone(), // Pickup address
+            organizer_email: Some(vendor.email.clone()),
+            status: match method {
+                CalendarMethod::Cancel => "CANCELLED",
+                _ => "CONFIRMED",
+            },
+        });
+    }
+
+    Ok(CalendarInvite {
+        method,
+        ics: build_calendar(method, None, &events),
+    })
+}
+
+// Only bookings that are still happening show up in calendar feeds
+const CALENDAR_FEED_BOOKING_STATUSES: [BookingStatus; 3] = [
+    BookingStatus::Requested,
+    BookingStatus::Accepted,
+    BookingStatus::Confirmed,
+];
+
+// Reuses the feed's active token so the url stays the same until the vendor revokes it
+#[tracing::instrument(name = "Get booking calendar feed link", skip(state, executor))]
+pub async fn get_booking_calendar_feed_link<'e>(
+    feed: &BookingCalendarFeed,
+    state: Arc<AppState>,
+    executor: &mut DbExecutor<'e>,
+) -> Result<BookingCalendarFeedLink, AppError> {
+    let token_id =
+        match get_active_booking_calendar_feed_token_id_from_database(feed, executor).await? {
+            Some(token_id) => token_id,
+            None => {
+                let token_id = Uuid::new_v4();
+                create_booking_calendar_feed_token_in_database(&token_id, feed, executor).await?;
+                token_id
+            }
+        };
+
+    let token = sign_booking_calendar_feed_token(
+        &token_id,
+        feed,
+        state.configuration.application.hmac_secret.expose_secret(),
+    );
+    // Same base url as the booking access links in emails
+    let base_url = &state.configuration.client.base_url;
+
+    let url = match feed.rental_id {
+        Some(rental_id) => format!(
+            "{}/bookings/calendar/{}/rentals/{}.ics?token={}",
+            base_url, feed.vendor_id, rental_id, token
+        ),
+        None => format!(
+            "{}/bookings/calendar/{}.ics?token={}",
+            base_url, feed.vendor_id, token
+        ),
+    };
+
+    Ok(BookingCalendarFeedLink { url })
+}
+
+#[tracing::instrument(
+    name = "Verify booking calendar feed token",
+    skip(token, state, executor)
+)]
+pub async fn verify_booking_calendar_feed_token<'e>(
+    token: &str,
+    feed: &BookingCalendarFeed,
+    state: Arc<AppState>,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), AppError> {
+    let token_id = verify_booking_calendar_feed_token_signature(
+        feed,
+        token,
+        state.configuration.application.hmac_secret.expose_secret(),
+    )?;
+
+    let active =
+        get_booking_calendar_feed_token_active_from_database(&token_id, feed, executor).await?;
+    if !active {
+        return Err(AppError::ValidationError(String::from(
+            "Calendar feed token has been revoked",
+        )));
+    }
+
+    Ok(())
+}
+
+// Subscribed calendars stop updating, the next feed link gets a new token
+#[tracing::instrument(name = "Revoke booking calendar feed tokens", skip(executor))]
+pub async fn revoke_booking_calendar_feed_tokens<'e>(
+    feed: &BookingCalendarFeed,
+    executor: &mut DbExecutor<'e>,
+) -> Result<u64, AppError> {
+    let revoked = revoke_booking_calendar_feed_tokens_in_database(feed, executor).await?;
+
+    Ok(revoked)
+}
+
+// Changes whenever a booking in the feed is added, removed or updated
+#[tracing::instrument(name = "Get booking calendar feed etag", skip(executor))]
+pub async fn get_booking_calendar_feed_etag<'e>(
+    feed: &BookingCalendarFeed,
+    executor: &mut DbExecutor<'e>,
+) -> Result<String, AppError> {
+    let since = OffsetDateTime::now_utc() - Duration::days(CALENDAR_FEED_HISTORY_DAYS);
+    let (booking_count, last_updated_at) = get_booking_calendar_fingerprint_from_database(
+        feed,
+        &CALENDAR_FEED_BOOKING_STATUSES,
+        &since,
+        executor,
+    )
+    .await?;
+
+    let last_updated_at = last_updated_at
+        .map(|last_updated_at| last_updated_at.unix_timestamp_nanos())
+        .unwrap_or(0);
+
+    Ok(format!("\"{}-{}\"", booking_count, last_updated_at))
+}
+
+#[tracing::instrument(name = "Build booking calendar feed", skip(executor))]
+pub async fn build_booking_calendar_feed<'e>(
+    feed: &BookingCalendarFeed,
+    executor: &mut DbExecutor<'e>,
+) -> Result<String, AppError> {
+    let bookings_query = GetBookingsQuery {
+        vendor_id: Some(feed.vendor_id),
+        rental_id: feed.rental_id,
+        booking_statuses: Some(CALENDAR_FEED_BOOKING_STATUSES.to_vec()),
+        start_date: Some(OffsetDateTime::now_utc() - Duration::days(CALENDAR_FEED_HISTORY_DAYS)),
+        include_rental: Some(true),
+        per_page: Some(10000),
+        ..Default::default()
+    };
+    let bookings = get_bookings_by_query(&bookings_query, executor).await?.data;
+    let vendor = get_vendor_by_vendor_id(&feed.vendor_id, executor).await?;
+
+    // Rentals are usually booked many times, so their slots are only looked up once
+    let mut slots: HashMap<Uuid, (BookingGranularity, &'static Tz)> = HashMap::new();
+    let mut events: Vec<CalendarEvent> = Vec::new();
+    for booking in bookings.iter() {
+        let (granularity, timezone) = match slots.get(&booking.rental_id) {
+            Some(slot) => *slot,
+            None => {
+                let (settings, timezone) =
+                    get_rental_booking_slots(&booking.rental_id, executor).await?;
+                let slot = (settings.booking_granularity, timezone);
+                slots.insert(booking.rental_id, slot);
+                slot
+            }
+        };
+        let rental_name = booking
+            .rental
+            .as_ref()
+            .map(|rental| rental.name.clone())
+            .unwrap_or(String::from("Rental"));
+        // Same sequence as the emailed invites, so clients subscribed to both keep the latest
+        let sequence =
+            get_booking_calendar_sequence_from_database(&booking.booking_id, executor).await?;
+
+        let (start_date, end_date, all_day) = calendar_event_dates(booking, &granularity, timezone);
+
+        events.push(CalendarEvent {
+            uid: format!("{}@bookings", booking.booking_id),
+            sequence,
+            start_date,
+            end_date,
+            all_day,
+            summary: format!(
+                "[{}] {} x{}",
+                booking.booking_status, rental_name, booking.quantity
+            ),
+            description: Some(format!("Booking {}", booking.booking_id)),
+            location: vendor.address.clone(),
+            organizer_email: None,
+            status: calendar_event_status(&booking.booking_status),
+        });
+    }
+
+    Ok(build_calendar(
+        CalendarMethod::Publish,
+        Some("Bookings"),
+        &events,
+    ))
+}
+
+/// Renders a notification and writes it to the outbox with the rest of the caller's
+/// transaction, so it's only delivered if that transaction commits.
+// Called by the email subscriber when a booking is canceled. The renter is told about every
+// cancellation, the vendor only about the ones the renter made.
+#[tracing::instrument(
+    name = "Queue booking cancellation notifications",
+    skip(booking, state, executor)
+)]
+pub async fn queue_booking_cancellation_notifications<'e>(
+    booking: &Booking,
+    previous_status: &BookingStatus,
+    canceled_by: &BookingParty,
+    state: Arc<AppState>,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), AppError> {
+    let transaction = get_transaction_by_transaction_id(&booking.transaction_id, executor).await?;
+    if transaction.transaction_type == TransactionType::External {
+        return Ok(());
+    }
+
+    if *canceled_by == BookingParty::Renter {
+        queue_vendor_booking_notification(
+            state.clone(),
+            &booking.vendor_id,
+            BookingNotification::VendorCanceled {
+                booking: booking.clone(),
+            },
+            executor,
+        )
+        .await?;
+    }
+
+    // Only confirmed bookings were sent a calendar invite that needs canceling
+    let calendar_invite = if *previous_status == BookingStatus::Confirmed {
+        Some(
+            build_booking_calendar_invite(
+                std::slice::from_ref(booking),
+                CalendarMethod::Cancel,
+                executor,
+            )
+            .await?,
+        )
+    } else {
+        None
+    };
+    let (user_email, details) = build_booking_email_details(&transaction, executor).await?;
+    let access_link = Some(issue_booking_access_link(booking, state.clone(), executor).await?);
+    let timezone = get_rental_booking_timezone(&booking.rental_id, executor).await?;
+    queue_booking_notification(
+        state,
+        user_email,
+        BookingNotification::Canceled {
+            details,
+            access_link,
+            calendar_invite,
+        },
+        timezone,
+        executor,
+    )
+    .await
+}
+
+// Called by the email subscriber when a modification needs the other party's approval
+#[tracing::instrument(
+    name = "Queue booking modification requested notification",
+    skip(state, executor)
+)]
+pub async fn queue_booking_modification_requested_notification<'e>(
+    modification: &BookingModification,
+    state: Arc<AppState>,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), AppError> {
+    let booking = get_booking_by_booking_id(&modification.booking_id, executor).await?;
+
+    if modification.requested_by_party == BookingParty::Renter {
+        return queue_vendor_booking_notification(
+            state,
+            &booking.vendor_id,
+            BookingNotification::ModificationRequested {
+                modification: modification.clone(),
+            },
+            executor,
+        )
+        .await;
+    }
+
+    // Changes requested by the vendor go to the renter for approval
+    let transaction = get_transaction_by_transaction_id(&booking.transaction_id, executor).await?;
+    if transaction.transaction_type == TransactionType::External {
+        return Ok(());
+    }
+
+    let (user_email, details) = build_booking_email_details(&transaction, executor).await?;
+    let access_link = Some(issue_booking_access_link(&booking, state.clone(), executor).await?);
+    let timezone = get_rental_booking_timezone(&booking.rental_id, executor).await?;
+    queue_booking_notification(
+        state,
+        user_email,
+        BookingNotification::ModificationProposed {
+            details,
+            access_link,
+            modification: modification.clone(),
+        },
+        timezone,
+        executor,
+    )
+    .await
+}
+
+// Called by the email subscriber when a modification is applied, renters of confirmed bookings
+// get an updated calendar invite
+#[tracing::instrument(
+    name = "Queue booking updated notification",
+    skip(booking, state, executor)
+)]
+pub async fn queue_booking_updated_notification<'e>(
+    booking: &Booking,
+    state: Arc<AppState>,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), AppError> {
+    if booking.booking_status != BookingStatus::Confirmed {
+        return Ok(());
+    }
+    let transaction = get_transaction_by_transaction_id(&booking.transaction_id, executor).await?;
+    if transaction.transaction_type == TransactionType::External {
+        return Ok(());
+    }
+
+    let (user_email, details) = build_booking_email_details(&transaction, executor).await?;
+    let access_link = Some(issue_booking_access_link(booking, state.clone(), executor).await?);
+    let timezone = get_rental_booking_timezone(&booking.rental_id, executor).await?;
+    let calendar_invite = Some(
+        build_booking_calendar_invite(
+            std::slice::from_ref(booking),
+            CalendarMethod::Request,
+            executor,
+        )
+        .await?,
+    );
+    queue_booking_notification(
+        state,
+        user_email,
+        BookingNotification::Updated {
+            details,
+            access_link,
+            calendar_invite,
+        },
+        timezone,
+        executor,
+    )
+    .await
+}
+
+#[tracing::instrument(
+    name = "Queue booking notification",
+    skip(state, recipient, notification, executor),
+    fields(notification = notification.name())
+)]
+pub async fn queue_booking_notification<'e>(
+    state: Arc<AppState>,
+    recipient: UserEmail,
+    notification: BookingNotification,
+    timezone: &Tz,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), AppError> {
+    let base_url = &state.configuration.client.base_url;
+    let enable_emails = &state.configuration.application.enable_emails;
+
+    if !enable_emails {
+        return Ok(());
+    }
+
+    let rendered =
+        render_booking_notification(&notification, &state.email_client.tera, base_url, timezone)?;
+    create_booking_notification_in_outbox(
+        &Uuid::new_v4(),
+        notification.name(),
+        recipient.as_ref(),
+        &rendered,
+        executor,
+    )
+    .await?;
+
+    Ok(())
+}
+
+// Vendor facing notifications go to each of the vendor's employees whose role should get them,
+// or to the vendor's own email when no employee has one of those roles
+#[tracing::instrument(
+    name = "Queue vendor booking notification",
+    skip(state, notification, executor)
+)]
+pub async fn queue_vendor_booking_notification<'e>(
+    state: Arc<AppState>,
+    vendor_id: &Uuid,
+    notification: BookingNotification,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), AppError> {
+    let base_url = &state.configuration.client.base_url;
+    let enable_emails = &state.configuration.application.enable_emails;
+
+    if !enable_emails {
+        return Ok(());
+    }
+
+    let roles = notification.vendor_roles();
+    let employees = get_vendor_employees_by_vendor_id(vendor_id, executor).await?;
+
+    let mut recipients: Vec<String> = Vec::new();
+    for employee in employees {
+        if roles.contains(&employee.role) && !recipients.contains(&employee.email) {
+            recipients.push(employee.email);
+        }
+    }
+    if recipients.is_empty() {
+        let vendor = get_vendor_by_vendor_id(vendor_id, executor).await?;
+        recipients.push(vendor.email);
+    }
+
+    // Rendered once, every recipient gets the same email
+    let timezone = get_vendor_booking_timezone(vendor_id, executor).await?;
+    let rendered =
+        render_booking_notification(&notification, &state.email_client.tera, base_url, timezone)?;
+    for recipient in recipients {
+        let recipient = UserEmail::parse(recipient).map_err(AppError::ValidationError)?;
+        create_booking_notification_in_outbox(
+            &Uuid::new_v4(),
+            notification.name(),
+            recipient.as_ref(),
+            &rendered,
+            executor,
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+// Notifications are claimed, sent and marked one at a time. The claim commits before the email
+// is sent, so no SMTP call runs inside a SQL transaction.
+#[tracing::instrument(name = "Deliver queued booking notifications", skip(state))]
+pub async fn deliver_queued_booking_notifications(
+    state: Arc<AppState>,
+) -> Result<(usize, usize), AppError> {
+    let mut executor = DbExecutor::Pool(&state.db_pool);
+    let notifications =
+        get_due_booking_notifications_from_outbox(NOTIFICATION_DELIVERY_BATCH_SIZE, &mut executor)
+            .await?;
+
+    let (mut delivered, mut failed) = (0, 0);
+    for notification in notifications.iter() {
+        let mut claim_executor = begin_booking_job_item(&state).await?;
+        let claimed = claim_booking_notification_in_outbox(
+            &notification.notification_id,
+            NOTIFICATION_CLAIM_SECONDS as f64,
+            &mut claim_executor,
+        )
+        .await?;
+        claim_executor
+            .commit()
+            .await
+            .context("Failed to commit SQL transaction to claim booking notification.")?;
+        if !claimed {
+            continue;
+        }
+
+        let result = send_booking_notification(state.clone(), notification).await;
+
+        let mut mark_executor = begin_booking_job_item(&state).await?;
+        match result {
+            Ok(_) => {
+                update_booking_notification_delivered_in_outbox(
+                    &notification.notification_id,
+                    &mut mark_executor,
+                )
+                .await?;
+                delivered += 1;
+            }
+            Err(e) => {
+                let attempts = notification.attempts + 1;
+                let notification_status = if attempts >= NOTIFICATION_MAX_ATTEMPTS {
+                    BookingNotificationStatus::Dead
+                } else {
+                    BookingNotificationStatus::Pending
+                };
+                let next_attempt_at =
+                    OffsetDateTime::now_utc() + calculate_notification_retry_delay(attempts);
+
+                update_booking_notification_failed_in_outbox(
+                    &notification.notification_id,
+                    &notification_status,
+                    &next_attempt_at,
+                    &format!("{:?}", e),
+                    &mut mark_executor,
+                )
+                .await?;
+                failed += 1;
+            }
+        }
+        mark_executor
+            .commit()
+            .await
+            .context("Failed to commit SQL transaction to mark booking notification.")?;
+    }
+
+    Ok((delivered, failed))
+}
+
+#[tracing::instrument(name = "Get dead booking notifications", skip(executor))]
+pub async fn get_dead_booking_notifications<'e>(
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<BookingOutboxNotification>, AppError> {
+    let notifications = get_dead_booking_notifications_from_outbox(executor).await?;
+
+    Ok(notifications)
+}
+
+#[tracing::instrument(name = "Replay dead booking notification", skip(executor))]
+pub async fn replay_dead_booking_notification<'e>(
+    notification_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), AppError> {
+    let replayed = replay_dead_booking_notification_in_outbox(notification_id, executor).await?;
+    if !replayed {
+        return Err(AppError::ValidationError(String::from(
+            "Only dead booking notifications can be replayed",
+        )));
+    }
+
+    Ok(())
+}
+
+#[tracing::instrument(name = "Create booking webhook", skip(executor))]
+pub async fn create_booking_webhook<'e>(
+    vendor_id: &Uuid,
+    webhook: CreateBookingWebhook,
+    executor: &mut DbExecutor<'e>,
+) -> Result<BookingWebhook, AppError> {
+    validate_booking_webhook_url(&webhook.url)?;
+    if webhook.events.is_empty() {
+        return Err(AppError::ValidationError(String::from(
+            "Webhooks must subscribe to at least one event",
+        )));
+    }
+    if webhook.events.contains(&BookingWebhookEvent::Ping) {
+        return Err(AppError::ValidationError(String::from(
+            "Webhooks cannot subscribe to ping events",
+        )));
+    }
+
+    let webhook_id = Uuid::new_v4();
+    let secret = generate_booking_webhook_secret();
+    create_booking_webhook_in_database(&webhook_id, vendor_id, &webhook, &secret, executor).await?;
+
+    // The secret is only ever returned here and when it's rotated
+    let mut webhook = get_booking_webhook_by_webhook_id(&webhook_id, executor).await?;
+    webhook.secret = Some(secret);
+
+    Ok(webhook)
+}
+
+// Deliveries are signed with the new secret right away, including retries of earlier events
+#[tracing::instrument(name = "Rotate booking webhook secret", skip(executor))]
+pub async fn rotate_booking_webhook_secret<'e>(
+    webhook: BookingWebhook,
+    executor: &mut DbExecutor<'e>,
+) -> Result<BookingWebhook, AppError> {
+    let secret = generate_booking_webhook_secret();
+    update_booking_webhook_secret_in_database(&webhook.webhook_id, &secret, executor).await?;
+
+    Ok(BookingWebhook {
+        secret: Some(secret),
+        ..webhook
+    })
+}
+
+#[tracing::instrument(name = "Get booking webhooks by vendor id", skip(executor))]
+pub async fn get_booking_webhooks_by_vendor_id<'e>(
+    vendor_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<BookingWebhook>, AppError> {
+    let webhooks = get_booking_webhooks_from_database_by_vendor_id(vendor_id, executor).await?;
+
+    Ok(webhooks)
+}
+
+#[tracing::instrument(name = "Get booking webhook by webhook id", skip(executor))]
+pub async fn get_booking_webhook_by_webhook_id<'e>(
+    webhook_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<BookingWebhook, AppError> {
+    match get_booking_webhook_from_database_by_webhook_id(webhook_id, executor).await? {
+        None => Err(AppError::DoesNotExistError(String::from(
+            "Booking webhook not found",
+        ))),
+        Some(webhook) => Ok(webhook),
+    }
+}
+
+#[tracing::instrument(name = "Delete booking webhook", skip(executor))]
+pub async fn delete_booking_webhook<'e>(
+    webhook_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), AppError> {
+    delete_booking_webhook_in_database(webhook_id, executor).await?;
+
+    Ok(())
+}
+
+#[tracing::instrument(name = "Get booking webhook deliveries", skip(executor))]
+pub async fn get_booking_webhook_deliveries<'e>(
+    webhook_id: &Uuid,
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<BookingWebhookDelivery>, AppError> {
+    let deliveries = get_booking_webhook_deliveries_from_database_by_webhook_id(
+        webhook_id,
+        WEBHOOK_DELIVERY_LOG_SIZE,
+        executor,
+    )
+    .await?;
+
+    Ok(deliveries)
+}
+
+// Queued in the same SQL transaction as the status change, delivered by a booking job
+#[tracing::instrument(name = "Queue booking webhook event", skip(booking, executor))]
+pub async fn queue_booking_webhook_event<'e>(
+    booking: &Booking,
+    event: &BookingWebhookEvent,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), AppError> {
+    let webhook_ids =
+        get_booking_webhook_ids_from_database_by_event(&booking.vendor_id, event, executor).await?;
+    if webhook_ids.is_empty() {
+        return Ok(());
+    }
+
+    let payload = build_booking_webhook_payload(event, Some(booking))?;
+    for webhook_id in webhook_ids.iter() {
+        create_booking_webhook_delivery_in_database(
+            &Uuid::new_v4(),
+            webhook_id,
+            event,
+            &payload,
+            &OffsetDateTime::now_utc(),
+            executor,
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+// Each attempt is logged with a single statement, so no request is made inside a SQL transaction
+#[tracing::instrument(name = "Deliver queued booking webhooks", skip(state))]
+pub async fn deliver_queued_booking_webhooks(
+    state: Arc<AppState>,
+) -> Result<(usize, usize), AppError> {
+    let mut executor = DbExecutor::Pool(&state.db_pool);
+    let deliveries = get_due_booking_webhook_deliveries_from_database(
+        WEBHOOK_DELIVERY_BATCH_SIZE,
+        &mut executor,
+    )
+    .await?;
+    if deliveries.is_empty() {
+        return Ok((0, 0));
+    }
+
+    let (mut delivered, mut failed) = (0, 0);
+    for delivery in deliveries.iter() {
+        let delivery = attempt_booking_webhook_delivery(delivery, &mut executor).await?;
+        if delivery.delivery_status == BookingNotificationStatus::Delivered {
+            delivered += 1;
+        } else {
+            failed += 1;
+        }
+    }
+
+    Ok((delivered, failed))
+}
+
+// Pings are logged like other deliveries. The delivery job only picks one up if it wasn't sent
+// right away, e.g. because the server restarted before ping_booking_webhook ran.
+#[tracing::instrument(name = "Queue booking webhook ping", skip(executor))]
+pub async fn queue_booking_webhook_ping<'e>(
+    webhook: &BookingWebhook,
+    executor: &mut DbExecutor<'e>,
+) -> Result<BookingWebhookDelivery, AppError> {
+    let delivery_id = Uuid::new_v4();
+    let payload = build_booking_webhook_payload(&BookingWebhookEvent::Ping, None)?;
+    let next_attempt_at = OffsetDateTime::now_utc() + Duration::minutes(WEBHOOK_PING_DELAY_MINUTES);
+    create_booking_webhook_delivery_in_database(
+        &delivery_id,
+        &webhook.webhook_id,
+        &BookingWebhookEvent::Ping,
+        &payload,
+        &next_attempt_at,
+        executor,
+    )
+    .await?;
+
+    let delivery = BookingWebhookDelivery {
+        delivery_id,
+        created_at: OffsetDateTime::now_utc(),
+        updated_at: OffsetDateTime::now_utc(),
+        webhook_id: webhook.webhook_id,
+        url: webhook.url.clone(),
+        event: BookingWebhookEvent::Ping,
+        payload,
+        delivery_status: BookingNotificationStatus::Pending,
+        attempts: 0,
+        next_attempt_at,
+        response_status: None,
+        last_error: None,
+        delivered_at: None,
+    };
+
+    Ok(delivery)
+}
+
+// Sends a queued ping right away instead of waiting for the delivery job, so vendors can check
+// their endpoint. Must be called after the ping's SQL transaction is committed.
+#[tracing::instrument(name = "Ping booking webhook", skip(executor))]
+pub async fn ping_booking_webhook<'e>(
+    delivery: &BookingWebhookDelivery,
+    executor: &mut DbExecutor<'e>,
+) -> Result<BookingWebhookDelivery, AppError> {
+    let delivery = attempt_booking_webhook_delivery(delivery, executor).await?;
+
+    Ok(delivery)
+}
+
+// Records the attempt in the delivery log and returns the delivery as it was logged
+async fn attempt_booking_webhook_delivery<'e>(
+    delivery: &BookingWebhookDelivery,
+    executor: &mut DbExecutor<'e>,
+) -> Result<BookingWebhookDelivery, AppError> {
+    let webhook_secret =
+        get_booking_webhook_secret_from_database_by_webhook_id(&delivery.webhook_id, executor)
+            .await?
+            .ok_or(AppError::DoesNotExistError(String::from(
+                "Booking webhook not found",
+            )))?;
+    let attempt = send_booking_webhook(delivery, &webhook_secret).await;
+
+    let attempts = delivery.attempts + 1;
+    let delivery_status = match &attempt.error {
+        None => BookingNotificationStatus::Delivered,
+        Some(_) if attempts >= WEBHOOK_MAX_ATTEMPTS => BookingNotificationStatus::Dead,
+        Some(_) => BookingNotificationStatus::Pending,
+    };
+    let next_attempt_at = OffsetDateTime::now_utc() + calculate_notification_retry_delay(attempts);
+
+    update_booking_webhook_delivery_in_database(
+        &delivery.delivery_id,
+        &delivery_status,
+        attempt.response_status,
+        &next_attempt_at,
+        attempt.error.as_deref(),
+        executor,
+    )
+    .await?;
+
+    let now = OffsetDateTime::now_utc();
+    Ok(BookingWebhookDelivery {
+        updated_at: now,
+        delivery_status,
+        attempts,
+        next_attempt_at,
+        response_status: attempt.response_status,
+        last_error: attempt.error,
+        delivered_at: (delivery_status == BookingNotificationStatus::Delivered).then_some(now),
+        ..delivery.clone()
+    })
+}
+
+#[tracing::instrument(name = "Acquire booking job lease", skip(executor))]
+pub async fn acquire_booking_job_lease<'e>(
+    job_name: &str,
+    instance_id: &Uuid,
+    lease: std::time::Duration,
+    executor: &mut DbExecutor<'e>,
+) -> Result<bool, AppError> {
+    let acquired =
+        acquire_booking_job_lease_in_database(job_name, instance_id, lease.as_secs_f64(), executor)
+            .await?;
+
+    Ok(acquired)
+}
+
+// Returns false when the lease expired and another replica has taken over the job
+#[tracing::instrument(name = "Renew booking job lease", skip(executor))]
+pub async fn renew_booking_job_lease<'e>(
+    job_name: &str,
+    instance_id: &Uuid,
+    lease: std::time::Duration,
+    executor: &mut DbExecutor<'e>,
+) -> Result<bool, AppError> {
+    let renewed =
+        renew_booking_job_lease_in_database(job_name, instance_id, lease.as_secs_f64(), executor)
+            .await?;
+
+    Ok(renewed)
+}
+
+#[tracing::instrument(name = "Finish booking job run", skip(executor))]
+pub async fn finish_booking_job_run<'e>(
+    job_name: &str,
+    instance_id: &Uuid,
+    outcome: &BookingJobOutcome,
+    message: &str,
+    interval: std::time::Duration,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), AppError> {
+    finish_booking_job_run_in_database(
+        job_name,
+        instance_id,
+        outcome,
+        message,
+        interval.as_secs_f64(),
+        executor,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tracing::instrument(name = "Get booking job runs", skip(executor))]
+pub async fn get_booking_job_runs<'e>(
+    executor: &mut DbExecutor<'e>,
+) -> Result<Vec<BookingJobRun>, AppError> {
+    let job_runs = get_booking_job_runs_from_database(executor).await?;
+
+    Ok(job_runs)
+}
+
+// Stores the event in the caller's SQL transaction, subscribers see it once it's dispatched
+#[tracing::instrument(name = "Publish booking event", skip(executor))]
+pub async fn publish_booking_event<'e>(
+    event: BookingEvent,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), AppError> {
+    let payload = serde_json::to_string(&event).context("Failed to serialize booking event")?;
+
+    create_booking_event_in_database(
+        &Uuid::new_v4(),
+        event.name(),
+        event.booking_id(),
+        &payload,
+        executor,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tracing::instrument(name = "Dispatch booking events", skip(events, state))]
+pub async fn dispatch_booking_events(
+    events: &BookingEventBus,
+    state: Arc<AppState>,
+) -> Result<usize, AppError> {
+    let mut executor = DbExecutor::Pool(&state.db_pool);
+    let undispatched = get_undispatched_booking_events_from_database(
+        BOOKING_EVENT_DISPATCH_BATCH_SIZE,
+        &mut executor,
+    )
+    .await?;
+
+    let mut dispatched = 0;
+    let mut failed_booking_ids: Vec<Uuid> = Vec::new();
+    for event in undispatched.iter() {
+        // Later events of a booking wait behind its failed event, other bookings carry on
+        if event
+            .booking_id
+            .is_some_and(|booking_id| failed_booking_ids.contains(&booking_id))
+        {
+            continue;
+        }
+
+        let mut item_executor = begin_booking_job_item(&state).await?;
+        let result = dispatch_booking_event(events, event, state.clone(), &mut item_executor).await;
+        match result {
+            Ok(_) => {
+                item_executor
+                    .commit()
+                    .await
+                    .context("Failed to commit SQL transaction to dispatch booking event.")?;
+                dispatched += 1;
+            }
+            Err(e) => {
+                // Rolls back whatever the subscribers wrote before recording the failure
+                drop(item_executor);
+                tracing::error!(
+                    "Booking event {} failed to dispatch: {:?}",
+                    event.event_id,
+                    e
+                );
+
+                let attempts = event.attempts + 1;
+                let event_status = if attempts >= BOOKING_EVENT_MAX_ATTEMPTS {
+                    BookingEventStatus::Dead
+                } else {
+                    BookingEventStatus::Pending
+                };
+                let next_attempt_at =
+                    OffsetDateTime::now_utc() + calculate_notification_retry_delay(attempts);
+                update_booking_event_failed_in_database(
+                    &event.event_id,
+                    &event_status,
+                    &next_attempt_at,
+                    &format!("{:?}", e),
+                    &mut executor,
+                )
+                .await?;
+
+                if let Some(booking_id) = event.booking_id {
+                    failed_booking_ids.push(booking_id);
+                }
+            }
+        }
+    }
+
+    Ok(dispatched)
+}
+
+async fn dispatch_booking_event<'e>(
+    events: &BookingEventBus,
+    event: &BookingOutboxEvent,
+    state: Arc<AppState>,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(), AppError> {
+    let payload: BookingEvent =
+        serde_json::from_str(&event.payload).context("Failed to deserialize booking event")?;
+
+    events.dispatch(&payload, state, executor).await?;
+    update_booking_event_dispatched_in_database(&event.event_id, executor).await?;
+
+    Ok(())
+}
+
+// Each item of a booking job runs in its own SQL transaction, so a failing item is rolled back on
+// its own and retried on the next run instead of holding back the rest of the batch
+async fn begin_booking_job_item(state: &AppState) -> Result<DbExecutor<'static>, AppError> {
+    let transaction = state
+        .db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+
+    Ok(DbExecutor::Transaction(transaction))
+}
+
+// Commits the item's transaction when it succeeded, otherwise logs the error and drops the
+// transaction to roll it back. Returns None for failed items.
+async fn finish_booking_job_item<T>(
+    executor: DbExecutor<'_>,
+    item_id: &Uuid,
+    result: Result<T, AppError>,
+) -> Result<Option<T>, AppError> {
+    match result {
+        Ok(value) => {
+            executor
+                .commit()
+                .await
+                .context("Failed to commit SQL transaction to run booking job item.")?;
+            Ok(Some(value))
+        }
+        Err(e) => {
+            tracing::error!("Booking job item {} failed: {:?}", item_id, e);
+            Ok(None)
+        }
+    }
 }
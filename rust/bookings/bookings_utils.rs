@@ -1,68 +1,189 @@
-use crate::routes::booking_holds::booking_holds_model::{BookingHold, BookingHoldStatus};
+use crate::routes::booking_holds::booking_holds_model::BookingHoldStatus;
 use crate::routes::bookings::bookings_model::{
-    Availability, Booking, BookingStatus, GetAvailabilityQuery,
+    Booking, BookingActor, BookingBlackoutRecurrence, BookingCalendarFeed, BookingGranularity,
+    BookingOperatorRole, BookingParty, BookingStatus, BookingWebhookEvent, CancellationPolicy,
+    CancellationRefund, CreateBookingBlackout, GetAvailabilityQuery, UpsertCancellationPolicy,
+};
+use crate::routes::bookings::bookings_service::{
+    check_availability, get_booking_operator_role, verify_booking_access_token,
+};
+use crate::routes::rbac::rbac_service::{
+    verify_rbac_user_employee_session, verify_rbac_user_session,
 };
-use crate::routes::bookings::bookings_service::check_availability;
 use crate::routes::rentals::rentals_model::{GetRentalsQuery, Rental};
 use crate::routes::rentals::rentals_service::get_rentals_by_query;
+use crate::routes::transactions::transactions_service::get_transaction_by_transaction_id;
+use crate::session::UserSession;
+use crate::startup::AppState;
 use crate::utilities::database::db_executor::DbExecutor;
 use crate::utilities::errors::AppError;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
 use std::collections::HashMap;
-use time::OffsetDateTime;
+use std::sync::Arc;
+use time::{Date, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
+use time_tz::{timezones, OffsetDateTimeExt, OffsetResult, PrimitiveDateTimeExt, TimeZone, Tz};
 use uuid::Uuid;
 
-pub fn merge_booked_quantities_and_holds(
-    booked_quantities: Vec<(OffsetDateTime, i32)>,
-    booking_holds: Vec<BookingHold>,
-) -> HashMap<OffsetDateTime, (i32, i32)> {
-    let mut merged = HashMap::new();
-
-    // Aggregate booked quantities by date
-    for (date, quantity) in booked_quantities {
-        merged.entry(date).or_insert((0, 0)).0 += quantity;
-    }
-
-    // TODO: Booking holds are meant to lock in a quantity for a user once they've
-    //  gone to the checkout page. Add a countdown timer on that page showing how long they
-    //  have to finalize their request. We can make that time limit 30 minutes.
-    //  Instead of just outright deleting the holds, we can throw away any that are >30 mins
-    //  here in this fn. We should still cleanup pending holds and delete payment intents that are
-    //  older than 24 hours.
-
-    // Aggregate booking holds by date
-    for hold in booking_holds {
-        let start_date = hold.start_date;
-        let end_date = hold.end_date;
-        let per_day_hold = hold.quantity;
-
-        let mut current_date = start_date;
-        while current_date <= end_date {
-            merged.entry(current_date).or_insert((0, 0)).1 += per_day_hold;
-            current_date += time::Duration::days(1);
-        }
+// How long a renter has to confirm or abandon a partially accepted request
+pub const PARTIAL_BOOKING_RESPONSE_HOURS: i64 = 48;
+
+// How long vendors have to answer a booking request unless they configure their own deadline
+pub const DEFAULT_RESPONSE_DEADLINE_HOURS: i32 = 72;
+
+// How long before a request's response deadline vendors are warned it's about to expire
+pub const RESPONSE_DEADLINE_WARNING_HOURS: i32 = 12;
+
+// How long after the end date bookings are completed unless the vendor configures their own grace period
+pub const DEFAULT_COMPLETION_GRACE_HOURS: i32 = 24;
+
+// How long before the start and end dates renters are reminded unless the vendor configures their own
+pub const DEFAULT_REMINDER_LEAD_HOURS: i32 = 24;
+
+// How long after a booking ends its emailed access links keep working
+pub const BOOKING_ACCESS_TOKEN_VALID_DAYS_AFTER_END: i64 = 30;
+
+// Failed booking emails are retried with exponential backoff, then dead lettered
+pub const NOTIFICATION_MAX_ATTEMPTS: i32 = 8;
+pub const NOTIFICATION_RETRY_BASE_SECONDS: i64 = 60;
+pub const NOTIFICATION_RETRY_MAX_SECONDS: i64 = 6 * 60 * 60;
+pub const NOTIFICATION_DELIVERY_BATCH_SIZE: i64 = 50;
+// How long a claimed notification waits before it's retried, in case delivery died mid send
+pub const NOTIFICATION_CLAIM_SECONDS: i64 = 10 * 60;
+
+// Failed webhook deliveries back off like emails, receivers that don't answer in time count as failed
+pub const WEBHOOK_MAX_ATTEMPTS: i32 = 10;
+pub const WEBHOOK_DELIVERY_BATCH_SIZE: i64 = 50;
+pub const WEBHOOK_TIMEOUT_SECONDS: u64 = 10;
+pub const WEBHOOK_DELIVERY_LOG_SIZE: i64 = 100;
+// Pings are sent right away, the delivery job only retries them if that never happened
+pub const WEBHOOK_PING_DELAY_MINUTES: i64 = 5;
+
+// Events dispatched per run of the event dispatch job, the rest wait for the next run. Failed
+// events back off like emails and are dead lettered after too many attempts.
+pub const BOOKING_EVENT_DISPATCH_BATCH_SIZE: i64 = 100;
+pub const BOOKING_EVENT_MAX_ATTEMPTS: i32 = 8;
+
+// How far back calendar feeds include bookings, older ones are dropped from subscribers' calendars
+pub const CALENDAR_FEED_HISTORY_DAYS: i64 = 90;
+
+// How long a hold locks in quantity for a renter on the checkout page, extending resets it
+pub const BOOKING_HOLD_DURATION_MINUTES: i64 = 30;
+
+// How long pending holds from abandoned checkouts are kept after they expire before being deleted
+pub const PENDING_BOOKING_HOLD_RETENTION_HOURS: i64 = 24;
+
+// Rentals are booked by the day unless they configure a finer granularity
+pub const DEFAULT_BOOKING_GRANULARITY: BookingGranularity = BookingGranularity::Day;
+
+// Vendors are in UTC until they set their own IANA timezone
+pub const DEFAULT_BOOKING_TIMEZONE: &str = "UTC";
+
+// Day slots are 23 to 25 hours long around DST changes
+pub const MAX_BOOKING_SLOT_HOURS: i64 = 25;
+
+pub fn parse_booking_timezone(name: &str) -> Result<&'static Tz, AppError> {
+    timezones::get_by_name(name).ok_or(AppError::ValidationError(format!(
+        "{} is not a valid IANA timezone",
+        name
+    )))
+}
+
+// Local times skipped by a DST change are moved to the end of the gap, repeated ones use the
+// earlier offset
+pub fn assume_booking_timezone(local: PrimitiveDateTime, timezone: &Tz) -> OffsetDateTime {
+    match local.assume_timezone(timezone) {
+        OffsetResult::Some(date) | OffsetResult::Ambiguous(date, _) => date,
+        OffsetResult::None => match (local + time::Duration::hours(1)).assume_timezone(timezone) {
+            OffsetResult::Some(date) | OffsetResult::Ambiguous(date, _) => date,
+            OffsetResult::None => local.assume_utc(),
+        },
     }
+}
 
-    merged
+// Rounds a date down to the start of the slot it falls in, slots follow the local wall clock
+pub fn align_to_booking_granularity(
+    date: OffsetDateTime,
+    granularity: &BookingGranularity,
+    timezone: &Tz,
+) -> OffsetDateTime {
+    let local = date.to_timezone(timezone);
+    let hour = match granularity {
+        BookingGranularity::Hour => local.hour(),
+        BookingGranularity::HalfDay => local.hour() - local.hour() % 12,
+        BookingGranularity::Day => 0,
+    };
+
+    let slot_start = PrimitiveDateTime::new(
+        local.date(),
+        Time::from_hms(hour, 0, 0).expect("Slot start is a valid time"),
+    );
+    assume_booking_timezone(slot_start, timezone).to_offset(UtcOffset::UTC)
 }
 
-pub fn calculate_availability_from_merged_bookings(
-    merged_data: HashMap<OffsetDateTime, (i32, i32)>,
-    total_quantity: i32,
-) -> Vec<Availability> {
-    let mut availability: Vec<Availability> = merged_data
-        .into_iter()
-        .map(|(date, (booked, hold))| {
-            let available_quantity = total_quantity - booked - hold;
-            Availability {
-                date,
-                available_quantity,
-            }
-        })
-        .collect();
+// The start of the slot after the given one. Hours are a fixed length, days and half days
+// follow the local calendar so DST changes don't shift them.
+pub fn next_booking_slot(
+    slot: OffsetDateTime,
+    granularity: &BookingGranularity,
+    timezone: &Tz,
+) -> OffsetDateTime {
+    let local_step = match granularity {
+        BookingGranularity::Hour => return slot + time::Duration::hours(1),
+        BookingGranularity::HalfDay => time::Duration::hours(12),
+        BookingGranularity::Day => time::Duration::days(1),
+    };
+
+    let local = slot.to_timezone(timezone);
+    let next = PrimitiveDateTime::new(local.date(), local.time()) + local_step;
+    assume_booking_timezone(next, timezone).to_offset(UtcOffset::UTC)
+}
+
+// The local start of a recurring blackout's nth occurrence. Counting from the first occurrence
+// keeps month ends from drifting, e.g. the 31st stays the 31st after a 30 day month.
+pub fn booking_blackout_occurrence(
+    first: PrimitiveDateTime,
+    recurrence: &BookingBlackoutRecurrence,
+    n: i64,
+) -> Option<PrimitiveDateTime> {
+    let months = match recurrence {
+        BookingBlackoutRecurrence::Daily => return first.checked_add(time::Duration::days(n)),
+        BookingBlackoutRecurrence::Weekly => return first.checked_add(time::Duration::weeks(n)),
+        BookingBlackoutRecurrence::Monthly => n,
+        BookingBlackoutRecurrence::Yearly => n * 12,
+    };
+
+    let month_index =
+        i64::from(first.year()) * 12 + i64::from(u8::from(first.month())) - 1 + months;
+    let year = i32::try_from(month_index.div_euclid(12)).ok()?;
+    let month = Month::try_from(u8::try_from(month_index.rem_euclid(12) + 1).ok()?).ok()?;
+    let day = first.day().min(time::util::days_in_year_month(year, month));
+
+    let date = Date::from_calendar_date(year, month, day).ok()?;
+    Some(PrimitiveDateTime::new(date, first.time()))
+}
 
-    availability.sort_by_key(|entry| entry.date);
+pub fn validate_booking_granularity(
+    granularity: &BookingGranularity,
+    timezone: &Tz,
+    start_date: &OffsetDateTime,
+    end_date: &OffsetDateTime,
+) -> Result<(), AppError> {
+    if align_to_booking_granularity(*start_date, granularity, timezone) != *start_date
+        || align_to_booking_granularity(*end_date, granularity, timezone) != *end_date
+    {
+        return Err(AppError::ValidationError(format!(
+            "Start and end dates must fall on {} boundaries in {}",
+            match granularity {
+                BookingGranularity::Hour => "hour",
+                BookingGranularity::HalfDay => "half day",
+                BookingGranularity::Day => "day",
+            },
+            timezone.name()
+        )));
+    }
 
-    availability
+    Ok(())
 }
 
 pub async fn build_booking_details<'e>(
@@ -115,6 +236,356 @@ pub async fn build_booking_details<'e>(
     Ok(bookings)
 }
 
+/// Verifies the session belongs to either an employee of the booking's vendor or the renter
+/// who made the booking, and returns which side of the booking they are on.
+pub async fn verify_booking_party<'e>(
+    session: &UserSession,
+    booking: &Booking,
+    executor: &mut DbExecutor<'e>,
+) -> Result<BookingParty, AppError> {
+    let is_employee =
+        verify_rbac_user_employee_session(session, &booking.vendor_id, executor).await;
+    match is_employee {
+        Ok(_) => Ok(BookingParty::Vendor),
+        Err(_) => {
+            let transaction =
+                get_transaction_by_transaction_id(&booking.transaction_id, executor).await?;
+            let user_id = &transaction
+                .user_id
+                .ok_or(AppError::DoesNotExistError(String::from(
+                    "Transaction has no user",
+                )))?;
+            verify_rbac_user_session(session, user_id).await?;
+            Ok(BookingParty::Renter)
+        }
+    }
+}
+
+/// Verifies the session belongs to a booking operator with one of the given roles and returns
+/// their user id.
+pub async fn verify_booking_operator_session<'e>(
+    session: &UserSession,
+    roles: &[BookingOperatorRole],
+    executor: &mut DbExecutor<'e>,
+) -> Result<Uuid, AppError> {
+    let user_id = session.id()?.expect("User id not found in session");
+
+    match get_booking_operator_role(&user_id, executor).await? {
+        Some(role) if roles.contains(&role) => Ok(user_id),
+        _ => Err(AppError::ValidationError(String::from(
+            "User is not allowed to perform this booking operation",
+        ))),
+    }
+}
+
+/// Verifies the caller is a party to the booking, either through their session or through a
+/// booking access token from one of the booking emails. Tokens always act as the renter.
+pub async fn verify_booking_access<'e>(
+    session: Option<&UserSession>,
+    token: Option<&str>,
+    booking: &Booking,
+    state: Arc<AppState>,
+    executor: &mut DbExecutor<'e>,
+) -> Result<(BookingParty, BookingActor), AppError> {
+    if let Some(token) = token {
+        let token_id =
+            verify_booking_access_token(token, &booking.booking_id, state, executor).await?;
+        return Ok((BookingParty::Renter, BookingActor::Token { token_id }));
+    }
+
+    let session = session.ok_or(AppError::ValidationError(String::from(
+        "A session or booking access token is required",
+    )))?;
+    let party = verify_booking_party(session, booking, executor).await?;
+    let actor = BookingActor::User {
+        user_id: session.id()?.expect("User id not found in session"),
+    };
+
+    Ok((party, actor))
+}
+
+type HmacSha256 = Hmac<Sha256>;
+
+// Tokens are {token_id}.{expires_at}.{signature}, the signature also covers the booking id
+// so a token only ever works for the booking it was issued for
+pub fn sign_booking_access_token(
+    token_id: &Uuid,
+    booking_id: &Uuid,
+    expires_at: &OffsetDateTime,
+    secret: &str,
+) -> String {
+    let signature = hex::encode(
+        booking_access_token_mac(token_id, booking_id, expires_at.unix_timestamp(), secret)
+            .finalize()
+            .into_bytes(),
+    );
+
+    format!("{}.{}.{}", token_id, expires_at.unix_timestamp(), signature)
+}
+
+/// Checks the token's signature and expiry and returns its token id. Whether the token
+/// has been revoked is checked against the database by the caller.
+pub fn verify_booking_access_token_signature(
+    token: &str,
+    booking_id: &Uuid,
+    secret: &str,
+) -> Result<Uuid, AppError> {
+    let invalid_token = || AppError::ValidationError(String::from("Invalid booking access token"));
+
+    let parts: Vec<&str> = token.split('.').collect();
+    let [token_id, expires_at, signature] = parts[..] else {
+        return Err(invalid_token());
+    };
+    let token_id = Uuid::parse_str(token_id).map_err(|_| invalid_token())?;
+    let expires_at = expires_at.parse::<i64>().map_err(|_| invalid_token())?;
+    let signature = hex::decode(signature).map_err(|_| invalid_token())?;
+
+    booking_access_token_mac(&token_id, booking_id, expires_at, secret)
+        .verify_slice(&signature)
+        .map_err(|_| invalid_token())?;
+
+    if expires_at <= OffsetDateTime::now_utc().unix_timestamp() {
+        return Err(AppError::ValidationError(String::from(
+            "Booking access token has expired",
+        )));
+    }
+
+    Ok(token_id)
+}
+
+fn booking_access_token_mac(
+    token_id: &Uuid,
+    booking_id: &Uuid,
+    expires_at: i64,
+    secret: &str,
+) -> HmacSha256 {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC can take key of any size");
+    mac.update(format!("{}.{}.{}", token_id, booking_id, expires_at).as_bytes());
+    mac
+}
+
+// Doubles the wait after every failed attempt, capped so dead letters don't take days
+pub fn calculate_notification_retry_delay(attempts: i32) -> time::Duration {
+    let exponent = attempts.saturating_sub(1).clamp(0, 16) as u32;
+    let seconds = NOTIFICATION_RETRY_BASE_SECONDS
+        .saturating_mul(2_i64.pow(exponent))
+        .min(NOTIFICATION_RETRY_MAX_SECONDS);
+
+    time::Duration::seconds(seconds)
+}
+
+// Feed tokens are {token_id}.{signature}. They don't expire since calendar clients keep polling
+// the same url, vendors revoke them instead.
+pub fn sign_booking_calendar_feed_token(
+    token_id: &Uuid,
+    feed: &BookingCalendarFeed,
+    secret: &str,
+) -> String {
+    let signature = hex::encode(
+        booking_calendar_feed_mac(token_id, feed, secret)
+            .finalize()
+            .into_bytes(),
+    );
+
+    format!("{}.{}", token_id, signature)
+}
+
+/// Checks the token's signature and returns its token id. Whether the token has been revoked is
+/// checked against the database by the caller.
+pub fn verify_booking_calendar_feed_token_signature(
+    feed: &BookingCalendarFeed,
+    token: &str,
+    secret: &str,
+) -> Result<Uuid, AppError> {
+    let invalid_token = || AppError::ValidationError(String::from("Invalid calendar feed token"));
+
+    let (token_id, signature) = token.split_once('.').ok_or_else(invalid_token)?;
+    let token_id = Uuid::parse_str(token_id).map_err(|_| invalid_token())?;
+    let signature = hex::decode(signature).map_err(|_| invalid_token())?;
+    booking_calendar_feed_mac(&token_id, feed, secret)
+        .verify_slice(&signature)
+        .map_err(|_| invalid_token())?;
+
+    Ok(token_id)
+}
+
+fn booking_calendar_feed_mac(
+    token_id: &Uuid,
+    feed: &BookingCalendarFeed,
+    secret: &str,
+) -> HmacSha256 {
+    let rental_id = feed
+        .rental_id
+        .map(|rental_id| rental_id.to_string())
+        .unwrap_or(String::from("*"));
+
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC can take key of any size");
+    mac.update(format!("calendar.{}.{}.{}", token_id, feed.vendor_id, rental_id).as_bytes());
+    mac
+}
+
+// Calendar feed urls end in {id}.ics so calendar clients recognize them
+pub fn parse_calendar_feed_file(file: &str) -> Result<Uuid, AppError> {
+    file.strip_suffix(".ics")
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .ok_or(AppError::DoesNotExistError(String::from(
+            "Calendar feed does not exist",
+        )))
+}
+
+// Calendar events of day bookings cover the local dates they start and end on, shorter slots
+// are timed events. Returns the start, the exclusive end and whether the event is all day.
+pub fn calendar_event_dates(
+    booking: &Booking,
+    granularity: &BookingGranularity,
+    timezone: &Tz,
+) -> (OffsetDateTime, OffsetDateTime, bool) {
+    let end_date = next_booking_slot(booking.end_date, granularity, timezone);
+
+    (
+        booking.start_date.to_timezone(timezone),
+        end_date.to_timezone(timezone),
+        *granularity == BookingGranularity::Day,
+    )
+}
+
+pub fn calendar_event_status(booking_status: &BookingStatus) -> &'static str {
+    match booking_status {
+        BookingStatus::Confirmed | BookingStatus::Completed => "CONFIRMED",
+        BookingStatus::Requested | BookingStatus::Accepted | BookingStatus::Disputed => "TENTATIVE",
+        _ => "CANCELLED",
+    }
+}
+
+// Statuses that don't map to an event aren't sent to webhooks
+pub fn webhook_event_for_status(booking_status: &BookingStatus) -> Option<BookingWebhookEvent> {
+    match booking_status {
+        BookingStatus::Requested => Some(BookingWebhookEvent::Requested),
+        BookingStatus::Accepted => Some(BookingWebhookEvent::Accepted),
+        BookingStatus::Declined => Some(BookingWebhookEvent::Declined),
+        BookingStatus::Canceled => Some(BookingWebhookEvent::Canceled),
+        BookingStatus::Confirmed => Some(BookingWebhookEvent::Confirmed),
+        BookingStatus::Completed => Some(BookingWebhookEvent::Completed),
+        BookingStatus::Disputed => Some(BookingWebhookEvent::Disputed),
+        _ => None,
+    }
+}
+
+// Each webhook gets its own random secret, two v4 uuids give 244 random bits
+pub fn generate_booking_webhook_secret() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+// Receivers recompute the signature over {timestamp}.{body} with their webhook secret
+pub fn sign_booking_webhook_payload(webhook_secret: &str, timestamp: i64, payload: &str) -> String {
+    let mut mac = HmacSha256::new_from_slice(webhook_secret.as_bytes())
+        .expect("HMAC can take key of any size");
+    mac.update(format!("{}.{}", timestamp, payload).as_bytes());
+    format!(
+        "t={},v1={}",
+        timestamp,
+        hex::encode(mac.finalize().into_bytes())
+    )
+}
+
+pub fn calculate_cancellation_refund(
+    booking: &Booking,
+    canceled_by: &BookingParty,
+    policy: Option<CancellationPolicy>,
+    canceled_at: OffsetDateTime,
+) -> CancellationRefund {
+    // Vendors canceling on the renter always refund everything
+    let policy = match (canceled_by, policy) {
+        (BookingParty::Renter, Some(policy)) => policy,
+        _ => {
+            return CancellationRefund {
+                canceled_by: *canceled_by,
+                policy: None,
+                refund_percent: 100,
+                refund_amount: booking.total,
+            }
+        }
+    };
+
+    let hours_before_start = (booking.start_date - canceled_at).whole_hours();
+    let refund_percent = policy
+        .tiers
+        .iter()
+        .filter(|tier| hours_before_start >= tier.min_hours_before_start)
+        .max_by_key(|tier| tier.min_hours_before_start)
+        .map(|tier| tier.refund_percent)
+        .unwrap_or(0);
+    let refund_amount = (booking.total * refund_percent as f64).round() / 100.0;
+
+    CancellationRefund {
+        canceled_by: *canceled_by,
+        policy: Some(policy),
+        refund_percent,
+        refund_amount,
+    }
+}
+
+pub fn validate_cancellation_policy(policy: &UpsertCancellationPolicy) -> Result<(), AppError> {
+    if policy.tiers.is_empty() {
+        return Err(AppError::ValidationError(String::from(
+            "Cancellation policy must have at least one tier",
+        )));
+    }
+
+    for tier in policy.tiers.iter() {
+        if tier.min_hours_before_start < 0 {
+            return Err(AppError::ValidationError(String::from(
+                "Cancellation policy tiers cannot start after the booking",
+            )));
+        }
+        if !(0..=100).contains(&tier.refund_percent) {
+            return Err(AppError::ValidationError(String::from(
+                "Cancellation policy refund percent must be between 0 and 100",
+            )));
+        }
+    }
+
+    let mut min_hours: Vec<i64> = policy
+        .tiers
+        .iter()
+        .map(|tier| tier.min_hours_before_start)
+        .collect();
+    min_hours.sort();
+    min_hours.dedup();
+    if min_hours.len() != policy.tiers.len() {
+        return Err(AppError::ValidationError(String::from(
+            "Cancellation policy tiers must have distinct hours",
+        )));
+    }
+
+    Ok(())
+}
+
+pub fn validate_booking_blackout(blackout: &CreateBookingBlackout) -> Result<(), AppError> {
+    if blackout.end_date <= blackout.start_date {
+        return Err(AppError::ValidationError(String::from(
+            "Blackout end date must be after its start date",
+        )));
+    }
+
+    if let Some(recur_until) = blackout.recur_until {
+        if blackout.recurrence.is_none() {
+            return Err(AppError::ValidationError(String::from(
+                "Only recurring blackouts can recur until a date",
+            )));
+        }
+        if recur_until <= blackout.start_date {
+            return Err(AppError::ValidationError(String::from(
+                "Blackouts must recur until after their start date",
+            )));
+        }
+    }
+
+    Ok(())
+}
+
 pub fn group_bookings_by_vendor(bookings: &[Booking]) -> HashMap<Uuid, Vec<&Booking>> {
     bookings.iter().fold(HashMap::new(), |mut acc, booking| {
         acc.entry(booking.vendor_id).or_default().push(booking);
@@ -153,7 +624,10 @@ pub fn validate_booking_status_transition(
 ) -> anyhow::Result<(), AppError> {
     match current_status {
         BookingStatus::Requested => match new_status {
-            BookingStatus::Accepted | BookingStatus::Declined | BookingStatus::Canceled => Ok(()),
+            BookingStatus::Accepted
+            | BookingStatus::Declined
+            | BookingStatus::Canceled
+            | BookingStatus::Expired => Ok(()),
             _ => Err(AppError::ValidationError(String::from(
                 "Invalid status transition",
             ))),
@@ -165,16 +639,25 @@ pub fn validate_booking_status_transition(
             ))),
         },
         BookingStatus::Confirmed => match new_status {
+            BookingStatus::Completed | BookingStatus::Canceled | BookingStatus::Disputed => Ok(()),
+            _ => Err(AppError::ValidationError(String::from(
+                "Invalid status transition",
+            ))),
+        },
+        BookingStatus::Completed => match new_status {
+            BookingStatus::Disputed => Ok(()),
+            _ => Err(AppError::ValidationError(String::from(
+                "Invalid status transition",
+            ))),
+        },
+        BookingStatus::Disputed => match new_status {
             BookingStatus::Completed | BookingStatus::Canceled => Ok(()),
             _ => Err(AppError::ValidationError(String::from(
                 "Invalid status transition",
             ))),
         },
-        BookingStatus::Declined
-        | BookingStatus::Canceled
-        | BookingStatus::Completed
-        | BookingStatus::Disputed => Err(AppError::ValidationError(String::from(
-            "Invalid status transition",
-        ))),
+        BookingStatus::Declined | BookingStatus::Canceled | BookingStatus::Expired => Err(
+            AppError::ValidationError(String::from("Invalid status transition")),
+        ),
     }
 }
@@ -0,0 +1,215 @@
+use crate::routes::bookings::bookings_model::{
+    Booking, BookingWebhookDelivery, BookingWebhookEvent, BookingWebhookPayload,
+};
+use crate::routes::bookings::bookings_utils::{
+    sign_booking_webhook_payload, WEBHOOK_TIMEOUT_SECONDS,
+};
+use crate::utilities::errors::AppError;
+use anyhow::Context;
+use reqwest::Url;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+use time::OffsetDateTime;
+
+// Signed JSON payloads posted to the webhook endpoints vendors register. Receivers verify the
+// X-Booking-Signature header, t={timestamp},v1={hex hmac of "{timestamp}.{body}"}, with their secret.
+
+// Outcome of one attempt to deliver a webhook, recorded in the delivery log either way
+pub struct BookingWebhookAttempt {
+    pub response_status: Option<i32>,
+    pub error: Option<String>,
+}
+
+pub fn booking_webhook_event_name(event: &BookingWebhookEvent) -> String {
+    format!("booking.{}", event)
+}
+
+pub fn build_booking_webhook_payload(
+    event: &BookingWebhookEvent,
+    booking: Option<&Booking>,
+) -> Result<String, AppError> {
+    let payload = BookingWebhookPayload {
+        event: booking_webhook_event_name(event),
+        created_at: OffsetDateTime::now_utc(),
+        booking: booking.cloned(),
+    };
+
+    let payload = serde_json::to_string(&payload).context("Failed to serialize webhook payload")?;
+
+    Ok(payload)
+}
+
+// Webhook urls are supplied by vendors, so they must not point at our own network
+pub fn validate_booking_webhook_url(url: &str) -> Result<Url, AppError> {
+    let url = Url::parse(url)
+        .map_err(|_| AppError::ValidationError(String::from("Webhook url is not a valid url")))?;
+    if url.scheme() != "https" {
+        return Err(AppError::ValidationError(String::from(
+            "Webhook url must be an https url",
+        )));
+    }
+
+    let host = url
+        .host_str()
+        .ok_or(AppError::ValidationError(String::from(
+            "Webhook url must have a host",
+        )))?
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_lowercase();
+    let is_public_host = match host.parse::<IpAddr>() {
+        Ok(ip_address) => is_public_ip_address(&ip_address),
+        Err(_) => {
+            host != "localhost" && !host.ends_with(".localhost") && !host.ends_with(".internal")
+        }
+    };
+    if !is_public_host {
+        return Err(AppError::ValidationError(String::from(
+            "Webhook url must point to a public address",
+        )));
+    }
+
+    Ok(url)
+}
+
+// Loopback, private, link-local (which includes cloud metadata endpoints) and other reserved ranges
+fn is_public_ip_address(ip_address: &IpAddr) -> bool {
+    match ip_address {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                || a == 0
+                || (a == 100 && (64..128).contains(&b))
+                || a >= 240)
+        }
+        IpAddr::V6(ip) => {
+            if let Some(ip) = ip.to_ipv4_mapped() {
+                return is_public_ip_address(&IpAddr::V4(ip));
+            }
+            let first_segment = ip.segments()[0];
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                || (first_segment & 0xfe00) == 0xfc00
+                || (first_segment & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+// Checks every address the host resolves to, so a public name can't be pointed at our network
+async fn resolve_booking_webhook_address(url: &Url) -> Result<SocketAddr, String> {
+    let host = url.host_str().ok_or("Webhook url has no host")?;
+    let port = url
+        .port_or_known_default()
+        .ok_or("Webhook url has no port")?;
+    let addresses: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|_| format!("Failed to resolve {}", host))?
+        .collect();
+
+    if addresses.is_empty()
+        || addresses
+            .iter()
+            .any(|address| !is_public_ip_address(&address.ip()))
+    {
+        return Err(format!("{} does not resolve to a public address", host));
+    }
+
+    Ok(addresses[0])
+}
+
+// The client is pinned to the address that was checked, so a second lookup can't return a
+// different one, and redirects aren't followed since they'd skip the check
+fn build_booking_webhook_client(url: &Url, address: SocketAddr) -> Result<reqwest::Client, String> {
+    let mut builder = reqwest::Client::builder()
+        .timeout(Duration::from_secs(WEBHOOK_TIMEOUT_SECONDS))
+        .redirect(reqwest::redirect::Policy::none());
+    if let Some(domain) = url.domain() {
+        builder = builder.resolve(domain, address);
+    }
+
+    builder
+        .build()
+        .map_err(|_| String::from("Failed to build webhook HTTP client"))
+}
+
+#[tracing::instrument(
+    name = "Send booking webhook",
+    skip(delivery, webhook_secret),
+    fields(delivery_id = %delivery.delivery_id)
+)]
+pub async fn send_booking_webhook(
+    delivery: &BookingWebhookDelivery,
+    webhook_secret: &str,
+) -> BookingWebhookAttempt {
+    // Checked again on every attempt, since the url's DNS records can change after it's registered
+    let url = match validate_booking_webhook_url(&delivery.url) {
+        Ok(url) => url,
+        Err(_) => {
+            return BookingWebhookAttempt {
+                response_status: None,
+                error: Some(String::from("Webhook url must be a public https url")),
+            }
+        }
+    };
+    let client = match resolve_booking_webhook_address(&url)
+        .await
+        .and_then(|address| build_booking_webhook_client(&url, address))
+    {
+        Ok(client) => client,
+        Err(error) => {
+            return BookingWebhookAttempt {
+                response_status: None,
+                error: Some(error),
+            }
+        }
+    };
+
+    let signature = sign_booking_webhook_payload(
+        webhook_secret,
+        OffsetDateTime::now_utc().unix_timestamp(),
+        &delivery.payload,
+    );
+
+    let response = client
+        .post(url)
+        .header("Content-Type", "application/json")
+        .header(
+            "X-Booking-Event",
+            booking_webhook_event_name(&delivery.event),
+        )
+        .header("X-Booking-Delivery", delivery.delivery_id.to_string())
+        .header("X-Booking-Signature", signature)
+        .body(delivery.payload.clone())
+        .send()
+        .await;
+
+    // Only 2xx responses count as delivered, anything else is retried
+    match response {
+        Ok(response) if response.status().is_success() => BookingWebhookAttempt {
+            response_status: Some(i32::from(response.status().as_u16())),
+            error: None,
+        },
+        Ok(response) => BookingWebhookAttempt {
+            response_status: Some(i32::from(response.status().as_u16())),
+            error: Some(format!("Receiver responded with {}", response.status())),
+        },
+        // Only the kind of failure is recorded, since the delivery log is shown to vendors
+        Err(e) => BookingWebhookAttempt {
+            response_status: None,
+            error: Some(if e.is_timeout() {
+                String::from("Request timed out")
+            } else if e.is_connect() {
+                String::from("Failed to connect")
+            } else {
+                String::from("Request failed")
+            }),
+        },
+    }
+}
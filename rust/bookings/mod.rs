@@ -1,7 +1,14 @@
+pub mod bookings_calendar;
 pub mod bookings_emails;
+pub mod bookings_events;
 mod bookings_handler;
+pub mod bookings_import;
+pub mod bookings_intervals;
+pub mod bookings_jobs;
+pub mod bookings_locales;
 pub mod bookings_model;
 mod bookings_repo;
 pub mod bookings_router;
 pub mod bookings_service;
 pub mod bookings_utils;
+pub mod bookings_webhooks;